@@ -0,0 +1,3 @@
+-- Drop the statement items table and its index
+DROP INDEX IF EXISTS idx_statement_items_statement;
+DROP TABLE IF EXISTS billing_statement_items;
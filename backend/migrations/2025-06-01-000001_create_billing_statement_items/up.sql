@@ -0,0 +1,18 @@
+-- Create a table for the itemized lines of a billing statement (one row per cost type)
+CREATE TABLE billing_statement_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    billing_statement_id INTEGER NOT NULL,
+    cost_type_id INTEGER NOT NULL,
+    cost_type_name TEXT NOT NULL,  -- Copied so historic statements survive renames
+    allocation_key TEXT NOT NULL,  -- LivingArea, PersonCount, Consumption or EqualShare
+    total_amount REAL NOT NULL,    -- Building total of the cost type
+    total_quantity REAL NOT NULL,  -- Sum of the key quantities of all participants
+    tenant_quantity REAL NOT NULL, -- Key quantity of the tenant
+    share_amount REAL NOT NULL,    -- Resulting share of the tenant
+    position INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (billing_statement_id) REFERENCES billing_statements (id) ON DELETE CASCADE,
+    FOREIGN KEY (cost_type_id) REFERENCES cost_types (id)
+);
+
+-- Index for loading the items of a statement
+CREATE INDEX idx_statement_items_statement ON billing_statement_items (billing_statement_id);
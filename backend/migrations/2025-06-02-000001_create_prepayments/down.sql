@@ -0,0 +1,7 @@
+-- Remove the prepayment columns and tables
+ALTER TABLE billing_statements DROP COLUMN balance_amount;
+ALTER TABLE billing_statements DROP COLUMN prepayments_amount;
+DROP INDEX IF EXISTS idx_prepayments_tenant_period;
+DROP TABLE IF EXISTS prepayments;
+DROP INDEX IF EXISTS idx_prepayment_schedules_tenant;
+DROP TABLE IF EXISTS prepayment_schedules;
@@ -0,0 +1,34 @@
+-- Monthly prepayment (Vorauszahlung) schedule per tenant
+CREATE TABLE prepayment_schedules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    monthly_amount REAL NOT NULL,
+    valid_from DATE NOT NULL,
+    valid_to DATE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_prepayment_schedules_tenant ON prepayment_schedules(tenant_id);
+
+-- Prepayments actually received from a tenant within a billing period
+CREATE TABLE prepayments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    billing_period_id INTEGER NOT NULL,
+    amount REAL NOT NULL,
+    received_date DATE NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
+    FOREIGN KEY (billing_period_id) REFERENCES billing_periods(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_prepayments_tenant_period ON prepayments(tenant_id, billing_period_id);
+
+-- Prepayments deducted on a statement and the resulting balance
+-- (positive balance = Nachzahlung, negative balance = Guthaben)
+ALTER TABLE billing_statements ADD COLUMN prepayments_amount REAL NOT NULL DEFAULT 0;
+ALTER TABLE billing_statements ADD COLUMN balance_amount REAL NOT NULL DEFAULT 0;
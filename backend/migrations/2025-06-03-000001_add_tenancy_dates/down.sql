@@ -0,0 +1,3 @@
+-- Remove the tenancy date range
+ALTER TABLE tenants DROP COLUMN move_out_date;
+ALTER TABLE tenants DROP COLUMN move_in_date;
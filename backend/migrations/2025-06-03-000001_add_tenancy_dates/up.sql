@@ -0,0 +1,3 @@
+-- Tenancy date range of a tenant (NULL = open ended)
+ALTER TABLE tenants ADD COLUMN move_in_date DATE;
+ALTER TABLE tenants ADD COLUMN move_out_date DATE;
@@ -0,0 +1,5 @@
+-- Remove the tariff segment columns
+ALTER TABLE billing_statement_items DROP COLUMN segment_end;
+ALTER TABLE billing_statement_items DROP COLUMN segment_start;
+ALTER TABLE billing_statement_items DROP COLUMN price_per_unit;
+ALTER TABLE billing_statement_items DROP COLUMN tariff_id;
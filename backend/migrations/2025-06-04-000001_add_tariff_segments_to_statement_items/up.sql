@@ -0,0 +1,5 @@
+-- Consumption-based cost types are itemized per tariff segment of the billing period
+ALTER TABLE billing_statement_items ADD COLUMN tariff_id INTEGER REFERENCES tariffs (id);
+ALTER TABLE billing_statement_items ADD COLUMN price_per_unit REAL;
+ALTER TABLE billing_statement_items ADD COLUMN segment_start DATE;
+ALTER TABLE billing_statement_items ADD COLUMN segment_end DATE;
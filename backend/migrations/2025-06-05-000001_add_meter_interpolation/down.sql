@@ -0,0 +1,3 @@
+-- Remove the interpolation settings
+ALTER TABLE billing_statement_items DROP COLUMN consumption_estimated;
+ALTER TABLE meters DROP COLUMN interpolation_method;
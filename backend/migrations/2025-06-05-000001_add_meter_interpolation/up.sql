@@ -0,0 +1,5 @@
+-- Weighting used to estimate meter values at dates without a reading
+ALTER TABLE meters ADD COLUMN interpolation_method TEXT NOT NULL DEFAULT 'linear'; -- linear or seasonal
+
+-- Statement items based on estimated meter values are flagged as such
+ALTER TABLE billing_statement_items ADD COLUMN consumption_estimated BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,9 @@
+-- Remove the heating cost split
+DELETE FROM cost_type_allocations WHERE allocation_method_id IN
+    (SELECT id FROM allocation_methods WHERE name = 'HeatedArea');
+DELETE FROM allocation_methods WHERE name = 'HeatedArea';
+
+ALTER TABLE billing_statement_items DROP COLUMN cost_part_percent;
+ALTER TABLE billing_statement_items DROP COLUMN cost_part;
+ALTER TABLE property_units DROP COLUMN heated_area_m2;
+ALTER TABLE cost_types DROP COLUMN consumption_share_percent;
@@ -0,0 +1,12 @@
+-- Share of a cost type allocated by consumption (HeizkostenV §7/§8), the rest by heated area
+ALTER TABLE cost_types ADD COLUMN consumption_share_percent REAL;
+
+-- Heated area of a unit; falls back to the living area if not set
+ALTER TABLE property_units ADD COLUMN heated_area_m2 REAL;
+
+-- Statement items of a split cost type are itemized per part (consumption or base)
+ALTER TABLE billing_statement_items ADD COLUMN cost_part TEXT;
+ALTER TABLE billing_statement_items ADD COLUMN cost_part_percent REAL;
+
+INSERT INTO allocation_methods (name, description) VALUES
+('HeatedArea', 'Allocate costs based on heated area in square meters');
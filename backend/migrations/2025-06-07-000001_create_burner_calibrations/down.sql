@@ -0,0 +1,4 @@
+-- Remove the burner calibrations
+ALTER TABLE cost_types DROP COLUMN uses_burner_hours;
+ALTER TABLE meters DROP COLUMN burner_stage;
+DROP TABLE burner_calibrations;
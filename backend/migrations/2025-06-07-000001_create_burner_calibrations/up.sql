@@ -0,0 +1,19 @@
+-- Oil consumption per operating hour of a burner stage. A new row starts a new
+-- version; each version applies from its valid_from date until the next one.
+CREATE TABLE burner_calibrations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stage INTEGER NOT NULL CHECK (stage IN (1, 2)),
+    litres_per_hour REAL NOT NULL,
+    valid_from DATE NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (stage, valid_from)
+);
+
+-- Burner stage counted by an operating-hours meter (meter_type 'burner_hours')
+ALTER TABLE meters ADD COLUMN burner_stage INTEGER;
+
+-- Consumption-based cost types whose building consumption is the oil burned,
+-- derived from the burner-hour meters
+ALTER TABLE cost_types ADD COLUMN uses_burner_hours BOOLEAN NOT NULL DEFAULT 0;
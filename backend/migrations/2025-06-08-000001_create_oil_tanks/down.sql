@@ -0,0 +1,4 @@
+-- Remove the oil tank tables
+DROP TABLE oil_tank_readings;
+DROP TABLE oil_deliveries;
+DROP TABLE oil_tanks;
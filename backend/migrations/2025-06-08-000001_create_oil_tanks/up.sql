@@ -0,0 +1,40 @@
+-- Heating oil tank; the fuel cost of its consumption is added to the assigned cost type
+CREATE TABLE oil_tanks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    capacity_litres REAL NOT NULL,
+    cost_type_id INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cost_type_id) REFERENCES cost_types(id) ON DELETE SET NULL
+);
+
+-- Oil deliveries, consumed first in first out
+CREATE TABLE oil_deliveries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tank_id INTEGER NOT NULL,
+    delivery_date DATE NOT NULL,
+    litres REAL NOT NULL,
+    total_price REAL NOT NULL,   -- Invoice amount of the delivery
+    invoice_reference TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tank_id) REFERENCES oil_tanks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_oil_deliveries_tank ON oil_deliveries(tank_id, delivery_date);
+
+-- Tank level readings, taken before any delivery of the same day
+CREATE TABLE oil_tank_readings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tank_id INTEGER NOT NULL,
+    reading_date DATE NOT NULL,
+    litres REAL NOT NULL,
+    reading_type TEXT NOT NULL DEFAULT 'dip', -- dip (dipstick) or gauge
+    notes TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tank_id) REFERENCES oil_tanks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_oil_tank_readings_tank ON oil_tank_readings(tank_id, reading_date);
@@ -0,0 +1,5 @@
+-- Remove the meter hierarchy
+DROP INDEX idx_meters_parent;
+ALTER TABLE meters DROP COLUMN residual_warning_percent;
+ALTER TABLE meters DROP COLUMN residual_property_unit_id;
+ALTER TABLE meters DROP COLUMN parent_meter_id;
@@ -0,0 +1,12 @@
+-- Sub-meters point to the main meter they are installed behind. The consumption of a
+-- main meter minus the sum of its sub-meters is its residual.
+ALTER TABLE meters ADD COLUMN parent_meter_id INTEGER;
+
+-- Unit receiving the residual of a common main meter (e.g. the landlord's flat behind
+-- the main water meter); without it the residual is unmeasured common consumption
+ALTER TABLE meters ADD COLUMN residual_property_unit_id INTEGER;
+
+-- Plausibility limit for the residual in percent of the main meter's consumption
+ALTER TABLE meters ADD COLUMN residual_warning_percent REAL;
+
+CREATE INDEX idx_meters_parent ON meters(parent_meter_id);
@@ -0,0 +1,2 @@
+-- Remove the common consumption flag
+ALTER TABLE billing_statement_items DROP COLUMN common_consumption;
@@ -0,0 +1,3 @@
+-- Statement lines for the consumption of common meters (e.g. Allgemeinstrom), allocated
+-- with the cost type's allocation key instead of the tenants' own meters
+ALTER TABLE billing_statement_items ADD COLUMN common_consumption BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Remove the meter to cost type binding
+DROP TABLE meter_cost_types;
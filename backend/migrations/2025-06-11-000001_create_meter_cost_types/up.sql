@@ -0,0 +1,24 @@
+-- Meters whose consumption is billed under a cost type. The conversion factor turns
+-- meter units into cost type units (e.g. m³ gas to kWh).
+CREATE TABLE meter_cost_types (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    meter_id INTEGER NOT NULL,
+    cost_type_id INTEGER NOT NULL,
+    conversion_factor REAL NOT NULL DEFAULT 1.0,
+    FOREIGN KEY (meter_id) REFERENCES meters(id) ON DELETE CASCADE,
+    FOREIGN KEY (cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE,
+    UNIQUE (meter_id, cost_type_id)
+);
+
+-- Keep existing setups billing as before, as far as the units match. Units are compared
+-- like units_match does: trimmed, lowercase, with m³ and m3 (m² and m2) counting the same.
+INSERT INTO meter_cost_types (meter_id, cost_type_id)
+SELECT meters.id, cost_types.id
+FROM meters, cost_types
+WHERE cost_types.is_consumption_based
+  AND meters.meter_type <> 'burner_hours'
+  AND (
+    cost_types.unit IS NULL
+    OR replace(replace(lower(trim(cost_types.unit)), '³', '3'), '²', '2')
+       = replace(replace(lower(trim(meters.unit)), '³', '3'), '²', '2')
+  );
@@ -0,0 +1,3 @@
+-- Remove the owner occupancy
+ALTER TABLE property_units DROP COLUMN owner_persons;
+ALTER TABLE property_units DROP COLUMN owner_occupied;
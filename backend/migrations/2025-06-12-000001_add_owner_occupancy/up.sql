@@ -0,0 +1,6 @@
+-- Units used by the owner. Days without a tenancy are the owner's own use
+-- (Eigenanteil) instead of vacancy.
+ALTER TABLE property_units ADD COLUMN owner_occupied BOOLEAN NOT NULL DEFAULT 0;
+
+-- Persons living in the unit during the owner's own use
+ALTER TABLE property_units ADD COLUMN owner_persons INTEGER;
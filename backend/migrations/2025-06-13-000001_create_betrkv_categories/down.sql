@@ -0,0 +1,3 @@
+-- Remove the BetrKV catalog
+ALTER TABLE cost_types DROP COLUMN betrkv_category_id;
+DROP TABLE betrkv_categories;
@@ -0,0 +1,34 @@
+-- Operating cost categories of BetrKV § 2. Only allocable (umlagefähige) categories
+-- may be passed on to tenants; statement lines are sorted by position.
+CREATE TABLE betrkv_categories (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    position INTEGER NOT NULL UNIQUE,
+    legal_reference TEXT,
+    name TEXT NOT NULL,
+    allocable BOOLEAN NOT NULL
+);
+
+INSERT INTO betrkv_categories (position, legal_reference, name, allocable) VALUES
+(1, '§ 2 Nr. 1 BetrKV', 'Laufende öffentliche Lasten des Grundstücks (Grundsteuer)', 1),
+(2, '§ 2 Nr. 2 BetrKV', 'Wasserversorgung', 1),
+(3, '§ 2 Nr. 3 BetrKV', 'Entwässerung', 1),
+(4, '§ 2 Nr. 4 BetrKV', 'Heizung', 1),
+(5, '§ 2 Nr. 5 BetrKV', 'Warmwasserversorgung', 1),
+(6, '§ 2 Nr. 6 BetrKV', 'Verbundene Heizungs- und Warmwasserversorgungsanlagen', 1),
+(7, '§ 2 Nr. 7 BetrKV', 'Aufzug', 1),
+(8, '§ 2 Nr. 8 BetrKV', 'Straßenreinigung und Müllbeseitigung', 1),
+(9, '§ 2 Nr. 9 BetrKV', 'Gebäudereinigung und Ungezieferbekämpfung', 1),
+(10, '§ 2 Nr. 10 BetrKV', 'Gartenpflege', 1),
+(11, '§ 2 Nr. 11 BetrKV', 'Beleuchtung', 1),
+(12, '§ 2 Nr. 12 BetrKV', 'Schornsteinreinigung', 1),
+(13, '§ 2 Nr. 13 BetrKV', 'Sach- und Haftpflichtversicherung', 1),
+(14, '§ 2 Nr. 14 BetrKV', 'Hauswart', 1),
+(15, '§ 2 Nr. 15 BetrKV', 'Gemeinschafts-Antennenanlage und Breitbandnetz', 1),
+(16, '§ 2 Nr. 16 BetrKV', 'Einrichtungen für die Wäschepflege', 1),
+(17, '§ 2 Nr. 17 BetrKV', 'Sonstige Betriebskosten', 1),
+(101, '§ 1 Abs. 2 Nr. 2 BetrKV', 'Instandhaltung und Instandsetzung', 0),
+(102, '§ 1 Abs. 2 Nr. 1 BetrKV', 'Verwaltungskosten', 0),
+(103, NULL, 'Sonstige nicht umlagefähige Kosten', 0);
+
+-- BetrKV category of a cost type; cost types without a category are treated as allocable
+ALTER TABLE cost_types ADD COLUMN betrkv_category_id INTEGER;
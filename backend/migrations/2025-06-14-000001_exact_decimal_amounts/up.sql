@@ -0,0 +1,212 @@
+-- Store amounts, prices, meter values and litres as exact decimal text instead of REAL.
+-- SQLite can't change a column's type, so the affected tables are rebuilt. Existing values
+-- were stored as single precision floats and are rounded to their meaningful digits.
+
+CREATE TABLE fixed_costs_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cost_type_id INTEGER NOT NULL,
+    amount TEXT NOT NULL,
+    billing_period_start DATE NOT NULL,
+    billing_period_end DATE NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE
+);
+INSERT INTO fixed_costs_new (id, cost_type_id, amount, billing_period_start, billing_period_end, created_at, updated_at)
+SELECT id, cost_type_id, CAST(ROUND(amount, 2) AS TEXT), billing_period_start, billing_period_end, created_at, updated_at
+FROM fixed_costs;
+DROP TABLE fixed_costs;
+ALTER TABLE fixed_costs_new RENAME TO fixed_costs;
+
+CREATE TABLE tariffs_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cost_type_id INTEGER NOT NULL,
+    price_per_unit TEXT NOT NULL,
+    valid_from DATE NOT NULL,
+    valid_to DATE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE
+);
+INSERT INTO tariffs_new (id, cost_type_id, price_per_unit, valid_from, valid_to, created_at, updated_at)
+SELECT id, cost_type_id, CAST(ROUND(price_per_unit, 6) AS TEXT), valid_from, valid_to, created_at, updated_at
+FROM tariffs;
+DROP TABLE tariffs;
+ALTER TABLE tariffs_new RENAME TO tariffs;
+
+CREATE TABLE meter_readings_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    meter_id INTEGER NOT NULL,
+    reading_date TIMESTAMP NOT NULL,
+    value TEXT NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (meter_id) REFERENCES meters(id) ON DELETE CASCADE
+);
+INSERT INTO meter_readings_new (id, meter_id, reading_date, value, notes, created_at, updated_at)
+SELECT id, meter_id, reading_date, CAST(ROUND(value, 4) AS TEXT), notes, created_at, updated_at
+FROM meter_readings;
+DROP TABLE meter_readings;
+ALTER TABLE meter_readings_new RENAME TO meter_readings;
+CREATE INDEX idx_meter_readings_meter_id ON meter_readings(meter_id);
+CREATE INDEX idx_meter_readings_date ON meter_readings(reading_date);
+
+CREATE TABLE meter_cost_types_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    meter_id INTEGER NOT NULL,
+    cost_type_id INTEGER NOT NULL,
+    conversion_factor TEXT NOT NULL DEFAULT '1',
+    FOREIGN KEY (meter_id) REFERENCES meters(id) ON DELETE CASCADE,
+    FOREIGN KEY (cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE,
+    UNIQUE (meter_id, cost_type_id)
+);
+INSERT INTO meter_cost_types_new (id, meter_id, cost_type_id, conversion_factor)
+SELECT id, meter_id, cost_type_id, CAST(ROUND(conversion_factor, 6) AS TEXT)
+FROM meter_cost_types;
+DROP TABLE meter_cost_types;
+ALTER TABLE meter_cost_types_new RENAME TO meter_cost_types;
+
+CREATE TABLE prepayments_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    billing_period_id INTEGER NOT NULL,
+    amount TEXT NOT NULL,
+    received_date DATE NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
+    FOREIGN KEY (billing_period_id) REFERENCES billing_periods(id) ON DELETE CASCADE
+);
+INSERT INTO prepayments_new (id, tenant_id, billing_period_id, amount, received_date, notes, created_at, updated_at)
+SELECT id, tenant_id, billing_period_id, CAST(ROUND(amount, 2) AS TEXT), received_date, notes, created_at, updated_at
+FROM prepayments;
+DROP TABLE prepayments;
+ALTER TABLE prepayments_new RENAME TO prepayments;
+CREATE INDEX idx_prepayments_tenant_period ON prepayments(tenant_id, billing_period_id);
+
+CREATE TABLE prepayment_schedules_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    monthly_amount TEXT NOT NULL,
+    valid_from DATE NOT NULL,
+    valid_to DATE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+INSERT INTO prepayment_schedules_new (id, tenant_id, monthly_amount, valid_from, valid_to, created_at, updated_at)
+SELECT id, tenant_id, CAST(ROUND(monthly_amount, 2) AS TEXT), valid_from, valid_to, created_at, updated_at
+FROM prepayment_schedules;
+DROP TABLE prepayment_schedules;
+ALTER TABLE prepayment_schedules_new RENAME TO prepayment_schedules;
+CREATE INDEX idx_prepayment_schedules_tenant ON prepayment_schedules(tenant_id);
+
+CREATE TABLE oil_tanks_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    capacity_litres TEXT NOT NULL,
+    cost_type_id INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cost_type_id) REFERENCES cost_types(id) ON DELETE SET NULL
+);
+INSERT INTO oil_tanks_new (id, name, capacity_litres, cost_type_id, created_at, updated_at)
+SELECT id, name, CAST(ROUND(capacity_litres, 3) AS TEXT), cost_type_id, created_at, updated_at
+FROM oil_tanks;
+DROP TABLE oil_tanks;
+ALTER TABLE oil_tanks_new RENAME TO oil_tanks;
+
+CREATE TABLE oil_deliveries_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tank_id INTEGER NOT NULL,
+    delivery_date DATE NOT NULL,
+    litres TEXT NOT NULL,
+    total_price TEXT NOT NULL,   -- Invoice amount of the delivery
+    invoice_reference TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tank_id) REFERENCES oil_tanks(id) ON DELETE CASCADE
+);
+INSERT INTO oil_deliveries_new (id, tank_id, delivery_date, litres, total_price, invoice_reference, created_at, updated_at)
+SELECT id, tank_id, delivery_date, CAST(ROUND(litres, 3) AS TEXT), CAST(ROUND(total_price, 2) AS TEXT), invoice_reference, created_at, updated_at
+FROM oil_deliveries;
+DROP TABLE oil_deliveries;
+ALTER TABLE oil_deliveries_new RENAME TO oil_deliveries;
+CREATE INDEX idx_oil_deliveries_tank ON oil_deliveries(tank_id, delivery_date);
+
+CREATE TABLE oil_tank_readings_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tank_id INTEGER NOT NULL,
+    reading_date DATE NOT NULL,
+    litres TEXT NOT NULL,
+    reading_type TEXT NOT NULL DEFAULT 'dip', -- dip (dipstick) or gauge
+    notes TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tank_id) REFERENCES oil_tanks(id) ON DELETE CASCADE
+);
+INSERT INTO oil_tank_readings_new (id, tank_id, reading_date, litres, reading_type, notes, created_at, updated_at)
+SELECT id, tank_id, reading_date, CAST(ROUND(litres, 3) AS TEXT), reading_type, notes, created_at, updated_at
+FROM oil_tank_readings;
+DROP TABLE oil_tank_readings;
+ALTER TABLE oil_tank_readings_new RENAME TO oil_tank_readings;
+CREATE INDEX idx_oil_tank_readings_tank ON oil_tank_readings(tank_id, reading_date);
+
+CREATE TABLE billing_statements_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    billing_period_id INTEGER NOT NULL,
+    tenant_id INTEGER NOT NULL,
+    total_amount TEXT NOT NULL,
+    generated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    html_content TEXT, -- Store the HTML representation of the statement
+    prepayments_amount TEXT NOT NULL DEFAULT '0',
+    balance_amount TEXT NOT NULL DEFAULT '0',
+    FOREIGN KEY (billing_period_id) REFERENCES billing_periods (id) ON DELETE CASCADE,
+    FOREIGN KEY (tenant_id) REFERENCES tenants (id) ON DELETE CASCADE
+);
+INSERT INTO billing_statements_new (id, billing_period_id, tenant_id, total_amount, generated_at, html_content, prepayments_amount, balance_amount)
+SELECT id, billing_period_id, tenant_id, CAST(ROUND(total_amount, 2) AS TEXT), generated_at, html_content,
+       CAST(ROUND(prepayments_amount, 2) AS TEXT), CAST(ROUND(balance_amount, 2) AS TEXT)
+FROM billing_statements;
+DROP TABLE billing_statements;
+ALTER TABLE billing_statements_new RENAME TO billing_statements;
+CREATE INDEX idx_statements_period_tenant ON billing_statements (billing_period_id, tenant_id);
+
+CREATE TABLE billing_statement_items_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    billing_statement_id INTEGER NOT NULL,
+    cost_type_id INTEGER NOT NULL,
+    cost_type_name TEXT NOT NULL,  -- Copied so historic statements survive renames
+    allocation_key TEXT NOT NULL,  -- LivingArea, PersonCount, Consumption or EqualShare
+    total_amount TEXT NOT NULL,    -- Building total of the cost type
+    total_quantity TEXT NOT NULL,  -- Sum of the key quantities of all participants
+    tenant_quantity TEXT NOT NULL, -- Key quantity of the tenant
+    share_amount TEXT NOT NULL,    -- Resulting share of the tenant
+    position INTEGER NOT NULL DEFAULT 0,
+    tariff_id INTEGER REFERENCES tariffs (id),
+    price_per_unit TEXT,
+    segment_start DATE,
+    segment_end DATE,
+    consumption_estimated BOOLEAN NOT NULL DEFAULT 0,
+    cost_part TEXT,
+    cost_part_percent REAL,
+    common_consumption BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY (billing_statement_id) REFERENCES billing_statements (id) ON DELETE CASCADE,
+    FOREIGN KEY (cost_type_id) REFERENCES cost_types (id)
+);
+INSERT INTO billing_statement_items_new (
+    id, billing_statement_id, cost_type_id, cost_type_name, allocation_key, total_amount, total_quantity,
+    tenant_quantity, share_amount, position, tariff_id, price_per_unit, segment_start, segment_end,
+    consumption_estimated, cost_part, cost_part_percent, common_consumption
+)
+SELECT id, billing_statement_id, cost_type_id, cost_type_name, allocation_key,
+       CAST(ROUND(total_amount, 2) AS TEXT), CAST(ROUND(total_quantity, 6) AS TEXT),
+       CAST(ROUND(tenant_quantity, 6) AS TEXT), CAST(ROUND(share_amount, 2) AS TEXT), position, tariff_id,
+       CAST(ROUND(price_per_unit, 6) AS TEXT), segment_start, segment_end,
+       consumption_estimated, cost_part, cost_part_percent, common_consumption
+FROM billing_statement_items;
+DROP TABLE billing_statement_items;
+ALTER TABLE billing_statement_items_new RENAME TO billing_statement_items;
+CREATE INDEX idx_statement_items_statement ON billing_statement_items (billing_statement_id);
@@ -0,0 +1,7 @@
+-- Remove the fixed cost apportionment columns
+ALTER TABLE billing_statement_items DROP COLUMN apportioned_days;
+ALTER TABLE billing_statement_items DROP COLUMN invoice_days;
+ALTER TABLE billing_statement_items DROP COLUMN invoice_end;
+ALTER TABLE billing_statement_items DROP COLUMN invoice_start;
+ALTER TABLE billing_statement_items DROP COLUMN invoice_amount;
+ALTER TABLE billing_statement_items DROP COLUMN fixed_cost_id;
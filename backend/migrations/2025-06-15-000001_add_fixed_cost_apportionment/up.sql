@@ -0,0 +1,8 @@
+-- Fixed costs are itemized per invoice with the share of the invoice period inside the
+-- billing period (Abgrenzung)
+ALTER TABLE billing_statement_items ADD COLUMN fixed_cost_id INTEGER;
+ALTER TABLE billing_statement_items ADD COLUMN invoice_amount TEXT;
+ALTER TABLE billing_statement_items ADD COLUMN invoice_start DATE;
+ALTER TABLE billing_statement_items ADD COLUMN invoice_end DATE;
+ALTER TABLE billing_statement_items ADD COLUMN invoice_days INTEGER;
+ALTER TABLE billing_statement_items ADD COLUMN apportioned_days INTEGER;
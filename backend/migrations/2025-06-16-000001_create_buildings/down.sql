@@ -0,0 +1,33 @@
+-- Billing periods go back to the first unit of their building
+CREATE TABLE billing_periods_old (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    property_unit_id INTEGER NOT NULL,
+    start_date TEXT NOT NULL, -- ISO 8601 format (YYYY-MM-DD)
+    end_date TEXT NOT NULL,   -- ISO 8601 format (YYYY-MM-DD)
+    name TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (property_unit_id) REFERENCES property_units (id) ON DELETE CASCADE,
+    CHECK (start_date <= end_date)
+);
+INSERT INTO billing_periods_old (id, property_unit_id, start_date, end_date, name, created_at, updated_at)
+SELECT id,
+       COALESCE((SELECT MIN(property_units.id) FROM property_units
+                 WHERE property_units.building_id = billing_periods.building_id), 0),
+       start_date, end_date, name, created_at, updated_at
+FROM billing_periods;
+DROP TABLE billing_periods;
+ALTER TABLE billing_periods_old RENAME TO billing_periods;
+CREATE INDEX idx_billing_periods_property_unit ON billing_periods (property_unit_id);
+
+DROP INDEX idx_burner_calibrations_building;
+DROP INDEX idx_cost_types_building;
+DROP INDEX idx_meters_building;
+DROP INDEX idx_property_units_building;
+
+ALTER TABLE burner_calibrations DROP COLUMN building_id;
+ALTER TABLE cost_types DROP COLUMN building_id;
+ALTER TABLE meters DROP COLUMN building_id;
+ALTER TABLE property_units DROP COLUMN building_id;
+
+DROP TABLE buildings;
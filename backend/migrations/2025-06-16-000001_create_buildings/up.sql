@@ -0,0 +1,72 @@
+-- Buildings (Abrechnungseinheit) owning their units, meters, cost types and billing periods.
+-- The costs of a billing period are computed for the whole building and split across its units.
+CREATE TABLE buildings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    address TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Existing data belongs to a first building
+INSERT INTO buildings (id, name) VALUES (1, 'Haus');
+
+ALTER TABLE property_units ADD COLUMN building_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE meters ADD COLUMN building_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE cost_types ADD COLUMN building_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE burner_calibrations ADD COLUMN building_id INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX idx_property_units_building ON property_units (building_id);
+CREATE INDEX idx_meters_building ON meters (building_id);
+CREATE INDEX idx_cost_types_building ON cost_types (building_id);
+CREATE INDEX idx_burner_calibrations_building ON burner_calibrations (building_id, stage, valid_from);
+
+-- Billing periods belong to the building instead of a single unit
+-- The per-unit periods of the same dates become one period of the building,
+-- their statements and prepayments move to the period that is kept
+CREATE TEMPORARY TABLE billing_period_merges AS
+SELECT billing_periods.id AS old_id,
+       (SELECT MIN(kept.id) FROM billing_periods AS kept
+        WHERE kept.start_date = billing_periods.start_date
+          AND kept.end_date = billing_periods.end_date) AS new_id
+FROM billing_periods;
+UPDATE billing_statements SET billing_period_id =
+    (SELECT new_id FROM billing_period_merges WHERE old_id = billing_statements.billing_period_id)
+WHERE billing_period_id IN (SELECT old_id FROM billing_period_merges WHERE old_id <> new_id);
+UPDATE prepayments SET billing_period_id =
+    (SELECT new_id FROM billing_period_merges WHERE old_id = prepayments.billing_period_id)
+WHERE billing_period_id IN (SELECT old_id FROM billing_period_merges WHERE old_id <> new_id);
+DELETE FROM billing_periods WHERE id IN (SELECT old_id FROM billing_period_merges WHERE old_id <> new_id);
+DROP TABLE billing_period_merges;
+
+-- Periods that overlap without sharing their dates would bill the building twice and
+-- can't be merged automatically, the migration stops so they can be corrected first
+CREATE TEMPORARY TABLE billing_period_overlaps (
+    overlapping INTEGER NOT NULL,
+    CONSTRAINT overlapping_billing_periods_must_be_corrected_before_migrating CHECK (overlapping = 0)
+);
+INSERT INTO billing_period_overlaps (overlapping)
+SELECT COUNT(*) FROM billing_periods AS a
+JOIN billing_periods AS b ON a.id < b.id AND a.start_date <= b.end_date AND b.start_date <= a.end_date;
+DROP TABLE billing_period_overlaps;
+
+CREATE TABLE billing_periods_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    building_id INTEGER NOT NULL,
+    start_date TEXT NOT NULL, -- ISO 8601 format (YYYY-MM-DD)
+    end_date TEXT NOT NULL,   -- ISO 8601 format (YYYY-MM-DD)
+    name TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (building_id) REFERENCES buildings (id) ON DELETE CASCADE,
+    CHECK (start_date <= end_date)
+);
+INSERT INTO billing_periods_new (id, building_id, start_date, end_date, name, created_at, updated_at)
+SELECT billing_periods.id, COALESCE(property_units.building_id, 1), billing_periods.start_date,
+       billing_periods.end_date, billing_periods.name, billing_periods.created_at, billing_periods.updated_at
+FROM billing_periods
+LEFT JOIN property_units ON property_units.id = billing_periods.property_unit_id;
+DROP TABLE billing_periods;
+ALTER TABLE billing_periods_new RENAME TO billing_periods;
+
+CREATE INDEX idx_billing_periods_building ON billing_periods (building_id);
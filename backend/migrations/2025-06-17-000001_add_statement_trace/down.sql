@@ -0,0 +1,2 @@
+-- Remove the calculation trace of statements
+ALTER TABLE billing_statements DROP COLUMN calculation_trace;
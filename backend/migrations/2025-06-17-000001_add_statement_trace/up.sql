@@ -0,0 +1,2 @@
+-- Calculation trace (Rechenweg) of the statement as JSON, to verify the numbers later
+ALTER TABLE billing_statements ADD COLUMN calculation_trace TEXT;
@@ -0,0 +1,3 @@
+-- Remove the batch generation jobs
+ALTER TABLE billing_statements DROP COLUMN billing_job_id;
+DROP TABLE billing_jobs;
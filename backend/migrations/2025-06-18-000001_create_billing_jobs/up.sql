@@ -0,0 +1,18 @@
+-- Batch runs generating the statements of all tenants of a billing period
+CREATE TABLE billing_jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    billing_period_id INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending', -- pending, running, completed or failed
+    total_tenants INTEGER NOT NULL DEFAULT 0,
+    processed_tenants INTEGER NOT NULL DEFAULT 0,
+    errors TEXT,         -- One problem per line if the job failed
+    owner_overview TEXT, -- JSON of the owner's share and vacancy account
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    finished_at TEXT,
+    FOREIGN KEY (billing_period_id) REFERENCES billing_periods (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_billing_jobs_period ON billing_jobs (billing_period_id);
+
+-- Statements remember the job that generated them
+ALTER TABLE billing_statements ADD COLUMN billing_job_id INTEGER;
@@ -0,0 +1,5 @@
+-- Remove the heating systems and the split statement items
+ALTER TABLE billing_statement_items DROP COLUMN source_share_percent;
+ALTER TABLE billing_statement_items DROP COLUMN source_cost_type_name;
+ALTER TABLE billing_statement_items DROP COLUMN source_cost_type_id;
+DROP TABLE heating_systems;
@@ -0,0 +1,31 @@
+-- Central boiler producing space heat and hot water (verbundene Anlage, HeizkostenV § 9).
+-- The costs of the fuel cost type are split into the heating and the hot water cost type
+-- before either is allocated.
+CREATE TABLE heating_systems (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    building_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    fuel_cost_type_id INTEGER NOT NULL,
+    heating_cost_type_id INTEGER NOT NULL,
+    hot_water_cost_type_id INTEGER NOT NULL,
+    fuel_meter_id INTEGER,                  -- Fuel counter, e.g. a gas meter; otherwise burner hours or oil tanks
+    hot_water_heat_meter_id INTEGER,        -- Heat meter (kWh) of the hot water; otherwise the § 9 formula
+    hot_water_temperature REAL NOT NULL DEFAULT 60, -- tw in °C for the formula
+    calorific_value REAL NOT NULL,          -- Hi in kWh per fuel unit, e.g. 10 kWh per litre of oil
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (building_id) REFERENCES buildings(id),
+    FOREIGN KEY (fuel_cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE,
+    FOREIGN KEY (heating_cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE,
+    FOREIGN KEY (hot_water_cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE,
+    FOREIGN KEY (fuel_meter_id) REFERENCES meters(id) ON DELETE SET NULL,
+    FOREIGN KEY (hot_water_heat_meter_id) REFERENCES meters(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_heating_systems_building ON heating_systems (building_id);
+CREATE UNIQUE INDEX idx_heating_systems_fuel_cost_type ON heating_systems (fuel_cost_type_id);
+
+-- Statement items split off another cost type, e.g. the hot water part of the fuel costs
+ALTER TABLE billing_statement_items ADD COLUMN source_cost_type_id INTEGER;
+ALTER TABLE billing_statement_items ADD COLUMN source_cost_type_name TEXT;
+ALTER TABLE billing_statement_items ADD COLUMN source_share_percent TEXT;
@@ -0,0 +1,4 @@
+-- Remove degree-day weighting
+DROP TABLE degree_day_shares;
+
+ALTER TABLE cost_types DROP COLUMN degree_day_weighting;
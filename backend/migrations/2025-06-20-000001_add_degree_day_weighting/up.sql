@@ -0,0 +1,15 @@
+-- Heating cost types prorate tenancies and estimate meter values by degree days (Gradtagzahlen)
+-- instead of calendar days
+ALTER TABLE cost_types ADD COLUMN degree_day_weighting BOOLEAN NOT NULL DEFAULT 0;
+
+-- Monthly degree-day shares of a building replacing the built-in standard table,
+-- e.g. from the local weather station. Either all twelve months are set or none.
+CREATE TABLE degree_day_shares (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    building_id INTEGER NOT NULL,
+    month INTEGER NOT NULL,  -- 1 (January) to 12 (December)
+    per_mille REAL NOT NULL, -- Share of the annual heating demand in per mille
+    FOREIGN KEY (building_id) REFERENCES buildings(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_degree_day_shares_building_month ON degree_day_shares (building_id, month);
@@ -0,0 +1,9 @@
+-- Remove the CO2 cost split
+ALTER TABLE billing_statement_items DROP COLUMN co2_landlord_amount;
+ALTER TABLE billing_statement_items DROP COLUMN co2_cost;
+
+ALTER TABLE fixed_costs DROP COLUMN co2_cost;
+ALTER TABLE fixed_costs DROP COLUMN co2_emission_factor;
+ALTER TABLE fixed_costs DROP COLUMN fuel_quantity;
+
+ALTER TABLE cost_types DROP COLUMN co2_emission_factor;
@@ -0,0 +1,12 @@
+-- CO2 levy in fuel costs, split between landlord and tenants by the building's emissions (CO2KostAufG).
+-- Emission factor of the fuel in kg CO2 per unit (litre, m³, kWh); set on fuel cost types only.
+ALTER TABLE cost_types ADD COLUMN co2_emission_factor REAL;
+
+-- Fuel invoices state the quantity delivered, its emission factor and the CO2 costs contained
+ALTER TABLE fixed_costs ADD COLUMN fuel_quantity TEXT;
+ALTER TABLE fixed_costs ADD COLUMN co2_emission_factor REAL; -- Overrides the cost type's factor
+ALTER TABLE fixed_costs ADD COLUMN co2_cost TEXT;             -- Otherwise priced with the BEHG CO2 price
+
+-- Tenant's part of the CO2 costs in a statement item and the landlord's part taken off it
+ALTER TABLE billing_statement_items ADD COLUMN co2_cost TEXT;
+ALTER TABLE billing_statement_items ADD COLUMN co2_landlord_amount TEXT;
@@ -0,0 +1,16 @@
+-- Restore the calorific value of the latest fuel version on the heating systems
+ALTER TABLE heating_systems ADD COLUMN calorific_value REAL NOT NULL DEFAULT 10;
+
+UPDATE heating_systems
+SET calorific_value = (
+    SELECT calorific_value FROM heating_system_fuels
+    WHERE heating_system_fuels.heating_system_id = heating_systems.id
+    ORDER BY valid_from DESC
+    LIMIT 1
+)
+WHERE EXISTS (
+    SELECT 1 FROM heating_system_fuels WHERE heating_system_fuels.heating_system_id = heating_systems.id
+);
+
+DROP INDEX IF EXISTS idx_heating_system_fuels_version;
+DROP TABLE IF EXISTS heating_system_fuels;
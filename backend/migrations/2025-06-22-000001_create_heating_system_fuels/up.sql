@@ -0,0 +1,35 @@
+-- Fuel a heating system burns, versioned like burner calibrations: each version applies
+-- from its valid_from date until the next one. Converts fuel quantities into kWh.
+CREATE TABLE heating_system_fuels (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    heating_system_id INTEGER NOT NULL,
+    fuel_type TEXT NOT NULL,        -- heating_oil, natural_gas, liquid_gas, wood_pellets or other
+    fuel_unit TEXT NOT NULL,        -- Unit fuel is measured and invoiced in: l, m3, kg or kWh
+    calorific_value REAL NOT NULL,  -- Hi in kWh per fuel unit
+    density REAL,                   -- kg per fuel unit, to convert weighed quantities
+    co2_emission_factor REAL,       -- kg CO2 per kWh (Hi)
+    efficiency REAL,                -- Annual efficiency of the boiler, useful heat per kWh of fuel
+    valid_from DATE NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (heating_system_id) REFERENCES heating_systems(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_heating_system_fuels_version ON heating_system_fuels (heating_system_id, valid_from);
+
+-- Existing systems keep their calorific value, measured in the unit of their fuel meter
+INSERT INTO heating_system_fuels (heating_system_id, fuel_type, fuel_unit, calorific_value, valid_from)
+SELECT id,
+       'other',
+       CASE (SELECT lower(replace(trim(unit), '³', '3')) FROM meters WHERE meters.id = heating_systems.fuel_meter_id)
+           WHEN 'm3' THEN 'm3'
+           WHEN 'kg' THEN 'kg'
+           WHEN 'kwh' THEN 'kWh'
+           ELSE 'l'
+       END,
+       calorific_value,
+       '2000-01-01'
+FROM heating_systems;
+
+ALTER TABLE heating_systems DROP COLUMN calorific_value;
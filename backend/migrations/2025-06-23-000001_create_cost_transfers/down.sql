@@ -0,0 +1,7 @@
+-- Remove cost transfers
+ALTER TABLE billing_statement_items DROP COLUMN transfer_amount;
+ALTER TABLE billing_statement_items DROP COLUMN transfer_cost_type_name;
+ALTER TABLE billing_statement_items DROP COLUMN transfer_name;
+
+DROP INDEX IF EXISTS idx_cost_transfers_building;
+DROP TABLE IF EXISTS cost_transfers;
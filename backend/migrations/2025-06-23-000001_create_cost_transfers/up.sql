@@ -0,0 +1,29 @@
+-- Costs moved from one cost type to another before allocation, e.g. the operating electricity
+-- of the heating (Betriebsstrom) from Allgemeinstrom into the heating costs (HeizkostenV § 7 (2)).
+-- The amount is a percentage of a basis cost type or the consumption of a sub-meter.
+CREATE TABLE cost_transfers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    building_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    source_cost_type_id INTEGER NOT NULL,  -- Costs are taken off this cost type
+    target_cost_type_id INTEGER NOT NULL,  -- and allocated with this one
+    method TEXT NOT NULL,                  -- percentage or meter
+    percent REAL,                          -- Percentage method: share of the basis cost type's costs
+    basis_cost_type_id INTEGER,            -- Percentage method, e.g. the fuel costs
+    meter_id INTEGER,                      -- Meter method: sub-meter of the transferred consumption
+    price_per_unit TEXT,                   -- Meter method: price; otherwise the source cost type's tariffs
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (building_id) REFERENCES buildings(id),
+    FOREIGN KEY (source_cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE,
+    FOREIGN KEY (target_cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE,
+    FOREIGN KEY (basis_cost_type_id) REFERENCES cost_types(id) ON DELETE CASCADE,
+    FOREIGN KEY (meter_id) REFERENCES meters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_cost_transfers_building ON cost_transfers (building_id);
+
+-- Statement items moved into or reduced by a cost transfer
+ALTER TABLE billing_statement_items ADD COLUMN transfer_name TEXT;
+ALTER TABLE billing_statement_items ADD COLUMN transfer_cost_type_name TEXT;
+ALTER TABLE billing_statement_items ADD COLUMN transfer_amount TEXT;
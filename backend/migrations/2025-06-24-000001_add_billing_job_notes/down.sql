@@ -0,0 +1,2 @@
+-- Remove the notes of billing jobs
+ALTER TABLE billing_jobs DROP COLUMN notes;
@@ -0,0 +1,2 @@
+-- Estimates and fallbacks the statements of a job are based on, e.g. extrapolated meter values
+ALTER TABLE billing_jobs ADD COLUMN notes TEXT; -- One note per line
@@ -0,0 +1,2 @@
+-- Remove the vacancy person setting
+ALTER TABLE buildings DROP COLUMN vacancy_persons;
@@ -0,0 +1,2 @@
+-- Persons a vacant unit counts with for person-based keys: none, one or previous_tenant
+ALTER TABLE buildings ADD COLUMN vacancy_persons TEXT NOT NULL DEFAULT 'one';
@@ -31,5 +31,15 @@ pub fn establish_connection() -> SqliteConnection {
     let database_url = env::var("DATABASE_URL")
         .unwrap_or_else(|_| "nebenkosten_knecht.db".to_string());
     SqliteConnection::establish(&database_url)
-        .expect(&format!("Error connecting to {}", database_url))
+        .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
+}
+
+// Fresh in-memory database with all migrations applied
+#[cfg(test)]
+pub fn test_connection() -> SqliteConnection {
+    let mut conn = SqliteConnection::establish(":memory:")
+        .expect("Failed to open in-memory database");
+    conn.run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run database migrations");
+    conn
 }
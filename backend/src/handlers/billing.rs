@@ -1,25 +1,36 @@
 use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
 use diesel::prelude::*;
-use diesel::backend::Backend;
-use diesel::BelongingToDsl;
-use chrono::{NaiveDate, NaiveDateTime, Utc};
-use diesel::deserialize::{self, FromSql};
-use diesel::sqlite::Sqlite;
-use diesel::sql_types::{Integer, Bool, Float, Text, Nullable, Timestamp};
-use diesel::sql_query;
-use log::{error, info};
-use serde::{Deserialize, Serialize};
-use std::fmt;
+use chrono::NaiveDate;
+use diesel::sql_types::{Integer, Text};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use thiserror::Error;
 
 use crate::db;
+use crate::handlers::building::resolve_building_id;
 use crate::DbPool;
-use crate::models::billing::{BillingPeriod, NewBillingPeriod, BillingStatement, NewBillingStatement, GenerateStatementRequest};
+use crate::models::building::{BuildingFilter, VacancyPersons};
+use crate::models::billing::{
+    BillingPeriod, NewBillingPeriod, BillingStatement, BillingStatementDto, BillingStatementItem,
+    NewBillingStatement, NewBillingStatementItem, GenerateStatementRequest, PreviewStatementRequest,
+    BillingJob, BillingJobDto, BillingJobStatus, NewBillingJob,
+};
+use crate::models::cost::TransferMethod;
 use crate::models::property_unit::PropertyUnit;
 use crate::models::tenant::Tenant;
-use crate::models::meter_reading::MeterReading;
-use crate::models::meter::Meter;
-use crate::models::cost::{CostType, FixedCost, Tariff};
-use crate::schema::{billing_periods, billing_statements, property_units, tenants, meters, meter_readings, cost_types, fixed_costs, tariffs};
+use crate::schema::{
+    billing_jobs, billing_periods, billing_statement_items, billing_statements, property_units, tenants,
+};
+use crate::services::allocation::{
+    self, AllocationError, AllocationKey, AppliedTransfer, BillingAllocation, CostLine, CostPart,
+};
+use crate::services::co2::Co2Split;
+use crate::services::hot_water::HotWaterSplit;
+use crate::services::interpolation::DegreeDays;
+use crate::services::owner;
+use crate::services::prepayment::{self, PrepaymentSource, PrepaymentSummary};
+use crate::services::rounding::round_cents;
+use crate::services::trace::{self, StatementTrace};
 
 // Define a struct to hold SQL count result
 #[derive(QueryableByName, Debug)]
@@ -28,25 +39,19 @@ struct SqliteBindedStatementCount {
     count: i32,
 }
 
-impl<DB> FromSql<Integer, DB> for SqliteBindedStatementCount
-where
-    DB: diesel::backend::Backend,
-    i32: FromSql<Integer, DB>,
-{
-    fn from_sql(bytes: diesel::backend::RawValue<DB>) -> deserialize::Result<Self> {
-        let count = i32::from_sql(bytes)?;
-        Ok(SqliteBindedStatementCount { count })
-    }
-}
-
 // Billing Period CRUD Operations
 
 #[get("/billing-periods")]
-pub async fn get_billing_periods() -> impl Responder {
+pub async fn get_billing_periods(filter: web::Query<BuildingFilter>) -> impl Responder {
     use crate::schema::billing_periods::dsl::*;
 
     let conn = &mut db::establish_connection();
-    match billing_periods.load::<BillingPeriod>(conn) {
+    let mut query = billing_periods.into_boxed();
+    if let Some(building_val) = filter.building_id {
+        query = query.filter(building_id.eq(building_val));
+    }
+
+    match query.load::<BillingPeriod>(conn) {
         Ok(results) => HttpResponse::Ok().json(results),
         Err(e) => {
             eprintln!("Error loading billing periods: {:?}", e);
@@ -76,20 +81,27 @@ pub async fn create_billing_period(
 ) -> Result<HttpResponse, actix_web::Error> {
     let new_period = new_period_json.into_inner();
 
+    let period_building_id = {
+        let mut conn = pool.get().expect("couldn't get db connection from pool");
+        match resolve_building_id(&mut conn, new_period.building_id) {
+            Ok(building_id) => building_id,
+            Err(e) => return Ok(e.response()),
+        }
+    };
+
     // First database operation - check for overlaps
     // We need to clone the pool for the first operation
     let pool_clone = pool.clone();
-    let period_unit_id = new_period.property_unit_id;
     let start_date = new_period.start_date.clone();
     let end_date = new_period.end_date.clone();
 
     // Check for overlapping billing periods
     let overlapping_periods_query = diesel::sql_query(
         "SELECT COUNT(*) as count FROM billing_periods \
-         WHERE property_unit_id = ?1 AND \
+         WHERE building_id = ?1 AND \
          (start_date < ?3 AND end_date > ?2)",
     )
-    .bind::<Integer, _>(period_unit_id)
+    .bind::<Integer, _>(period_building_id)
     .bind::<Text, _>(start_date)
     .bind::<Text, _>(end_date);
 
@@ -118,11 +130,11 @@ pub async fn create_billing_period(
     }
 
     // Validate date format
-    if let Err(_) = NaiveDate::parse_from_str(&new_period.start_date, "%Y-%m-%d") {
+    if NaiveDate::parse_from_str(&new_period.start_date, "%Y-%m-%d").is_err() {
         return Ok(HttpResponse::BadRequest().body("Invalid start_date format. Use YYYY-MM-DD"));
     }
 
-    if let Err(_) = NaiveDate::parse_from_str(&new_period.end_date, "%Y-%m-%d") {
+    if NaiveDate::parse_from_str(&new_period.end_date, "%Y-%m-%d").is_err() {
         return Ok(HttpResponse::BadRequest().body("Invalid end_date format. Use YYYY-MM-DD"));
     }
 
@@ -131,7 +143,7 @@ pub async fn create_billing_period(
 
     // Insert the new billing period
     let new_period_record = (
-        billing_periods::property_unit_id.eq(new_period.property_unit_id),
+        billing_periods::building_id.eq(period_building_id),
         billing_periods::start_date.eq(&new_period.start_date),
         billing_periods::end_date.eq(&new_period.end_date),
         billing_periods::name.eq(&new_period.name),
@@ -147,7 +159,7 @@ pub async fn create_billing_period(
         Ok(_) => {
             // Get the newly created billing period
             match billing_periods::table
-                .filter(billing_periods::property_unit_id.eq(new_period.property_unit_id))
+                .filter(billing_periods::building_id.eq(period_building_id))
                 .filter(billing_periods::start_date.eq(&new_period.start_date))
                 .filter(billing_periods::end_date.eq(&new_period.end_date))
                 .first::<BillingPeriod>(&mut conn)
@@ -180,22 +192,19 @@ pub async fn update_billing_period(path: web::Path<i32>, updated_period: web::Js
         Err(_) => return HttpResponse::NotFound().body("Billing period not found"),
     }
 
-    // Validate that the property unit exists
-    match property_units::table
-        .filter(property_units::id.eq(updated_period.property_unit_id))
-        .first::<PropertyUnit>(conn)
-    {
-        Ok(_) => {},
-        Err(_) => return HttpResponse::BadRequest().body("Property unit not found"),
-    }
+    // Validate that the building exists
+    let period_building_id = match resolve_building_id(conn, updated_period.building_id) {
+        Ok(building_id) => building_id,
+        Err(e) => return e.response(),
+    };
 
-    // Check for overlapping periods for the same property unit (excluding the current period)
+    // Check for overlapping periods of the same building (excluding the current period)
     let overlap = diesel::sql_query("
         SELECT COUNT(*) as count FROM billing_periods
-        WHERE property_unit_id = ? AND id != ?
+        WHERE building_id = ? AND id != ?
         AND ((start_date <= ? AND end_date >= ?) OR (start_date <= ? AND end_date >= ?))
     ")
-    .bind::<diesel::sql_types::Integer, _>(updated_period.property_unit_id)
+    .bind::<diesel::sql_types::Integer, _>(period_building_id)
     .bind::<diesel::sql_types::Integer, _>(period_id)
     .bind::<diesel::sql_types::Text, _>(&updated_period.end_date)
     .bind::<diesel::sql_types::Text, _>(&updated_period.start_date)
@@ -206,7 +215,7 @@ pub async fn update_billing_period(path: web::Path<i32>, updated_period: web::Js
     match overlap {
         Ok(result) => {
             if result.count > 0 {
-                return HttpResponse::BadRequest().body("This period overlaps with an existing billing period for this property unit");
+                return HttpResponse::BadRequest().body("This period overlaps with an existing billing period for this building");
             }
         },
         Err(e) => {
@@ -220,7 +229,7 @@ pub async fn update_billing_period(path: web::Path<i32>, updated_period: web::Js
 
     match diesel::update(billing_periods::table.filter(billing_periods::id.eq(period_id)))
         .set((
-            billing_periods::property_unit_id.eq(updated_period.property_unit_id),
+            billing_periods::building_id.eq(period_building_id),
             billing_periods::start_date.eq(&updated_period.start_date),
             billing_periods::end_date.eq(&updated_period.end_date),
             billing_periods::name.eq(&updated_period.name),
@@ -257,6 +266,42 @@ pub async fn delete_billing_period(path: web::Path<i32>) -> impl Responder {
     }
 }
 
+// Costs of the period carried by the owner instead of tenants: the owner's own share
+// and the vacancy account, with the tenancy, owner and vacancy days of each unit
+#[get("/billing-periods/{id}/owner-overview")]
+pub async fn get_owner_overview(path: web::Path<i32>) -> impl Responder {
+    let period_id = path.into_inner();
+    let conn = &mut db::establish_connection();
+
+    let billing_period = match billing_periods::table
+        .filter(billing_periods::id.eq(period_id))
+        .first::<BillingPeriod>(conn)
+    {
+        Ok(period) => period,
+        Err(_) => return HttpResponse::NotFound().body("Billing period not found"),
+    };
+
+    let units = match property_units::table
+        .filter(property_units::building_id.eq(billing_period.building_id))
+        .load::<PropertyUnit>(conn)
+    {
+        Ok(units) => units,
+        Err(e) => {
+            eprintln!("Error loading property units: {:?}", e);
+            return HttpResponse::InternalServerError().body("Error loading property units");
+        }
+    };
+
+    match allocation::allocate_billing_period(conn, &billing_period) {
+        Ok(allocation) => HttpResponse::Ok().json(owner::owner_overview(&allocation, &units)),
+        Err(AllocationError::InvalidPeriod(message)) => HttpResponse::BadRequest().body(message),
+        Err(e) => {
+            eprintln!("Error allocating billing period costs: {:?}", e);
+            HttpResponse::InternalServerError().body("Error allocating billing period costs")
+        }
+    }
+}
+
 #[post("/billing-statements/generate")]
 pub async fn generate_billing_statement(request: web::Json<GenerateStatementRequest>) -> impl Responder {
     let conn = &mut db::establish_connection();
@@ -279,52 +324,258 @@ pub async fn generate_billing_statement(request: web::Json<GenerateStatementRequ
         Err(_) => return HttpResponse::NotFound().body("Tenant not found"),
     };
 
-    // Calculate costs for this tenant and billing period
-    let total_amount = match calculate_tenant_costs(conn, &billing_period, &tenant) {
-        Ok(amount) => amount,
+    // The tenant's unit must belong to the building of the billing period
+    match property_units::table
+        .filter(property_units::id.eq(tenant.property_unit_id))
+        .filter(property_units::building_id.eq(billing_period.building_id))
+        .count()
+        .get_result::<i64>(conn)
+    {
+        Ok(0) => return HttpResponse::BadRequest().body("Tenant doesn't live in the building of the billing period"),
+        Ok(_) => (),
+        Err(e) => {
+            eprintln!("Error checking the tenant's building: {:?}", e);
+            return HttpResponse::InternalServerError().body("Error checking the tenant's building");
+        }
+    }
+
+    // A second statement for the same tenant and period would bill the costs twice
+    match existing_statements(conn, request.billing_period_id) {
+        Ok(existing) if existing.iter().any(|(tenant_id, _)| *tenant_id == tenant.id) => {
+            return HttpResponse::Conflict()
+                .body("A statement for this tenant already exists for this billing period");
+        }
+        Ok(_) => (),
+        Err(e) => {
+            eprintln!("Error checking existing billing statements: {:?}", e);
+            return HttpResponse::InternalServerError().body("Error checking existing billing statements");
+        }
+    }
+
+    // Calculate the itemized costs for this tenant and billing period
+    let allocation = match allocation::allocate_billing_period(conn, &billing_period) {
+        Ok(allocation) => allocation,
         Err(e) => {
             eprintln!("Error calculating tenant costs: {:?}", e);
             return HttpResponse::InternalServerError().body("Error calculating tenant costs");
         }
     };
+    let prepared = match prepare_statement(conn, &allocation, &billing_period, &tenant) {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            eprintln!("Error preparing billing statement: {:?}", e);
+            return HttpResponse::InternalServerError().body("Error preparing billing statement");
+        }
+    };
 
-    // Generate HTML content
-    let html_content = generate_billing_statement_html(&billing_period, &tenant, total_amount, conn);
+    // Store the statement and its items together
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let statement = store_statement(conn, prepared)?;
+        load_statement_dto(conn, statement)
+    });
 
-    // Create current timestamp for generated_at field
-    let now = chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
+    match result {
+        Ok(statement) => HttpResponse::Created().json(statement),
+        Err(e) => {
+            eprintln!("Error creating billing statement: {:?}", e);
+            HttpResponse::InternalServerError().body("Error creating billing statement")
+        }
+    }
+}
 
-    // Create a new statement
-    let new_statement = NewBillingStatement {
-        billing_period_id: request.billing_period_id,
-        tenant_id: request.tenant_id,
-        total_amount,
-        generated_at: now.clone(),
-        html_content: Some(html_content),
+// Dry run of statement generation: the calculation trace of every tenant of the period
+// (or the requested one) without storing anything
+#[post("/billing-statements/preview")]
+pub async fn preview_billing_statements(request: web::Json<PreviewStatementRequest>) -> impl Responder {
+    let conn = &mut db::establish_connection();
+
+    let billing_period = match billing_periods::table
+        .filter(billing_periods::id.eq(request.billing_period_id))
+        .first::<BillingPeriod>(conn)
+    {
+        Ok(period) => period,
+        Err(_) => return HttpResponse::NotFound().body("Billing period not found"),
     };
 
-    match diesel::insert_into(billing_statements::table)
-        .values(&new_statement)
-        .execute(conn)
+    let allocation = match allocation::allocate_billing_period(conn, &billing_period) {
+        Ok(allocation) => allocation,
+        Err(AllocationError::InvalidPeriod(message)) => return HttpResponse::BadRequest().body(message),
+        Err(e) => {
+            eprintln!("Error allocating billing period costs: {:?}", e);
+            return HttpResponse::InternalServerError().body("Error allocating billing period costs");
+        }
+    };
+
+    // Without a tenant, everyone with a tenancy in the period
+    let tenant_ids: Vec<i32> = match request.tenant_id {
+        Some(tenant_id) => vec![tenant_id],
+        None => allocation
+            .participants
+            .iter()
+            .filter_map(|participant| participant.tenant_id)
+            .collect(),
+    };
+    let period_tenants = match tenants::table
+        .filter(tenants::id.eq_any(&tenant_ids))
+        .order(tenants::id.asc())
+        .load::<Tenant>(conn)
     {
-        Ok(_) => {
-            // Get the newly created statement
-            match billing_statements::table
-                .filter(billing_statements::billing_period_id.eq(request.billing_period_id))
-                .filter(billing_statements::tenant_id.eq(request.tenant_id))
-                .filter(billing_statements::generated_at.eq(&now))
-                .first::<BillingStatement>(conn)
-            {
-                Ok(statement) => HttpResponse::Created().json(statement),
-                Err(e) => {
-                    eprintln!("Error retrieving created billing statement: {:?}", e);
-                    HttpResponse::InternalServerError().body("Error retrieving created billing statement")
-                }
+        Ok(period_tenants) => period_tenants,
+        Err(e) => {
+            eprintln!("Error loading tenants: {:?}", e);
+            return HttpResponse::InternalServerError().body("Error loading tenants");
+        }
+    };
+    if request.tenant_id.is_some() && period_tenants.is_empty() {
+        return HttpResponse::NotFound().body("Tenant not found");
+    }
+
+    let mut traces: Vec<StatementTrace> = Vec::new();
+    for tenant in &period_tenants {
+        let (start_date, end_date) = billing_period.to_naive_date_range();
+        let prepayments = match prepayment::prepayment_summary(
+            conn,
+            tenant.id.unwrap_or(0),
+            request.billing_period_id,
+            start_date,
+            end_date,
+        ) {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("Error loading tenant prepayments: {:?}", e);
+                return HttpResponse::InternalServerError().body("Error loading tenant prepayments");
             }
+        };
+        traces.push(trace::statement_trace(&allocation, &billing_period, tenant, prepayments));
+    }
+
+    HttpResponse::Ok().json(traces)
+}
+
+// Calculation trace stored when the statement was generated
+#[get("/billing-statements/{id}/trace")]
+pub async fn get_billing_statement_trace(path: web::Path<i32>) -> impl Responder {
+    let statement_id = path.into_inner();
+    let conn = &mut db::establish_connection();
+
+    match billing_statements::table
+        .filter(billing_statements::id.eq(statement_id))
+        .first::<BillingStatement>(conn)
+    {
+        Ok(statement) => match statement.calculation_trace {
+            Some(json) => HttpResponse::Ok()
+                .content_type("application/json")
+                .body(json),
+            // Statements generated before traces were recorded
+            None => HttpResponse::NotFound().body("Calculation trace not found for this statement"),
+        },
+        Err(_) => HttpResponse::NotFound().body("Billing statement not found"),
+    }
+}
+
+// Start generating the statements of all tenants of a period, together with the owner's
+// share, as a background job. The job is returned right away and can be polled.
+#[post("/billing-periods/{id}/generate-statements")]
+pub async fn generate_billing_period_statements(path: web::Path<i32>) -> impl Responder {
+    let period_id = path.into_inner();
+    let conn = &mut db::establish_connection();
+
+    let billing_period = match billing_periods::table
+        .filter(billing_periods::id.eq(period_id))
+        .first::<BillingPeriod>(conn)
+    {
+        Ok(period) => period,
+        Err(_) => return HttpResponse::NotFound().body("Billing period not found"),
+    };
+
+    // Only one job per period at a time
+    match billing_jobs::table
+        .filter(billing_jobs::billing_period_id.eq(period_id))
+        .filter(billing_jobs::status.eq_any([
+            BillingJobStatus::Pending.to_string(),
+            BillingJobStatus::Running.to_string(),
+        ]))
+        .count()
+        .get_result::<i64>(conn)
+    {
+        Ok(0) => (),
+        Ok(_) => {
+            return HttpResponse::Conflict().body("Statements of this billing period are already being generated")
         }
         Err(e) => {
-            eprintln!("Error creating billing statement: {:?}", e);
-            HttpResponse::InternalServerError().body("Error creating billing statement")
+            eprintln!("Error checking billing jobs: {:?}", e);
+            return HttpResponse::InternalServerError().body("Error checking billing jobs");
+        }
+    }
+
+    let new_job = NewBillingJob {
+        billing_period_id: period_id,
+        status: BillingJobStatus::Pending.to_string(),
+    };
+    let job = match diesel::insert_into(billing_jobs::table)
+        .values(&new_job)
+        .execute(conn)
+        .and_then(|_| {
+            billing_jobs::table
+                .order(billing_jobs::id.desc())
+                .first::<BillingJob>(conn)
+        }) {
+        Ok(job) => job,
+        Err(e) => {
+            eprintln!("Error creating billing job: {:?}", e);
+            return HttpResponse::InternalServerError().body("Error creating billing job");
+        }
+    };
+
+    let job_id = job.id.unwrap_or(0);
+    std::thread::spawn(move || run_billing_job(job_id, billing_period));
+
+    HttpResponse::Accepted().json(BillingJobDto::from(job))
+}
+
+#[get("/billing-periods/{id}/billing-jobs")]
+pub async fn get_billing_period_jobs(path: web::Path<i32>) -> impl Responder {
+    let period_id = path.into_inner();
+    let conn = &mut db::establish_connection();
+
+    let result = billing_jobs::table
+        .filter(billing_jobs::billing_period_id.eq(period_id))
+        .order(billing_jobs::id.desc())
+        .load::<BillingJob>(conn)
+        .and_then(|jobs| {
+            jobs.into_iter()
+                .map(|job| load_job_dto(conn, job))
+                .collect::<Result<Vec<_>, _>>()
+        });
+
+    match result {
+        Ok(jobs) => HttpResponse::Ok().json(jobs),
+        Err(e) => {
+            eprintln!("Error loading billing jobs: {:?}", e);
+            HttpResponse::InternalServerError().body("Error loading billing jobs")
+        }
+    }
+}
+
+// Progress and outcome of a batch generation job
+#[get("/billing-jobs/{id}")]
+pub async fn get_billing_job(path: web::Path<i32>) -> impl Responder {
+    let job_id = path.into_inner();
+    let conn = &mut db::establish_connection();
+
+    let job = match billing_jobs::table
+        .filter(billing_jobs::id.eq(job_id))
+        .first::<BillingJob>(conn)
+    {
+        Ok(job) => job,
+        Err(_) => return HttpResponse::NotFound().body("Billing job not found"),
+    };
+
+    match load_job_dto(conn, job) {
+        Ok(job) => HttpResponse::Ok().json(job),
+        Err(e) => {
+            eprintln!("Error loading billing job statements: {:?}", e);
+            HttpResponse::InternalServerError().body("Error loading billing job statements")
         }
     }
 }
@@ -334,12 +585,38 @@ pub async fn get_billing_statement(path: web::Path<i32>) -> impl Responder {
     let statement_id = path.into_inner();
     let conn = &mut db::establish_connection();
 
-    match billing_statements::table
+    let statement = match billing_statements::table
         .filter(billing_statements::id.eq(statement_id))
         .first::<BillingStatement>(conn)
     {
+        Ok(statement) => statement,
+        Err(_) => return HttpResponse::NotFound().body("Billing statement not found"),
+    };
+
+    match load_statement_dto(conn, statement) {
         Ok(statement) => HttpResponse::Ok().json(statement),
-        Err(_) => HttpResponse::NotFound().body("Billing statement not found"),
+        Err(e) => {
+            eprintln!("Error loading billing statement items: {:?}", e);
+            HttpResponse::InternalServerError().body("Error loading billing statement items")
+        }
+    }
+}
+
+#[get("/billing-statements/{id}/items")]
+pub async fn get_billing_statement_items(path: web::Path<i32>) -> impl Responder {
+    let statement_id = path.into_inner();
+    let conn = &mut db::establish_connection();
+
+    match billing_statement_items::table
+        .filter(billing_statement_items::billing_statement_id.eq(statement_id))
+        .order(billing_statement_items::position.asc())
+        .load::<BillingStatementItem>(conn)
+    {
+        Ok(items) => HttpResponse::Ok().json(items),
+        Err(e) => {
+            eprintln!("Error loading billing statement items: {:?}", e);
+            HttpResponse::InternalServerError().body("Error loading billing statement items")
+        }
     }
 }
 
@@ -410,8 +687,17 @@ pub async fn delete_billing_statement(path: web::Path<i32>) -> impl Responder {
         Err(_) => return HttpResponse::NotFound().body("Billing statement not found"),
     }
 
-    // Delete the statement
-    match diesel::delete(billing_statements::table.filter(billing_statements::id.eq(statement_id))).execute(conn) {
+    // Delete the statement together with its items
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(
+            billing_statement_items::table
+                .filter(billing_statement_items::billing_statement_id.eq(statement_id)),
+        )
+        .execute(conn)?;
+        diesel::delete(billing_statements::table.filter(billing_statements::id.eq(statement_id))).execute(conn)
+    });
+
+    match result {
         Ok(_) => HttpResponse::Ok().body("Billing statement deleted successfully"),
         Err(e) => {
             eprintln!("Error deleting billing statement: {:?}", e);
@@ -422,127 +708,460 @@ pub async fn delete_billing_statement(path: web::Path<i32>) -> impl Responder {
 
 // Helper functions
 
-fn calculate_tenant_costs(
+// Key quantities are stored with this many decimal places
+const QUANTITY_DECIMALS: u32 = 6;
+
+// Errors while preparing a tenant's statement from the allocation of its period
+#[derive(Debug, Error)]
+enum StatementError {
+    #[error("error loading tenant prepayments: {0}")]
+    Prepayments(#[from] diesel::result::Error),
+    #[error("error serializing calculation trace: {0}")]
+    Trace(#[from] serde_json::Error),
+}
+
+// Errors that stop a batch generation job; nothing is stored in that case
+#[derive(Debug, Error)]
+enum BatchError {
+    #[error("{}", .0.join("\n"))]
+    Inconsistent(Vec<String>), // Problems in the input data, one per line
+    #[error("{0}")]
+    Allocation(#[from] AllocationError),
+    #[error("{0}")]
+    Statement(#[from] StatementError),
+    #[error("error serializing owner overview: {0}")]
+    OwnerOverview(#[from] serde_json::Error),
+    #[error("database error: {0}")]
+    Database(#[from] diesel::result::Error),
+}
+
+// Statement of a tenant with its items, ready to be stored
+struct PreparedStatement {
+    statement: NewBillingStatement,
+    items: Vec<NewBillingStatementItem>,
+}
+
+// Build the statement of a tenant from the allocation of its billing period:
+// itemized costs, deducted prepayments, the HTML and the calculation trace
+fn prepare_statement(
     conn: &mut SqliteConnection,
+    allocation: &BillingAllocation,
     billing_period: &BillingPeriod,
     tenant: &Tenant,
-) -> Result<f32, diesel::result::Error> {
-    let (start_date, end_date) = billing_period.to_naive_date_range();
-    let mut total_cost = 0.0;
-
-    // Get the property unit
-    let property_unit = property_units::table
-        .filter(property_units::id.eq(billing_period.property_unit_id))
-        .first::<PropertyUnit>(conn)?;
-
-    // Get all cost types
-    let all_cost_types = cost_types::table.load::<CostType>(conn)?;
-
-    // For each cost type, calculate the tenant's share
-    for cost_type in all_cost_types {
-        if cost_type.is_consumption_based {
-            // Handle consumption-based costs
-            // Calculate costs allocated by area proportion
-            let total_area = property_unit.living_area_m2 as f64;
-            let tenant_area = total_area; // Assuming the tenant occupies the entire unit
-
-            if total_area > 0.0 {
-                // Get all meters for the property unit
-                let meters_for_unit = meters::table
-                    .filter(meters::property_unit_id.eq(property_unit.id))
-                    .select(Meter::as_select())
-                    .load::<Meter>(conn)?;
-
-                for meter in meters_for_unit {
-                    if let Some(meter_id) = meter.id {
-                        // Convert NaiveDate to NaiveDateTime for comparisons with reading_date (Timestamp)
-                        let start_datetime = NaiveDateTime::new(
-                            start_date,
-                            chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap()
-                        );
-
-                        let start_reading_val = meter_readings::table
-                            .filter(meter_readings::meter_id.eq(meter_id))
-                            .filter(meter_readings::reading_date.ge(start_datetime))
-                            .order(meter_readings::reading_date.asc())
-                            .select(meter_readings::value)
-                            .first::<f32>(conn)
-                            .optional()?;
-
-                        // Convert NaiveDate to NaiveDateTime for comparisons
-                        let end_datetime = NaiveDateTime::new(
-                            end_date,
-                            chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap()
-                        );
-
-                        let end_reading_val = meter_readings::table
-                            .filter(meter_readings::meter_id.eq(meter_id))
-                            .filter(meter_readings::reading_date.le(end_datetime))
-                            .order(meter_readings::reading_date.desc())
-                            .select(meter_readings::value)
-                            .first::<f32>(conn)
-                            .optional()?;
-
-                        if let (Some(start_reading), Some(end_reading)) = (start_reading_val, end_reading_val) {
-                            let consumption = end_reading - start_reading;
-
-                            // Get tariff for the meter type and billing period
-                            let tariffs_for_cost_type = tariffs::table
-                                .filter(tariffs::cost_type_id.eq(cost_type.id.unwrap())) // Assuming cost_type.id is Option<i32>
-                                // We'll filter dates after loading due to type conversion complexities
-                                .load::<Tariff>(conn)?
-                                .into_iter()
-                                .filter(|tariff| {
-                                    // Check if tariff is valid for the billing period
-                                    tariff.valid_from <= end_date &&
-                                    (tariff.valid_to.is_none() || tariff.valid_to.unwrap() >= start_date)
-                                })
-                                .collect::<Vec<Tariff>>();
-
-                            if let Some(tariff) = tariffs_for_cost_type.first() {
-                                total_cost += consumption * tariff.price_per_unit;
-                            }
-                        }
-                    }
-                }
+) -> Result<PreparedStatement, StatementError> {
+    let costs = calculate_tenant_costs(allocation, tenant);
+    let total_amount: Decimal = costs.items.iter().map(|item| item.share_amount).sum();
+
+    // Deduct the tenant's prepayments for this period
+    let billing_period_id = billing_period.id.unwrap_or(0);
+    let prepayments = prepayment::prepayment_summary(
+        conn,
+        tenant.id.unwrap_or(0),
+        billing_period_id,
+        allocation.start_date,
+        allocation.end_date,
+    )?;
+    let prepayments_amount = prepayments.deducted_amount;
+    let balance_amount = total_amount - prepayments_amount;
+
+    // Keep the calculation trace with the statement so the numbers can be checked later
+    let calculation_trace = serde_json::to_string(&trace::statement_trace(
+        allocation,
+        billing_period,
+        tenant,
+        prepayments.clone(),
+    ))?;
+
+    // Generate HTML content
+    let html_content = generate_billing_statement_html(
+        billing_period,
+        tenant,
+        total_amount,
+        &costs,
+        &prepayments,
+        conn,
+    );
+
+    // Create current timestamp for generated_at field
+    let now = chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    Ok(PreparedStatement {
+        statement: NewBillingStatement {
+            billing_period_id,
+            tenant_id: tenant.id.unwrap_or(0),
+            total_amount,
+            generated_at: now,
+            html_content: Some(html_content),
+            prepayments_amount,
+            balance_amount,
+            calculation_trace: Some(calculation_trace),
+            billing_job_id: None,
+        },
+        items: costs.items,
+    })
+}
+
+// Insert a prepared statement and its items; callers run this inside a transaction
+fn store_statement(
+    conn: &mut SqliteConnection,
+    prepared: PreparedStatement,
+) -> Result<BillingStatement, diesel::result::Error> {
+    let PreparedStatement { statement, mut items } = prepared;
+    diesel::insert_into(billing_statements::table)
+        .values(statement)
+        .execute(conn)?;
+
+    let statement = billing_statements::table
+        .order(billing_statements::id.desc())
+        .first::<BillingStatement>(conn)?;
+
+    for item in items.iter_mut() {
+        item.billing_statement_id = statement.id.unwrap_or(0);
+    }
+    diesel::insert_into(billing_statement_items::table)
+        .values(items)
+        .execute(conn)?;
+
+    Ok(statement)
+}
+
+// Background part of a batch generation job. Failures are recorded on the job.
+fn run_billing_job(job_id: i32, billing_period: BillingPeriod) {
+    let conn = &mut db::establish_connection();
+    let job = billing_jobs::table.filter(billing_jobs::id.eq(job_id));
+
+    if let Err(e) = diesel::update(job)
+        .set(billing_jobs::status.eq(BillingJobStatus::Running.to_string()))
+        .execute(conn)
+    {
+        eprintln!("Error starting billing job {}: {:?}", job_id, e);
+        return;
+    }
+
+    if let Err(e) = generate_period_statements(conn, job_id, &billing_period) {
+        eprintln!("Billing job {} failed: {}", job_id, e);
+        let now = chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
+        if let Err(e) = diesel::update(job)
+            .set((
+                billing_jobs::status.eq(BillingJobStatus::Failed.to_string()),
+                billing_jobs::errors.eq(Some(e.to_string())),
+                billing_jobs::finished_at.eq(Some(now)),
+            ))
+            .execute(conn)
+        {
+            eprintln!("Error recording the failure of billing job {}: {:?}", job_id, e);
+        }
+    }
+}
+
+// Jobs still pending or running when the server stopped will never finish.
+// Mark them failed at startup so the period can be generated again.
+pub fn fail_interrupted_jobs(conn: &mut SqliteConnection) -> Result<usize, diesel::result::Error> {
+    let now = chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
+    diesel::update(billing_jobs::table.filter(billing_jobs::status.eq_any([
+        BillingJobStatus::Pending.to_string(),
+        BillingJobStatus::Running.to_string(),
+    ])))
+    .set((
+        billing_jobs::status.eq(BillingJobStatus::Failed.to_string()),
+        billing_jobs::errors.eq(Some("Interrupted by a server restart".to_string())),
+        billing_jobs::finished_at.eq(Some(now)),
+    ))
+    .execute(conn)
+}
+
+// Tenants (ID and name) that already have a statement for the billing period
+fn existing_statements(
+    conn: &mut SqliteConnection,
+    period_id: i32,
+) -> Result<Vec<(Option<i32>, String)>, diesel::result::Error> {
+    billing_statements::table
+        .inner_join(tenants::table)
+        .filter(billing_statements::billing_period_id.eq(period_id))
+        .select((tenants::id, tenants::name))
+        .load::<(Option<i32>, String)>(conn)
+}
+
+// Generate the statements of every tenant of the period and the owner overview.
+// The input is checked first; the statements are stored in a single transaction
+// that also completes the job, so either all of them exist or none.
+fn generate_period_statements(
+    conn: &mut SqliteConnection,
+    job_id: i32,
+    billing_period: &BillingPeriod,
+) -> Result<(), BatchError> {
+    let job = billing_jobs::table.filter(billing_jobs::id.eq(job_id));
+    let period_id = billing_period.id.unwrap_or(0);
+    let allocation = allocation::allocate_billing_period(conn, billing_period)?;
+
+    // Missing readings, tariff gaps and unbound meters would give wrong statements. Notes on
+    // estimates and fallbacks don't stop the job, they are kept with it and the statements' traces.
+    let mut problems = allocation.warnings.clone();
+
+    let tenant_ids: Vec<i32> = allocation
+        .participants
+        .iter()
+        .filter_map(|participant| participant.tenant_id)
+        .collect();
+    let period_tenants = tenants::table
+        .filter(tenants::id.eq_any(&tenant_ids))
+        .order(tenants::id.asc())
+        .load::<Tenant>(conn)?;
+    if period_tenants.is_empty() {
+        problems.push("No tenant has a tenancy in the billing period".to_string());
+    }
+
+    // Statements generated before would be duplicated
+    for (_, name) in existing_statements(conn, period_id)? {
+        problems.push(format!("A statement for tenant '{}' already exists for this billing period", name));
+    }
+
+    if !problems.is_empty() {
+        return Err(BatchError::Inconsistent(problems));
+    }
+
+    diesel::update(job)
+        .set(billing_jobs::total_tenants.eq(period_tenants.len() as i32))
+        .execute(conn)?;
+
+    let mut prepared = Vec::new();
+    for (index, tenant) in period_tenants.iter().enumerate() {
+        let mut statement = prepare_statement(conn, &allocation, billing_period, tenant)?;
+        statement.statement.billing_job_id = Some(job_id);
+        prepared.push(statement);
+
+        diesel::update(job)
+            .set(billing_jobs::processed_tenants.eq(index as i32 + 1))
+            .execute(conn)?;
+    }
+
+    // The owner's share and the vacancy account of the same allocation
+    let units = property_units::table
+        .filter(property_units::building_id.eq(billing_period.building_id))
+        .load::<PropertyUnit>(conn)?;
+    let owner_overview = serde_json::to_string(&owner::owner_overview(&allocation, &units))?;
+
+    conn.transaction::<_, BatchError, _>(|conn| {
+        for statement in prepared {
+            store_statement(conn, statement)?;
+        }
+
+        let now = chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
+        diesel::update(job)
+            .set((
+                billing_jobs::status.eq(BillingJobStatus::Completed.to_string()),
+                billing_jobs::owner_overview.eq(Some(owner_overview)),
+                billing_jobs::notes.eq((!allocation.notes.is_empty()).then(|| allocation.notes.join("\n"))),
+                billing_jobs::finished_at.eq(Some(now)),
+            ))
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+fn load_job_dto(conn: &mut SqliteConnection, job: BillingJob) -> Result<BillingJobDto, diesel::result::Error> {
+    let statement_ids = billing_statements::table
+        .filter(billing_statements::billing_job_id.eq(job.id))
+        .order(billing_statements::id.asc())
+        .select(billing_statements::id)
+        .load::<Option<i32>>(conn)?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    Ok(BillingJobDto {
+        statement_ids,
+        ..BillingJobDto::from(job)
+    })
+}
+
+// Statement items of a tenant together with the tenant's days of use in the period
+struct TenantCosts {
+    items: Vec<NewBillingStatementItem>,
+    occupied_days: i64,
+    period_days: i64,
+    hot_water_splits: Vec<HotWaterSplit>, // Fuel cost splits behind the tenant's items
+    degree_day_cost_types: Vec<String>,   // Cost types weighted by degree days
+    degree_days: DegreeDays,
+    co2_splits: Vec<Co2Split>, // CO2 cost splits of the fuels behind the tenant's items
+    transfers: Vec<AppliedTransfer>, // Cost transfers into or out of the tenant's items
+    vacancy_persons: VacancyPersons, // Persons of vacant units in the tenant's person-based items
+}
+
+// Name of the cost transfer that moved a line in, or of the transfers that took parts of it
+fn transfer_name(line: &CostLine) -> Option<String> {
+    match &line.transfer_in {
+        Some(transfer) => Some(transfer.name.clone()),
+        None if line.transfers_out.is_empty() => None,
+        None => Some(
+            line.transfers_out
+                .iter()
+                .map(|part| part.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    }
+}
+
+// Cost type on the other side of a line's cost transfers
+fn transfer_cost_type_name(line: &CostLine) -> Option<String> {
+    match &line.transfer_in {
+        Some(transfer) => Some(transfer.source_cost_type_name.clone()),
+        None if line.transfers_out.is_empty() => None,
+        None => Some(
+            line.transfers_out
+                .iter()
+                .map(|part| part.target_cost_type_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    }
+}
+
+// Building amount moved into a line (positive) or out of it (negative)
+fn transfer_amount(line: &CostLine) -> Option<Decimal> {
+    if line.transfer_in.is_some() {
+        Some(line.total_amount)
+    } else if line.transfers_out.is_empty() {
+        None
+    } else {
+        Some(-round_cents(line.transfers_out.iter().map(|part| part.amount).sum()))
+    }
+}
+
+// Build one statement item per cost line the tenant takes part in.
+// The billing_statement_id is filled in once the statement has been stored.
+fn calculate_tenant_costs(allocation: &BillingAllocation, tenant: &Tenant) -> TenantCosts {
+    let tenant_id = tenant.id.unwrap_or(0);
+    let occupied_days = allocation
+        .participants
+        .iter()
+        .filter(|participant| participant.tenant_id == Some(tenant_id))
+        .map(|participant| participant.occupied_days)
+        .sum();
+
+    let tenant_lines: Vec<_> = allocation
+        .lines
+        .iter()
+        .filter_map(|line| line.share_for(tenant_id).map(|share| (line, share)))
+        .collect();
+
+    let mut hot_water_splits: Vec<HotWaterSplit> = Vec::new();
+    for split in tenant_lines.iter().filter_map(|(line, _)| line.hot_water_split.as_ref()) {
+        if !hot_water_splits.iter().any(|known| known.heating_system_id == split.heating_system_id) {
+            hot_water_splits.push(split.clone());
+        }
+    }
+
+    let mut co2_splits: Vec<Co2Split> = Vec::new();
+    for split in tenant_lines.iter().filter_map(|(line, _)| line.co2_split.as_ref()) {
+        if !co2_splits.iter().any(|known| known.cost_type_id == split.cost_type_id) {
+            co2_splits.push(split.clone());
+        }
+    }
+
+    let mut transfers: Vec<AppliedTransfer> = Vec::new();
+    for (line, _) in &tenant_lines {
+        let transfer_ids = line
+            .transfer_in
+            .iter()
+            .map(|transfer| transfer.transfer_id)
+            .chain(line.transfers_out.iter().map(|part| part.transfer_id));
+        for transfer_id in transfer_ids {
+            if transfers.iter().any(|known| known.transfer_id == transfer_id) {
+                continue;
             }
-        } else {
-            // Handle fixed costs
-            // Get fixed costs for this cost type during the billing period
-            let fixed_costs_for_type = fixed_costs::table
-                .filter(fixed_costs::cost_type_id.eq(cost_type.id.unwrap())) // Assuming cost_type.id is Option<i32>
-                .load::<FixedCost>(conn)?
-                .into_iter()
-                .filter(|fixed_cost| {
-                    // Check if fixed cost period overlaps with billing period
-                    fixed_cost.billing_period_start <= end_date &&
-                    fixed_cost.billing_period_end >= start_date
-                })
-                .collect::<Vec<FixedCost>>();
-
-            for fixed_cost in fixed_costs_for_type {
-                // By default, distribute by area
-                let total_area = property_unit.living_area_m2 as f64;
-
-                // Get all tenants in the property unit to calculate proportions
-                let tenants_in_unit = tenants::table
-                    .filter(tenants::property_unit_id.eq(property_unit.id.unwrap())) // Assuming property_unit.id is Option<i32>
-                    .select(Tenant::as_select())
-                    .load::<Tenant>(conn)?;
-
-                let number_of_tenants_in_unit = tenants_in_unit.len() as f32;
-
-                if number_of_tenants_in_unit > 0.0 {
-                    total_cost += fixed_cost.amount / number_of_tenants_in_unit;
-                }
+            if let Some(transfer) = allocation.transfers.iter().find(|transfer| transfer.transfer_id == transfer_id) {
+                transfers.push(transfer.clone());
             }
         }
     }
 
-    Ok(total_cost)
+    let mut degree_day_cost_types: Vec<String> = Vec::new();
+    for (line, _) in tenant_lines.iter().filter(|(line, _)| line.degree_day_weighting) {
+        if !degree_day_cost_types.contains(&line.cost_type_name) {
+            degree_day_cost_types.push(line.cost_type_name.clone());
+        }
+    }
+
+    let items = tenant_lines
+        .into_iter()
+        .enumerate()
+        .map(|(position, (line, share))| {
+            let co2 = line.co2_for(share);
+            NewBillingStatementItem {
+                billing_statement_id: 0,
+                cost_type_id: line.cost_type_id,
+                cost_type_name: line.cost_type_name.clone(),
+                allocation_key: line.allocation_key.as_str().to_string(),
+                total_amount: line.total_amount,
+                total_quantity: line.total_quantity.round_dp(QUANTITY_DECIMALS),
+                tenant_quantity: share.quantity.round_dp(QUANTITY_DECIMALS),
+                share_amount: share.amount,
+                position: position as i32,
+                tariff_id: line.tariff_segment.as_ref().and_then(|segment| segment.tariff_id),
+                price_per_unit: line.tariff_segment.as_ref().map(|segment| segment.price_per_unit),
+                segment_start: line.tariff_segment.as_ref().map(|segment| segment.start_date),
+                segment_end: line.tariff_segment.as_ref().map(|segment| segment.end_date),
+                consumption_estimated: share.estimated,
+                cost_part: line.cost_part.map(|part| part.as_str().to_string()),
+                cost_part_percent: line.cost_part_percent.and_then(|percent| percent.to_f32()),
+                common_consumption: line.common_consumption,
+                fixed_cost_id: line.apportionment.as_ref().and_then(|apportionment| apportionment.fixed_cost_id),
+                invoice_amount: line.apportionment.as_ref().map(|apportionment| apportionment.invoice_amount),
+                invoice_start: line.apportionment.as_ref().map(|apportionment| apportionment.invoice_start),
+                invoice_end: line.apportionment.as_ref().map(|apportionment| apportionment.invoice_end),
+                invoice_days: line.apportionment.as_ref().map(|apportionment| apportionment.invoice_days as i32),
+                apportioned_days: line.apportionment.as_ref().map(|apportionment| apportionment.apportioned_days as i32),
+                source_cost_type_id: line.hot_water_split.as_ref().map(|split| split.fuel_cost_type_id),
+                source_cost_type_name: line.hot_water_split.as_ref().map(|split| split.fuel_cost_type_name.clone()),
+                source_share_percent: line
+                    .hot_water_split
+                    .as_ref()
+                    .map(|split| split.percent_for(line.cost_type_id).round_dp(2)),
+                co2_cost: co2.map(|(co2_cost, _)| round_cents(co2_cost)),
+                co2_landlord_amount: co2.map(|(_, landlord_amount)| round_cents(landlord_amount)),
+                transfer_name: transfer_name(line),
+                transfer_cost_type_name: transfer_cost_type_name(line),
+                transfer_amount: transfer_amount(line),
+            }
+        })
+        .collect();
+
+    TenantCosts {
+        items,
+        occupied_days,
+        period_days: allocation.period_days,
+        hot_water_splits,
+        degree_day_cost_types,
+        degree_days: allocation.degree_days,
+        co2_splits,
+        transfers,
+        vacancy_persons: allocation.vacancy_persons,
+    }
 }
 
-fn generate_billing_statement_html(billing_period: &BillingPeriod, tenant: &Tenant, total_amount: f32, conn: &mut SqliteConnection) -> String {
+fn load_statement_dto(
+    conn: &mut SqliteConnection,
+    statement: BillingStatement,
+) -> Result<BillingStatementDto, diesel::result::Error> {
+    let items = billing_statement_items::table
+        .filter(billing_statement_items::billing_statement_id.eq(statement.id.unwrap_or(0)))
+        .order(billing_statement_items::position.asc())
+        .load::<BillingStatementItem>(conn)?;
+
+    Ok(BillingStatementDto { statement, items })
+}
+
+fn generate_billing_statement_html(
+    billing_period: &BillingPeriod,
+    tenant: &Tenant,
+    total_amount: Decimal,
+    costs: &TenantCosts,
+    prepayments: &PrepaymentSummary,
+    conn: &mut SqliteConnection,
+) -> String {
+    let items = &costs.items;
     // Get the tenant's property unit living area
     let area = property_units::table
                 .filter(property_units::id.eq(tenant.property_unit_id))
@@ -550,6 +1169,278 @@ fn generate_billing_statement_html(billing_period: &BillingPeriod, tenant: &Tena
                 .first::<f32>(conn)
                 .unwrap_or(0.0);
 
+    // One table row per cost type, consumption-based costs once per tariff segment and fixed costs once per invoice
+    let item_rows: String = items
+        .iter()
+        .map(|item| {
+            let key_label = AllocationKey::from_name(&item.allocation_key)
+                .map(|key| key.label())
+                .unwrap_or(item.allocation_key.as_str());
+            let part_note = match (
+                item.cost_part.as_deref().and_then(CostPart::from_name),
+                item.cost_part_percent,
+            ) {
+                (Some(part), Some(percent)) => format!(" – {} ({:.0} %)", part.label(), percent),
+                _ => String::new(),
+            };
+            let common_note = if item.common_consumption { " – Allgemeinverbrauch" } else { "" };
+            let segment_note = match (item.segment_start, item.segment_end, item.price_per_unit) {
+                (Some(segment_start), Some(segment_end), Some(price)) => format!(
+                    "<br><small>{} bis {}, {:.4} € je Einheit</small>",
+                    segment_start.format("%d.%m.%Y"),
+                    segment_end.format("%d.%m.%Y"),
+                    price.round_dp(4)
+                ),
+                _ => String::new(),
+            };
+            // Original invoice and the fraction of it falling into the billing period
+            let invoice_note = match (
+                item.invoice_start,
+                item.invoice_end,
+                item.invoice_amount,
+                item.invoice_days,
+                item.apportioned_days,
+            ) {
+                (Some(invoice_start), Some(invoice_end), Some(invoice_amount), Some(invoice_days), Some(apportioned_days)) => {
+                    format!(
+                        "<br><small>Rechnung {} bis {} über {:.2} €, anteilig {}/{} Tage</small>",
+                        invoice_start.format("%d.%m.%Y"),
+                        invoice_end.format("%d.%m.%Y"),
+                        round_cents(invoice_amount),
+                        apportioned_days,
+                        invoice_days
+                    )
+                }
+                _ => String::new(),
+            };
+            // Part of another cost type, e.g. the hot water share of the fuel costs
+            let source_note = match (&item.source_cost_type_name, item.source_share_percent) {
+                (Some(source_name), Some(percent)) => format!(
+                    "<br><small>{:.2} % der Kosten „{}“</small>",
+                    percent.round_dp(2),
+                    escape_html(source_name)
+                ),
+                _ => String::new(),
+            };
+            // CO2 costs in the item and the landlord's part already taken off (CO2KostAufG)
+            let co2_note = match (item.co2_cost, item.co2_landlord_amount) {
+                (Some(co2_cost), Some(landlord_amount)) if !landlord_amount.is_zero() => format!(
+                    "<br><small>Darin CO2-Kosten {:.2} €, abzüglich Anteil Vermieter {:.2} €</small>",
+                    round_cents(co2_cost),
+                    round_cents(landlord_amount)
+                ),
+                (Some(co2_cost), _) => {
+                    format!("<br><small>Darin CO2-Kosten {:.2} €</small>", round_cents(co2_cost))
+                }
+                _ => String::new(),
+            };
+            // Costs moved in from another cost type, or the part moved out of this one
+            let transfer_note = match (&item.transfer_name, &item.transfer_cost_type_name, item.transfer_amount) {
+                (Some(name), Some(other_name), Some(amount)) if amount.is_sign_negative() => format!(
+                    "<br><small>Abzüglich {:.2} € umgebucht nach „{}“ ({})</small>",
+                    round_cents(-amount),
+                    escape_html(other_name),
+                    escape_html(name)
+                ),
+                (Some(name), Some(other_name), Some(_)) => format!(
+                    "<br><small>Umgebucht aus „{}“ ({})</small>",
+                    escape_html(other_name),
+                    escape_html(name)
+                ),
+                _ => String::new(),
+            };
+            let estimate_mark = if item.consumption_estimated { " *" } else { "" };
+            // Decimal formatting truncates, so every value is rounded before printing
+            format!(
+                "<tr><td>{}{}{}{}{}{}{}{}</td><td class=\"num\">{:.2} €</td><td>{}</td><td class=\"num\">{:.2}</td><td class=\"num\">{:.2}{}</td><td class=\"num\">{:.2} €</td></tr>",
+                escape_html(&item.cost_type_name),
+                common_note,
+                part_note,
+                segment_note,
+                invoice_note,
+                source_note,
+                co2_note,
+                transfer_note,
+                round_cents(item.total_amount),
+                key_label,
+                item.total_quantity.round_dp(2),
+                item.tenant_quantity.round_dp(2),
+                estimate_mark,
+                round_cents(item.share_amount)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let estimate_note = if items.iter().any(|item| item.consumption_estimated) {
+        "<p><small>* Verbrauch geschätzt: Zählerstände zu Beginn oder Ende des Zeitraums wurden aus den umliegenden Ablesungen interpoliert oder, höchstens einen Monat über die erste oder letzte Ablesung hinaus, hochgerechnet.</small></p>"
+    } else {
+        ""
+    };
+
+    // Vacant units take part in person-based keys with the building's setting
+    let person_note = if items.iter().any(|item| item.allocation_key == AllocationKey::PersonCount.as_str()) {
+        format!("<p><small>Umlage nach Personen: {}.</small></p>", costs.vacancy_persons.label())
+    } else {
+        String::new()
+    };
+
+    // Derivation of the hot water share required by HeizkostenV § 9
+    let hot_water_note = if costs.hot_water_splits.is_empty() {
+        String::new()
+    } else {
+        let paragraphs: String = costs
+            .hot_water_splits
+            .iter()
+            .map(|split| {
+                let heat_quantity = match (split.hot_water_volume, split.hot_water_temperature) {
+                    (Some(volume), Some(temperature)) => format!(
+                        "Q = 2,5 kWh/(m³·K) × {:.2} m³ × ({:.0} °C − 10 °C) = {:.0} kWh",
+                        volume.round_dp(2),
+                        temperature.round_dp(0),
+                        split.heat_quantity.round_dp(0)
+                    ),
+                    _ => format!("Q = {:.0} kWh", split.heat_quantity.round_dp(0)),
+                };
+                // Decimal formatting truncates, so round before printing
+                let hot_water_percent = split.hot_water_percent.round_dp(2);
+                format!(
+                    "<p><small>„{}“ ({}): {}; B = Q / Hi = {:.0} kWh / {:.2} kWh/{} = {:.1} von {:.1} {} Brennstoff. Warmwasser {:.2} %, Heizung {:.2} %.</small></p>",
+                    escape_html(&split.fuel_cost_type_name),
+                    split.method.label(),
+                    heat_quantity,
+                    split.heat_quantity.round_dp(0),
+                    split.calorific_value.round_dp(2),
+                    split.fuel_unit.label(),
+                    split.hot_water_fuel.round_dp(1),
+                    split.fuel_quantity.round_dp(1),
+                    split.fuel_unit.label(),
+                    hot_water_percent,
+                    Decimal::ONE_HUNDRED - hot_water_percent
+                )
+            })
+            .collect();
+        format!(
+            "<div class=\"notes\"><h3>Aufteilung der Brennstoffkosten auf Heizung und Warmwasser (§ 9 HeizkostenV)</h3>{}</div>",
+            paragraphs
+        )
+    };
+
+    // Heating costs follow the heating demand over the year rather than calendar days
+    let degree_day_note = if costs.degree_day_cost_types.is_empty() {
+        String::new()
+    } else {
+        const MONTHS: [&str; 12] = [
+            "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+        ];
+        let table: Vec<String> = MONTHS
+            .iter()
+            .zip(costs.degree_days.monthly_per_mille)
+            .map(|(month, per_mille)| format!("{} {}", month, per_mille.round_dp(1).normalize()))
+            .collect();
+        format!(
+            "<div class=\"notes\"><p><small>Nutzungszeiten und geschätzte Zählerstände sind bei {} nach Gradtagzahlen gewichtet ({}, Promille je Monat: {}).</small></p></div>",
+            costs
+                .degree_day_cost_types
+                .iter()
+                .map(|name| format!("„{}“", escape_html(name)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            if costs.degree_days.custom { "eigene Tabelle" } else { "Standardtabelle" },
+            table.join(", ")
+        )
+    };
+
+    // Split of the CO2 costs required by CO2KostAufG § 7 (3)
+    let co2_note = if costs.co2_splits.is_empty() {
+        String::new()
+    } else {
+        let tenant_co2: Decimal = items.iter().filter_map(|item| item.co2_cost).sum();
+        let tenant_landlord: Decimal = items.iter().filter_map(|item| item.co2_landlord_amount).sum();
+        let paragraphs: String = costs
+            .co2_splits
+            .iter()
+            .map(|split| {
+                format!(
+                    "<p><small>„{}“: {:.0} kg CO2 aus {:.1} Einheiten Brennstoff, CO2-Kosten des Gebäudes {:.2} €. Bei {:.2} m² beheizter Wohnfläche sind das {:.1} kg CO2/m²/Jahr, Stufe {} des Stufenmodells: Mieter {} %, Vermieter {} %.</small></p>",
+                    escape_html(&split.cost_type_name),
+                    split.emissions_kg.round_dp(0),
+                    split.fuel_quantity.round_dp(1),
+                    round_cents(split.co2_cost),
+                    split.heated_area_m2.round_dp(2),
+                    split.specific_emissions.round_dp(1),
+                    split.tier,
+                    split.tenant_percent.normalize(),
+                    split.landlord_percent.normalize()
+                )
+            })
+            .collect();
+        format!(
+            "<div class=\"notes\"><h3>Aufteilung der Kohlendioxidkosten (CO2KostAufG)</h3>{}<p><small>Auf Sie entfallen CO2-Kosten von {:.2} €; davon trägt der Vermieter {:.2} €, die in den Heizkosten oben bereits abgezogen sind. Ihr Anteil beträgt {:.2} €.</small></p></div>",
+            paragraphs,
+            round_cents(tenant_co2),
+            round_cents(tenant_landlord),
+            round_cents(tenant_co2 - tenant_landlord)
+        )
+    };
+
+    // Derivation of the costs moved between cost types, e.g. the operating electricity of the heating
+    let transfer_note = if costs.transfers.is_empty() {
+        String::new()
+    } else {
+        let paragraphs: String = costs
+            .transfers
+            .iter()
+            .map(|transfer| {
+                let derivation = match (
+                    transfer.method,
+                    transfer.percent,
+                    &transfer.basis_cost_type_name,
+                    transfer.basis_amount,
+                    &transfer.meter_name,
+                    transfer.meter_consumption,
+                    transfer.price_per_unit,
+                ) {
+                    (TransferMethod::Percentage, Some(percent), Some(basis_name), Some(basis_amount), ..) => format!(
+                        "{} % der Kosten „{}“ ({:.2} €)",
+                        percent.normalize(),
+                        escape_html(basis_name),
+                        round_cents(basis_amount)
+                    ),
+                    (TransferMethod::Meter, .., Some(meter_name), Some(consumption), Some(price)) => format!(
+                        "Zähler „{}“: {} Einheiten × {:.4} €",
+                        escape_html(meter_name),
+                        consumption.round_dp(3).normalize(),
+                        price.round_dp(4)
+                    ),
+                    _ => String::new(),
+                };
+                format!(
+                    "<p><small>„{}“: {} = {:.2} €, umgebucht von „{}“ nach „{}“.</small></p>",
+                    escape_html(&transfer.name),
+                    derivation,
+                    round_cents(transfer.amount),
+                    escape_html(&transfer.source_cost_type_name),
+                    escape_html(&transfer.target_cost_type_name)
+                )
+            })
+            .collect();
+        format!("<div class=\"notes\"><h3>Umbuchungen zwischen Kostenarten</h3>{}</div>", paragraphs)
+    };
+
+    let prepayments_line = match prepayments.source {
+        PrepaymentSource::Received => format!(
+            "Abzüglich geleisteter Vorauszahlungen: {:.2} €",
+            round_cents(prepayments.deducted_amount)
+        ),
+        PrepaymentSource::NoneReceived if prepayments.scheduled_amount > Decimal::ZERO => format!(
+            "Es wurden keine Vorauszahlungen geleistet (laut Vorauszahlungsplan fällig: {:.2} €).",
+            round_cents(prepayments.scheduled_amount)
+        ),
+        PrepaymentSource::NoneReceived => "Es wurden keine Vorauszahlungen geleistet.".to_string(),
+    };
+    let balance_line = prepayment::balance_line(total_amount, prepayments);
+
     format!(r###"
     <!DOCTYPE html>
     <html>
@@ -561,6 +1452,9 @@ fn generate_billing_statement_html(billing_period: &BillingPeriod, tenant: &Tena
             .header {{ text-align: center; margin-bottom: 30px; }}
             .info {{ margin-bottom: 20px; }}
             .total {{ margin-top: 30px; font-weight: bold; }}
+            table {{ width: 100%; border-collapse: collapse; }}
+            th, td {{ border-bottom: 1px solid #ccc; padding: 6px; text-align: left; }}
+            .num {{ text-align: right; }}
         </style>
     </head>
     <body>
@@ -573,20 +1467,70 @@ fn generate_billing_statement_html(billing_period: &BillingPeriod, tenant: &Tena
             <h2>Mieter: {tenant_name}</h2>
             <p><strong>Personen:</strong> {persons}</p>
             <p><strong>Wohnfläche:</strong> {area} m²</p>
+            <p><strong>Nutzungszeitraum:</strong> {occupied_days} von {period_days} Tagen</p>
+        </div>
+
+        <div class="items">
+            <table>
+                <thead>
+                    <tr>
+                        <th>Kostenart</th>
+                        <th class="num">Gesamtkosten</th>
+                        <th>Umlageschlüssel</th>
+                        <th class="num">Gesamteinheiten</th>
+                        <th class="num">Ihre Einheiten</th>
+                        <th class="num">Ihr Anteil</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {item_rows}
+                </tbody>
+            </table>
+            {estimate_note}
+            {person_note}
         </div>
 
+        {hot_water_note}
+
+        {degree_day_note}
+
+        {co2_note}
+
+        {transfer_note}
+
         <div class="total">
             <p>Gesamtbetrag: {total_amount:.2} €</p>
+            <p>{prepayments_line}</p>
+            <p>{balance_line}</p>
         </div>
     </body>
     </html>
     "###,
     start_date = billing_period.start_date,
     end_date = billing_period.end_date,
-    tenant_name = tenant.name,
+    tenant_name = escape_html(&tenant.name),
     persons = tenant.number_of_persons,
     area = area,
-    total_amount = total_amount)
+    occupied_days = costs.occupied_days,
+    period_days = costs.period_days,
+    item_rows = item_rows,
+    estimate_note = estimate_note,
+    person_note = person_note,
+    hot_water_note = hot_water_note,
+    degree_day_note = degree_day_note,
+    co2_note = co2_note,
+    transfer_note = transfer_note,
+    total_amount = round_cents(total_amount),
+    prepayments_line = prepayments_line,
+    balance_line = balance_line)
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
 
 // Configure function to register routes
@@ -598,11 +1542,86 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .service(create_billing_period)
             .service(update_billing_period)
             .service(delete_billing_period)
+            .service(get_owner_overview)
             .service(generate_billing_statement)
+            .service(preview_billing_statements)
+            .service(get_billing_statement_trace)
+            .service(generate_billing_period_statements)
+            .service(get_billing_period_jobs)
+            .service(get_billing_job)
             .service(get_billing_statement)
+            .service(get_billing_statement_items)
             .service(get_billing_statements)
             .service(get_tenant_billing_statements)
             .service(get_billing_statement_html)
             .service(delete_billing_statement)
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::connection::SimpleConnection;
+    use rust_decimal_macros::dec;
+
+    const HOUSE_2024: &str = "
+        INSERT INTO property_units (id, name, living_area_m2) VALUES (1, 'EG', 100), (2, 'OG', 50);
+        INSERT INTO tenants (id, name, number_of_persons, property_unit_id)
+            VALUES (1, 'Mieter EG', 1, 1), (2, 'Mieter OG', 3, 2);
+        INSERT INTO billing_periods (id, building_id, start_date, end_date, name)
+            VALUES (1, 1, '2024-01-01', '2024-12-31', '2024');
+        INSERT INTO billing_jobs (id, billing_period_id, status) VALUES (1, 1, 'running');
+        INSERT INTO cost_types (id, name) VALUES (1, 'Grundsteuer');
+        INSERT INTO fixed_costs (cost_type_id, amount, billing_period_start, billing_period_end)
+            VALUES (1, '300', '2024-01-01', '2024-12-31');
+    ";
+
+    // Run the batch job 1 on a test database filled with `seed`; returns the job's status
+    // and the statements stored
+    fn run_job_2024(seed: &str) -> (Result<(), BatchError>, String, Vec<BillingStatement>) {
+        let conn = &mut db::test_connection();
+        conn.batch_execute(HOUSE_2024).unwrap();
+        conn.batch_execute(seed).unwrap();
+        let billing_period = billing_periods::table.first::<BillingPeriod>(conn).unwrap();
+
+        let result = generate_period_statements(conn, 1, &billing_period);
+        let status = billing_jobs::table.select(billing_jobs::status).first::<String>(conn).unwrap();
+        let statements = billing_statements::table
+            .order(billing_statements::tenant_id.asc())
+            .load::<BillingStatement>(conn)
+            .unwrap();
+        (result, status, statements)
+    }
+
+    #[test]
+    fn batch_job_stores_the_statements_of_all_tenants() {
+        let (result, status, statements) = run_job_2024("");
+
+        assert!(result.is_ok());
+        assert_eq!(status, "completed");
+        let totals: Vec<_> = statements.iter().map(|statement| statement.total_amount).collect();
+        assert_eq!(totals, vec![dec!(200), dec!(100)]);
+        assert!(statements.iter().all(|statement| statement.billing_job_id == Some(1)));
+    }
+
+    #[test]
+    fn batch_job_with_inconsistent_input_stores_no_statement() {
+        // Water without tariffs can't be priced
+        let (result, _, statements) = run_job_2024(
+            "
+            INSERT INTO cost_types (id, name, is_consumption_based, unit) VALUES (2, 'Wasser', 1, 'm3');
+            INSERT INTO meters (id, name, meter_type, unit, assignment_type, property_unit_id)
+                VALUES (1, 'EG', 'water', 'm3', 'unit', 1);
+            INSERT INTO meter_cost_types (meter_id, cost_type_id) VALUES (1, 2);
+            INSERT INTO meter_readings (meter_id, reading_date, value) VALUES
+                (1, '2024-01-01 00:00:00', '0'), (1, '2025-01-01 00:00:00', '40');
+            ",
+        );
+
+        let Err(BatchError::Inconsistent(problems)) = result else {
+            panic!("job should fail on the missing tariffs");
+        };
+        assert!(problems.iter().any(|problem| problem.contains("Tariffs of cost type 'Wasser'")));
+        assert!(statements.is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use diesel::prelude::*;
+use log::{error, info};
+use thiserror::Error;
+
+use crate::db;
+use crate::models::building::{
+    valid_vacancy_persons, Building, BuildingDto, BuildingUpdate, NewBuilding, VACANCY_PERSONS_ERROR,
+};
+use crate::schema::{billing_periods, cost_types, meters, property_units};
+use crate::DbPool;
+
+// Configure routes for buildings
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/buildings")
+            .service(get_all_buildings)
+            .service(get_building_by_id)
+            .service(create_building)
+            .service(update_building)
+            .service(delete_building),
+    );
+}
+
+// Reasons the building of a new record can't be determined
+#[derive(Debug, Error)]
+pub enum BuildingError {
+    #[error("Building with ID {0} not found")]
+    NotFound(i32),
+    #[error("building_id is required when there is not exactly one building")]
+    Ambiguous,
+    #[error("Error resolving building: {0}")]
+    Database(#[from] diesel::result::Error),
+}
+
+impl BuildingError {
+    pub fn response(&self) -> HttpResponse {
+        match self {
+            BuildingError::Database(e) => {
+                error!("Error resolving building: {}", e);
+                HttpResponse::InternalServerError().json(self.to_string())
+            }
+            _ => HttpResponse::BadRequest().json(self.to_string()),
+        }
+    }
+}
+
+// Building a new record belongs to. Without an explicit building the only building is used,
+// so installations managing a single house don't need to pass it.
+pub fn resolve_building_id(
+    conn: &mut SqliteConnection,
+    requested: Option<i32>,
+) -> Result<i32, BuildingError> {
+    use crate::schema::buildings::dsl::*;
+
+    let ids = match requested {
+        Some(building_id) => buildings.filter(id.eq(building_id)).select(id).load::<Option<i32>>(conn)?,
+        None => buildings.select(id).limit(2).load::<Option<i32>>(conn)?,
+    };
+
+    match (ids.as_slice(), requested) {
+        ([Some(building_id)], _) => Ok(*building_id),
+        (_, Some(building_id)) => Err(BuildingError::NotFound(building_id)),
+        (_, None) => Err(BuildingError::Ambiguous),
+    }
+}
+
+// GET /api/buildings
+#[get("")]
+async fn get_all_buildings(pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::buildings::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+
+    match buildings.order_by(name.asc()).load::<Building>(conn) {
+        Ok(results) => {
+            let dtos: Vec<BuildingDto> = results.into_iter().map(|building| building.into()).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!("Error loading buildings: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error loading buildings: {}", e))
+        }
+    }
+}
+
+// GET /api/buildings/{id}
+#[get("/{id}")]
+async fn get_building_by_id(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::buildings::dsl::*;
+
+    let building_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match buildings.filter(id.eq(building_id)).first::<Building>(conn) {
+        Ok(building) => HttpResponse::Ok().json(BuildingDto::from(building)),
+        Err(diesel::NotFound) => {
+            HttpResponse::NotFound().json(format!("Building with ID {} not found", building_id))
+        }
+        Err(e) => {
+            error!("Error finding building {}: {}", building_id, e);
+            HttpResponse::InternalServerError().json(format!("Error finding building: {}", e))
+        }
+    }
+}
+
+// POST /api/buildings
+#[post("")]
+async fn create_building(
+    new_building_json: web::Json<NewBuilding>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::buildings::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let new_building = new_building_json.0;
+
+    // Input validation
+    if new_building.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json("Building name cannot be empty");
+    }
+
+    if let Some(ref vacancy_val) = new_building.vacancy_persons {
+        if !valid_vacancy_persons(vacancy_val) {
+            return HttpResponse::BadRequest().json(VACANCY_PERSONS_ERROR);
+        }
+    }
+
+    match diesel::insert_into(buildings)
+        .values(&new_building)
+        .execute(conn)
+    {
+        Ok(_) => match buildings.order_by(id.desc()).first::<Building>(conn) {
+            Ok(created_building) => {
+                info!("Created building: {:?}", created_building);
+                HttpResponse::Created().json(BuildingDto::from(created_building))
+            }
+            Err(e) => {
+                error!("Error retrieving created building: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(format!("Building created but error retrieving it: {}", e))
+            }
+        },
+        Err(e) => {
+            error!("Error creating building: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error creating building: {}", e))
+        }
+    }
+}
+
+// PUT /api/buildings/{id}
+#[put("/{id}")]
+async fn update_building(
+    path: web::Path<i32>,
+    building_update: web::Json<BuildingUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::buildings::dsl::*;
+
+    let building_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    // Input validation
+    if let Some(ref name_val) = building_update.name {
+        if name_val.trim().is_empty() {
+            return HttpResponse::BadRequest().json("Building name cannot be empty");
+        }
+    }
+
+    if let Some(ref vacancy_val) = building_update.vacancy_persons {
+        if !valid_vacancy_persons(vacancy_val) {
+            return HttpResponse::BadRequest().json(VACANCY_PERSONS_ERROR);
+        }
+    }
+
+    match diesel::update(buildings.filter(id.eq(building_id)))
+        .set(building_update.into_inner())
+        .execute(conn)
+    {
+        Ok(0) => HttpResponse::NotFound().json(format!("Building with ID {} not found", building_id)),
+        Ok(_) => match buildings.filter(id.eq(building_id)).first::<Building>(conn) {
+            Ok(updated_building) => {
+                info!("Updated building: {:?}", updated_building);
+                HttpResponse::Ok().json(BuildingDto::from(updated_building))
+            }
+            Err(e) => {
+                error!("Error retrieving updated building: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(format!("Building updated but error retrieving it: {}", e))
+            }
+        },
+        Err(e) => {
+            error!("Error updating building: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error updating building: {}", e))
+        }
+    }
+}
+
+// DELETE /api/buildings/{id}
+// Only empty buildings can be deleted; units, meters, cost types and billing periods must be removed first
+#[delete("/{id}")]
+async fn delete_building(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::buildings::dsl::*;
+
+    let building_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    let in_use = property_units::table
+        .filter(property_units::building_id.eq(building_id))
+        .count()
+        .get_result::<i64>(conn)
+        .and_then(|units| {
+            let meter_count = meters::table
+                .filter(meters::building_id.eq(building_id))
+                .count()
+                .get_result::<i64>(conn)?;
+            let cost_type_count = cost_types::table
+                .filter(cost_types::building_id.eq(building_id))
+                .count()
+                .get_result::<i64>(conn)?;
+            let period_count = billing_periods::table
+                .filter(billing_periods::building_id.eq(building_id))
+                .count()
+                .get_result::<i64>(conn)?;
+            Ok(units + meter_count + cost_type_count + period_count > 0)
+        });
+
+    match in_use {
+        Ok(false) => (),
+        Ok(true) => {
+            return HttpResponse::BadRequest().json(
+                "Building still has property units, meters, cost types or billing periods assigned",
+            );
+        }
+        Err(e) => {
+            error!("Error checking building usage: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking building usage: {}", e));
+        }
+    }
+
+    match diesel::delete(buildings.filter(id.eq(building_id))).execute(conn) {
+        Ok(0) => HttpResponse::NotFound().json(format!("Building with ID {} not found", building_id)),
+        Ok(_) => {
+            info!("Deleted building with ID: {}", building_id);
+            HttpResponse::NoContent().finish()
+        }
+        Err(e) => {
+            error!("Error deleting building: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error deleting building: {}", e))
+        }
+    }
+}
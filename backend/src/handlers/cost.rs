@@ -1,13 +1,19 @@
 use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
 use diesel::prelude::*;
 use log::error;
+use rust_decimal::Decimal;
 
 use crate::db;
+use crate::handlers::building::resolve_building_id;
+use crate::handlers::meter::binding_conflicts;
+use crate::models::building::BuildingFilter;
 use crate::models::cost::{
-    AllocationMethod, AllocationMethodDto, CostType, CostTypeAllocation, CostTypeDto,
-    CostTypeUpdate, FixedCost, FixedCostDto, FixedCostUpdate, NewCostType, NewCostTypeAllocation,
-    NewFixedCost, NewTariff, Tariff, TariffDto, TariffUpdate,
+    AllocationMethod, AllocationMethodDto, BetrkvCategory, BetrkvCategoryDto, CostTransfer, CostTransferDto,
+    CostTransferUpdate, CostType, CostTypeAllocation, CostTypeDto, CostTypeUpdate, FixedCost, FixedCostDto,
+    FixedCostUpdate, NewCostTransfer, NewCostType, NewCostTypeAllocation, NewFixedCost, NewTariff, Tariff,
+    TariffDto, TariffUpdate, TransferMethod,
 };
+use crate::models::meter::Meter;
 use crate::DbPool;
 
 // Configure routes for cost management
@@ -15,6 +21,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/api/cost-types")
             .service(get_all_allocation_methods) // Move this to be the first service registered
+            .service(get_all_betrkv_categories)
             .service(get_all_cost_types)
             .service(get_cost_type_by_id)
             .service(create_cost_type)
@@ -39,6 +46,59 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .service(update_fixed_cost)
             .service(delete_fixed_cost),
     );
+
+    cfg.service(
+        web::scope("/api/cost-transfers")
+            .service(get_all_cost_transfers)
+            .service(get_cost_transfer_by_id)
+            .service(create_cost_transfer)
+            .service(update_cost_transfer)
+            .service(delete_cost_transfer),
+    );
+}
+
+// HeizkostenV §7/§8 require 50-70 % by consumption; §10 allows contracts with a higher share
+const CONSUMPTION_SHARE_ERROR: &str = "Consumption share must be between 50 and 100 percent";
+
+fn valid_consumption_share(percent: f32) -> bool {
+    (50.0..=100.0).contains(&percent)
+}
+
+const CO2_EMISSION_FACTOR_ERROR: &str = "CO2 emission factor must be greater than 0";
+
+// Fuel details of an invoice, used to split its CO2 costs (CO2KostAufG)
+fn fuel_details_error(
+    fuel_quantity: Option<Decimal>,
+    co2_emission_factor: Option<f32>,
+    co2_cost: Option<Decimal>,
+) -> Option<&'static str> {
+    if fuel_quantity.is_some_and(|quantity| quantity <= Decimal::ZERO) {
+        return Some("Fuel quantity must be greater than 0");
+    }
+    if co2_emission_factor.is_some_and(|factor| factor <= 0.0) {
+        return Some(CO2_EMISSION_FACTOR_ERROR);
+    }
+    if co2_cost.is_some_and(|cost| cost < Decimal::ZERO) {
+        return Some("CO2 cost cannot be negative");
+    }
+    if fuel_quantity.is_none() && (co2_emission_factor.is_some() || co2_cost.is_some()) {
+        return Some("CO2 details require the fuel quantity of the invoice");
+    }
+    None
+}
+
+// Helper function to check that a BetrKV category exists
+fn betrkv_category_exists(
+    category_id: i32,
+    conn: &mut SqliteConnection,
+) -> Result<bool, diesel::result::Error> {
+    use crate::schema::betrkv_categories::dsl::*;
+
+    betrkv_categories
+        .filter(id.eq(category_id))
+        .count()
+        .get_result::<i64>(conn)
+        .map(|count| count > 0)
 }
 
 // Helper function to load allocation methods for a cost type
@@ -66,14 +126,19 @@ fn load_allocation_methods_for_cost_type(
     }
 }
 
-// GET /api/cost-types
+// GET /api/cost-types?building_id=1
 #[get("")]
-async fn get_all_cost_types(pool: web::Data<DbPool>) -> impl Responder {
+async fn get_all_cost_types(filter: web::Query<BuildingFilter>, pool: web::Data<DbPool>) -> impl Responder {
     use crate::schema::cost_types::dsl::*;
 
     let conn = &mut db::get_connection(&pool);
 
-    match cost_types.order_by(name.asc()).load::<CostType>(conn) {
+    let mut query = cost_types.into_boxed();
+    if let Some(building_val) = filter.building_id {
+        query = query.filter(building_id.eq(building_val));
+    }
+
+    match query.order_by(name.asc()).load::<CostType>(conn) {
         Ok(results) => {
             let mut cost_type_dtos: Vec<CostTypeDto> = Vec::new();
 
@@ -129,7 +194,7 @@ async fn create_cost_type(
     use crate::schema::cost_types::dsl::*;
 
     let conn = &mut db::get_connection(&pool);
-    let new_cost_type = new_cost_type_json.0;
+    let mut new_cost_type = new_cost_type_json.0;
 
     // Input validation
     if new_cost_type.name.trim().is_empty() {
@@ -142,6 +207,36 @@ async fn create_cost_type(
             .json("Unit is required for consumption-based cost types");
     }
 
+    if let Some(percent) = new_cost_type.consumption_share_percent {
+        if !valid_consumption_share(percent) {
+            return HttpResponse::BadRequest().json(CONSUMPTION_SHARE_ERROR);
+        }
+    }
+
+    if new_cost_type.co2_emission_factor.is_some_and(|factor| factor <= 0.0) {
+        return HttpResponse::BadRequest().json(CO2_EMISSION_FACTOR_ERROR);
+    }
+
+    if let Some(category_id) = new_cost_type.betrkv_category_id {
+        match betrkv_category_exists(category_id, conn) {
+            Ok(true) => (),
+            Ok(false) => {
+                return HttpResponse::BadRequest()
+                    .json(format!("BetrKV category with ID {} not found", category_id));
+            }
+            Err(e) => {
+                error!("Error checking BetrKV category {}: {}", category_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(format!("Error checking BetrKV category: {}", e));
+            }
+        }
+    }
+
+    match resolve_building_id(conn, new_cost_type.building_id) {
+        Ok(building_val) => new_cost_type.building_id = Some(building_val),
+        Err(e) => return e.response(),
+    }
+
     match diesel::insert_into(cost_types)
         .values(&new_cost_type)
         .execute(conn)
@@ -190,6 +285,33 @@ async fn update_cost_type(
         }
     }
 
+    if let Some(Some(percent)) = update.consumption_share_percent {
+        if !valid_consumption_share(percent) {
+            return HttpResponse::BadRequest().json(CONSUMPTION_SHARE_ERROR);
+        }
+    }
+
+    if let Some(Some(factor)) = update.co2_emission_factor {
+        if factor <= 0.0 {
+            return HttpResponse::BadRequest().json(CO2_EMISSION_FACTOR_ERROR);
+        }
+    }
+
+    if let Some(Some(category_id)) = update.betrkv_category_id {
+        match betrkv_category_exists(category_id, conn) {
+            Ok(true) => (),
+            Ok(false) => {
+                return HttpResponse::BadRequest()
+                    .json(format!("BetrKV category with ID {} not found", category_id));
+            }
+            Err(e) => {
+                error!("Error checking BetrKV category {}: {}", category_id, e);
+                return HttpResponse::InternalServerError()
+                    .json(format!("Error checking BetrKV category: {}", e));
+            }
+        }
+    }
+
     // Check if the cost type exists
     match cost_types
         .filter(id.eq(cost_type_id))
@@ -207,6 +329,26 @@ async fn update_cost_type(
                     .json("Unit is required for consumption-based cost types");
             }
 
+            // Bound meters have to keep fitting the new unit
+            if let Some(ref unit_after) = update.unit {
+                let conflicts =
+                    binding_conflicts(conn, existing_cost_type.building_id, |binding| {
+                        binding.cost_type_unit = Some(unit_after.clone());
+                        binding.cost_type_id == cost_type_id
+                    });
+                match conflicts {
+                    Ok(conflicts) if conflicts.is_empty() => (),
+                    Ok(conflicts) => {
+                        return HttpResponse::BadRequest().json(conflicts.join("; "))
+                    }
+                    Err(e) => {
+                        error!("Error checking meter bindings: {}", e);
+                        return HttpResponse::InternalServerError()
+                            .json(format!("Error checking meter bindings: {}", e));
+                    }
+                }
+            }
+
             match diesel::update(cost_types.filter(id.eq(cost_type_id)))
                 .set(&update)
                 .execute(conn)
@@ -295,6 +437,29 @@ async fn get_all_allocation_methods(pool: web::Data<DbPool>) -> impl Responder {
     }
 }
 
+// GET /api/cost-types/betrkv-categories
+#[get("/betrkv-categories")]
+async fn get_all_betrkv_categories(pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::betrkv_categories::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+
+    match betrkv_categories
+        .order_by(position.asc())
+        .load::<BetrkvCategory>(conn)
+    {
+        Ok(results) => {
+            let dtos: Vec<BetrkvCategoryDto> = results.into_iter().map(|c| c.into()).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!("Error loading BetrKV categories: {}", e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error loading BetrKV categories: {}", e))
+        }
+    }
+}
+
 // POST /api/cost-types/{id}/allocation-methods/{method_id}
 #[post("/{id}/allocation-methods/{method_id}")]
 async fn assign_allocation_method(
@@ -427,12 +592,12 @@ async fn create_tariff(
     let new_tariff = new_tariff_json.0;
 
     // Input validation
-    if new_tariff.price_per_unit <= 0.0 {
+    if new_tariff.price_per_unit <= Decimal::ZERO {
         return HttpResponse::BadRequest().json("Price per unit must be greater than 0");
     }
 
     match diesel::insert_into(tariffs)
-        .values(&new_tariff)
+        .values(new_tariff)
         .execute(conn)
     {
         Ok(_) => match tariffs.order_by(id.desc()).first::<Tariff>(conn) {
@@ -465,13 +630,13 @@ async fn update_tariff(
 
     // Input validation
     if let Some(price) = update.price_per_unit {
-        if price <= 0.0 {
+        if price <= Decimal::ZERO {
             return HttpResponse::BadRequest().json("Price per unit must be greater than 0");
         }
     }
 
     match diesel::update(tariffs.filter(id.eq(tariff_id)))
-        .set(&update)
+        .set(update)
         .execute(conn)
     {
         Ok(count) => {
@@ -560,7 +725,7 @@ async fn create_fixed_cost(
     let new_cost = new_fixed_cost_json.0;
 
     // Input validation
-    if new_cost.amount <= 0.0 {
+    if new_cost.amount <= Decimal::ZERO {
         return HttpResponse::BadRequest().json("Amount must be greater than 0");
     }
 
@@ -568,8 +733,16 @@ async fn create_fixed_cost(
         return HttpResponse::BadRequest().json("Billing period end date must be after start date");
     }
 
+    if let Some(message) = fuel_details_error(
+        new_cost.fuel_quantity,
+        new_cost.co2_emission_factor,
+        new_cost.co2_cost,
+    ) {
+        return HttpResponse::BadRequest().json(message);
+    }
+
     match diesel::insert_into(fixed_costs)
-        .values(&new_cost)
+        .values(new_cost)
         .execute(conn)
     {
         Ok(_) => match fixed_costs.order_by(id.desc()).first::<FixedCost>(conn) {
@@ -610,7 +783,7 @@ async fn update_fixed_cost(
         Ok(existing_cost) => {
             // Input validation
             if let Some(amount_val) = update.amount {
-                if amount_val <= 0.0 {
+                if amount_val <= Decimal::ZERO {
                     return HttpResponse::BadRequest().json("Amount must be greater than 0");
                 }
             }
@@ -627,8 +800,16 @@ async fn update_fixed_cost(
                     .json("Billing period end date must be after start date");
             }
 
+            if let Some(message) = fuel_details_error(
+                update.fuel_quantity.unwrap_or(existing_cost.fuel_quantity),
+                update.co2_emission_factor.unwrap_or(existing_cost.co2_emission_factor),
+                update.co2_cost.unwrap_or(existing_cost.co2_cost),
+            ) {
+                return HttpResponse::BadRequest().json(message);
+            }
+
             match diesel::update(fixed_costs.filter(id.eq(fixed_cost_id)))
-                .set(&update)
+                .set(update)
                 .execute(conn)
             {
                 Ok(count) => {
@@ -693,3 +874,323 @@ async fn delete_fixed_cost(path: web::Path<i32>, pool: web::Data<DbPool>) -> imp
         }
     }
 }
+
+// Checks a cost transfer as it will be stored, next to the other transfers of its building
+// (all but `transfer_id` when updating); returns a message if it is invalid
+fn validate_cost_transfer(
+    transfer: &NewCostTransfer,
+    transfer_id: Option<i32>,
+    building_val: i32,
+    conn: &mut SqliteConnection,
+) -> Result<Option<String>, diesel::result::Error> {
+    use crate::schema::{cost_transfers, cost_types, meters};
+
+    if transfer.name.trim().is_empty() {
+        return Ok(Some("Cost transfer name cannot be empty".to_string()));
+    }
+
+    let Some(method) = TransferMethod::from_name(&transfer.method) else {
+        return Ok(Some(format!(
+            "Unknown transfer method '{}', expected percentage or meter",
+            transfer.method
+        )));
+    };
+
+    if transfer.source_cost_type_id == transfer.target_cost_type_id {
+        return Ok(Some("Source and target cost type must differ".to_string()));
+    }
+
+    let mut cost_type_ids = vec![transfer.source_cost_type_id, transfer.target_cost_type_id];
+    cost_type_ids.extend(transfer.basis_cost_type_id);
+    for cost_type_id in cost_type_ids {
+        let count = cost_types::table
+            .filter(cost_types::id.eq(cost_type_id))
+            .filter(cost_types::building_id.eq(building_val))
+            .count()
+            .get_result::<i64>(conn)?;
+        if count == 0 {
+            return Ok(Some(format!(
+                "Cost type with ID {} not found in building {}",
+                cost_type_id, building_val
+            )));
+        }
+    }
+
+    match method {
+        TransferMethod::Percentage => {
+            if !transfer.percent.is_some_and(|percent| percent > 0.0 && percent <= 100.0) {
+                return Ok(Some(
+                    "Percentage transfers need a percent greater than 0 and at most 100".to_string(),
+                ));
+            }
+            if transfer.basis_cost_type_id.is_none() {
+                return Ok(Some("Percentage transfers need a basis cost type".to_string()));
+            }
+        }
+        TransferMethod::Meter => {
+            let Some(meter_val) = transfer.meter_id else {
+                return Ok(Some("Meter transfers need a meter".to_string()));
+            };
+            let meter = meters::table
+                .filter(meters::id.eq(meter_val))
+                .filter(meters::building_id.eq(building_val))
+                .select(Meter::as_select())
+                .first::<Meter>(conn)
+                .optional()?;
+            if meter.is_none() {
+                return Ok(Some(format!(
+                    "Meter with ID {} not found in building {}",
+                    meter_val, building_val
+                )));
+            }
+            if transfer.price_per_unit.is_some_and(|price| price < Decimal::ZERO) {
+                return Ok(Some("Price per unit cannot be negative".to_string()));
+            }
+        }
+    }
+
+    let others: Vec<CostTransfer> = cost_transfers::table
+        .filter(cost_transfers::building_id.eq(building_val))
+        .filter(cost_transfers::id.ne(transfer_id.unwrap_or(0)))
+        .select(CostTransfer::as_select())
+        .load::<CostTransfer>(conn)?;
+
+    // Transfers are applied one after another, costs moved on by a second transfer or back
+    // to their source would depend on that order
+    if let Some(other) = others.iter().find(|other| {
+        other.target_cost_type_id == transfer.source_cost_type_id
+            || other.source_cost_type_id == transfer.target_cost_type_id
+    }) {
+        return Ok(Some(format!(
+            "Cost transfer '{}' already moves costs between cost types {} and {}, transfers can't be chained",
+            other.name, other.source_cost_type_id, other.target_cost_type_id
+        )));
+    }
+
+    if method == TransferMethod::Percentage {
+        let total_percent: f32 = others
+            .iter()
+            .filter(|other| {
+                other.source_cost_type_id == transfer.source_cost_type_id
+                    && TransferMethod::from_name(&other.method) == Some(TransferMethod::Percentage)
+            })
+            .filter_map(|other| other.percent)
+            .sum::<f32>()
+            + transfer.percent.unwrap_or_default();
+        if total_percent > 100.0 {
+            return Ok(Some(format!(
+                "Percentage transfers out of cost type {} add up to {} %, at most 100 % can be moved",
+                transfer.source_cost_type_id, total_percent
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
+// GET /api/cost-transfers?building_id=1
+#[get("")]
+async fn get_all_cost_transfers(filter: web::Query<BuildingFilter>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::cost_transfers::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+
+    let mut query = cost_transfers.into_boxed();
+    if let Some(building_val) = filter.building_id {
+        query = query.filter(building_id.eq(building_val));
+    }
+
+    match query
+        .order_by(id.asc())
+        .select(CostTransfer::as_select())
+        .load::<CostTransfer>(conn)
+    {
+        Ok(results) => {
+            let dtos: Vec<CostTransferDto> = results.into_iter().map(CostTransferDto::from).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!("Error loading cost transfers: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error loading cost transfers: {}", e))
+        }
+    }
+}
+
+// GET /api/cost-transfers/{id}
+#[get("/{id}")]
+async fn get_cost_transfer_by_id(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::cost_transfers::dsl::*;
+
+    let transfer_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match cost_transfers
+        .filter(id.eq(transfer_id))
+        .select(CostTransfer::as_select())
+        .first::<CostTransfer>(conn)
+    {
+        Ok(transfer) => HttpResponse::Ok().json(CostTransferDto::from(transfer)),
+        Err(diesel::NotFound) => {
+            HttpResponse::NotFound().json(format!("Cost transfer with ID {} not found", transfer_id))
+        }
+        Err(e) => {
+            error!("Error finding cost transfer {}: {}", transfer_id, e);
+            HttpResponse::InternalServerError().json(format!("Error finding cost transfer: {}", e))
+        }
+    }
+}
+
+// POST /api/cost-transfers
+#[post("")]
+async fn create_cost_transfer(
+    new_transfer_json: web::Json<NewCostTransfer>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::cost_transfers::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let mut new_transfer = new_transfer_json.0;
+
+    let building_val = match resolve_building_id(conn, new_transfer.building_id) {
+        Ok(building_val) => building_val,
+        Err(e) => return e.response(),
+    };
+    new_transfer.building_id = Some(building_val);
+
+    match validate_cost_transfer(&new_transfer, None, building_val, conn) {
+        Ok(None) => (),
+        Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+        Err(e) => {
+            error!("Error validating cost transfer: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error validating cost transfer: {}", e));
+        }
+    }
+
+    match diesel::insert_into(cost_transfers)
+        .values(new_transfer)
+        .execute(conn)
+    {
+        Ok(_) => match cost_transfers
+            .order_by(id.desc())
+            .select(CostTransfer::as_select())
+            .first::<CostTransfer>(conn)
+        {
+            Ok(created) => HttpResponse::Created().json(CostTransferDto::from(created)),
+            Err(e) => {
+                error!("Error retrieving created cost transfer: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Cost transfer created but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error creating cost transfer: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error creating cost transfer: {}", e))
+        }
+    }
+}
+
+// PUT /api/cost-transfers/{id}
+#[put("/{id}")]
+async fn update_cost_transfer(
+    path: web::Path<i32>,
+    update_json: web::Json<CostTransferUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::cost_transfers::dsl::*;
+
+    let transfer_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+    let update = update_json.0;
+
+    // Get existing record for validation
+    let existing = match cost_transfers
+        .filter(id.eq(transfer_id))
+        .select(CostTransfer::as_select())
+        .first::<CostTransfer>(conn)
+    {
+        Ok(existing) => existing,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound()
+                .json(format!("Cost transfer with ID {} not found", transfer_id));
+        }
+        Err(e) => {
+            error!("Error finding cost transfer {}: {}", transfer_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error finding cost transfer: {}", e));
+        }
+    };
+
+    // The transfer as it will be after the update
+    let updated_transfer = NewCostTransfer {
+        building_id: Some(existing.building_id),
+        name: update.name.clone().unwrap_or(existing.name),
+        source_cost_type_id: update.source_cost_type_id.unwrap_or(existing.source_cost_type_id),
+        target_cost_type_id: update.target_cost_type_id.unwrap_or(existing.target_cost_type_id),
+        method: update.method.clone().unwrap_or(existing.method),
+        percent: update.percent.unwrap_or(existing.percent),
+        basis_cost_type_id: update.basis_cost_type_id.unwrap_or(existing.basis_cost_type_id),
+        meter_id: update.meter_id.unwrap_or(existing.meter_id),
+        price_per_unit: update.price_per_unit.unwrap_or(existing.price_per_unit),
+    };
+
+    match validate_cost_transfer(&updated_transfer, Some(transfer_id), existing.building_id, conn) {
+        Ok(None) => (),
+        Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+        Err(e) => {
+            error!("Error validating cost transfer: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error validating cost transfer: {}", e));
+        }
+    }
+
+    match diesel::update(cost_transfers.filter(id.eq(transfer_id)))
+        .set(update)
+        .execute(conn)
+    {
+        Ok(_) => match cost_transfers
+            .filter(id.eq(transfer_id))
+            .select(CostTransfer::as_select())
+            .first::<CostTransfer>(conn)
+        {
+            Ok(updated) => HttpResponse::Ok().json(CostTransferDto::from(updated)),
+            Err(e) => {
+                error!("Error retrieving updated cost transfer: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Cost transfer updated but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error updating cost transfer {}: {}", transfer_id, e);
+            HttpResponse::InternalServerError().json(format!("Error updating cost transfer: {}", e))
+        }
+    }
+}
+
+// DELETE /api/cost-transfers/{id}
+#[delete("/{id}")]
+async fn delete_cost_transfer(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::cost_transfers::dsl::*;
+
+    let transfer_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(cost_transfers.filter(id.eq(transfer_id))).execute(conn) {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json("Cost transfer deleted successfully")
+            } else {
+                HttpResponse::NotFound()
+                    .json(format!("Cost transfer with ID {} not found", transfer_id))
+            }
+        }
+        Err(e) => {
+            error!("Error deleting cost transfer {}: {}", transfer_id, e);
+            HttpResponse::InternalServerError().json(format!("Error deleting cost transfer: {}", e))
+        }
+    }
+}
@@ -0,0 +1,1154 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use diesel::prelude::*;
+use log::error;
+
+use crate::db;
+use crate::handlers::building::resolve_building_id;
+use crate::models::building::BuildingFilter;
+use crate::models::cost::CostType;
+use crate::models::heating::{
+    BurnerCalibration, BurnerCalibrationDto, BurnerCalibrationUpdate, DegreeDayTableInput,
+    FuelDefaults, FuelEnergyQuery, FuelType, FuelUnit, HeatingSystem, HeatingSystemDto,
+    HeatingSystemFuel, HeatingSystemFuelDto, HeatingSystemFuelFilter, HeatingSystemFuelInput,
+    HeatingSystemFuelUpdate, HeatingSystemUpdate, NewBurnerCalibration, NewDegreeDayShare,
+    NewHeatingSystem, NewHeatingSystemFuel, OilConsumptionQuery,
+};
+use crate::models::meter::{units_match, Meter};
+use crate::services::fuel::{self, FuelError};
+use crate::services::{heating, interpolation};
+use crate::DbPool;
+
+// Configure routes for burner calibrations, heating systems and their fuels
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/burner-calibrations")
+            .service(get_all_calibrations)
+            .service(create_calibration)
+            .service(update_calibration)
+            .service(delete_calibration),
+    );
+
+    cfg.service(
+        web::scope("/api/heating-systems")
+            .service(get_all_heating_systems)
+            .service(get_heating_system_by_id)
+            .service(get_heating_system_energy)
+            .service(create_heating_system)
+            .service(update_heating_system)
+            .service(delete_heating_system),
+    );
+
+    cfg.service(
+        web::scope("/api/heating-system-fuels")
+            .service(get_fuel_types)
+            .service(get_all_fuels)
+            .service(create_fuel)
+            .service(update_fuel)
+            .service(delete_fuel),
+    );
+
+    cfg.service(
+        web::scope("/api/heating")
+            .service(get_oil_consumption)
+            .service(get_degree_days)
+            .service(set_degree_days)
+            .service(reset_degree_days),
+    );
+}
+
+// Helper function to build the calibration DTOs with the end of each version's validity
+fn calibration_dtos(calibrations: Vec<BurnerCalibration>) -> Vec<BurnerCalibrationDto> {
+    let valid_to_dates: Vec<_> = calibrations
+        .iter()
+        .map(|calibration| heating::valid_to(&calibrations, calibration))
+        .collect();
+
+    calibrations
+        .into_iter()
+        .zip(valid_to_dates)
+        .map(|(calibration, valid_to)| BurnerCalibrationDto {
+            valid_to,
+            ..calibration.into()
+        })
+        .collect()
+}
+
+// Helper function to build the DTO of a single version, including the end of its validity
+fn calibration_dto(
+    calibration: BurnerCalibration,
+    conn: &mut SqliteConnection,
+) -> Result<BurnerCalibrationDto, diesel::result::Error> {
+    use crate::schema::burner_calibrations::dsl::*;
+
+    let stage_calibrations = burner_calibrations
+        .filter(building_id.eq(calibration.building_id))
+        .filter(stage.eq(calibration.stage))
+        .load::<BurnerCalibration>(conn)?;
+
+    Ok(BurnerCalibrationDto {
+        valid_to: heating::valid_to(&stage_calibrations, &calibration),
+        ..calibration.into()
+    })
+}
+
+// Helper function to check that no other version of the stage starts on the same day
+fn version_exists(
+    building_val: i32,
+    stage_val: i32,
+    valid_from_val: chrono::NaiveDate,
+    exclude_id: Option<i32>,
+    conn: &mut SqliteConnection,
+) -> Result<bool, diesel::result::Error> {
+    use crate::schema::burner_calibrations::dsl::*;
+
+    let existing = burner_calibrations
+        .filter(building_id.eq(building_val))
+        .filter(stage.eq(stage_val))
+        .filter(valid_from.eq(valid_from_val))
+        .load::<BurnerCalibration>(conn)?;
+
+    Ok(existing.iter().any(|calibration| calibration.id != exclude_id))
+}
+
+// GET /api/burner-calibrations?building_id=1
+#[get("")]
+async fn get_all_calibrations(filter: web::Query<BuildingFilter>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::burner_calibrations::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+
+    let mut query = burner_calibrations.into_boxed();
+    if let Some(building_val) = filter.building_id {
+        query = query.filter(building_id.eq(building_val));
+    }
+
+    match query
+        .order_by((building_id.asc(), stage.asc(), valid_from.asc()))
+        .load::<BurnerCalibration>(conn)
+    {
+        Ok(results) => HttpResponse::Ok().json(calibration_dtos(results)),
+        Err(e) => {
+            error!("Error loading burner calibrations: {}", e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error loading burner calibrations: {}", e))
+        }
+    }
+}
+
+// POST /api/burner-calibrations
+// Adds a new version; earlier versions stay valid until its valid_from date
+#[post("")]
+async fn create_calibration(
+    new_calibration_json: web::Json<NewBurnerCalibration>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::burner_calibrations::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let mut new_calibration = new_calibration_json.0;
+
+    // Input validation
+    if new_calibration.stage != 1 && new_calibration.stage != 2 {
+        return HttpResponse::BadRequest().json("Burner stage must be 1 or 2");
+    }
+
+    if new_calibration.litres_per_hour <= 0.0 {
+        return HttpResponse::BadRequest().json("Litres per hour must be greater than 0");
+    }
+
+    let calibration_building_id = match resolve_building_id(conn, new_calibration.building_id) {
+        Ok(building_val) => building_val,
+        Err(e) => return e.response(),
+    };
+    new_calibration.building_id = Some(calibration_building_id);
+
+    match version_exists(
+        calibration_building_id,
+        new_calibration.stage,
+        new_calibration.valid_from,
+        None,
+        conn,
+    ) {
+        Ok(false) => (),
+        Ok(true) => {
+            return HttpResponse::BadRequest().json(format!(
+                "A calibration of stage {} valid from {} already exists",
+                new_calibration.stage, new_calibration.valid_from
+            ));
+        }
+        Err(e) => {
+            error!("Error checking existing burner calibrations: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking existing burner calibrations: {}", e));
+        }
+    }
+
+    match diesel::insert_into(burner_calibrations)
+        .values(&new_calibration)
+        .execute(conn)
+    {
+        Ok(_) => match burner_calibrations
+            .order_by(id.desc())
+            .first::<BurnerCalibration>(conn)
+        {
+            Ok(created) => match calibration_dto(created, conn) {
+                Ok(dto) => HttpResponse::Created().json(dto),
+                Err(e) => {
+                    error!("Error loading burner calibration versions: {}", e);
+                    HttpResponse::InternalServerError()
+                        .json(format!("Error loading burner calibration versions: {}", e))
+                }
+            },
+            Err(e) => {
+                error!("Error retrieving created burner calibration: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Burner calibration created but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error creating burner calibration: {}", e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error creating burner calibration: {}", e))
+        }
+    }
+}
+
+// PUT /api/burner-calibrations/{id}
+// Corrects an existing version; a changed rate from a later date is a new version instead
+#[put("/{id}")]
+async fn update_calibration(
+    path: web::Path<i32>,
+    update_json: web::Json<BurnerCalibrationUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::burner_calibrations::dsl::*;
+
+    let calibration_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+    let update = update_json.0;
+
+    // Get existing record for validation
+    let existing = match burner_calibrations
+        .filter(id.eq(calibration_id))
+        .first::<BurnerCalibration>(conn)
+    {
+        Ok(existing) => existing,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound()
+                .json(format!("Burner calibration with ID {} not found", calibration_id));
+        }
+        Err(e) => {
+            error!("Error finding burner calibration {}: {}", calibration_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error finding burner calibration: {}", e));
+        }
+    };
+
+    // Input validation
+    if let Some(rate_val) = update.litres_per_hour {
+        if rate_val <= 0.0 {
+            return HttpResponse::BadRequest().json("Litres per hour must be greater than 0");
+        }
+    }
+
+    if let Some(valid_from_val) = update.valid_from {
+        match version_exists(existing.building_id, existing.stage, valid_from_val, existing.id, conn) {
+            Ok(false) => (),
+            Ok(true) => {
+                return HttpResponse::BadRequest().json(format!(
+                    "A calibration of stage {} valid from {} already exists",
+                    existing.stage, valid_from_val
+                ));
+            }
+            Err(e) => {
+                error!("Error checking existing burner calibrations: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(format!("Error checking existing burner calibrations: {}", e));
+            }
+        }
+    }
+
+    match diesel::update(burner_calibrations.filter(id.eq(calibration_id)))
+        .set(&update)
+        .execute(conn)
+    {
+        Ok(_) => match burner_calibrations
+            .filter(id.eq(calibration_id))
+            .first::<BurnerCalibration>(conn)
+        {
+            Ok(updated) => match calibration_dto(updated, conn) {
+                Ok(dto) => HttpResponse::Ok().json(dto),
+                Err(e) => {
+                    error!("Error loading burner calibration versions: {}", e);
+                    HttpResponse::InternalServerError()
+                        .json(format!("Error loading burner calibration versions: {}", e))
+                }
+            },
+            Err(e) => {
+                error!("Error retrieving updated burner calibration: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Burner calibration updated but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error updating burner calibration {}: {}", calibration_id, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error updating burner calibration: {}", e))
+        }
+    }
+}
+
+// DELETE /api/burner-calibrations/{id}
+#[delete("/{id}")]
+async fn delete_calibration(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::burner_calibrations::dsl::*;
+
+    let calibration_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(burner_calibrations.filter(id.eq(calibration_id))).execute(conn) {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json("Burner calibration deleted successfully")
+            } else {
+                HttpResponse::NotFound()
+                    .json(format!("Burner calibration with ID {} not found", calibration_id))
+            }
+        }
+        Err(e) => {
+            error!("Error deleting burner calibration {}: {}", calibration_id, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error deleting burner calibration: {}", e))
+        }
+    }
+}
+
+// Helper function to validate the cost types and meters of a heating system.
+// Returns an error message for the client if the configuration is not acceptable.
+fn validate_heating_system(
+    system_id: Option<i32>,
+    system: &NewHeatingSystem,
+    system_building_id: i32,
+    conn: &mut SqliteConnection,
+) -> Result<Option<String>, diesel::result::Error> {
+    use crate::schema::{cost_types, heating_systems, meters};
+
+    if system.name.trim().is_empty() {
+        return Ok(Some("Heating system name cannot be empty".to_string()));
+    }
+
+    // The § 9 formula uses the heating of the water above 10 °C
+    if system.hot_water_temperature <= 10.0 {
+        return Ok(Some("Hot water temperature must be above 10 °C".to_string()));
+    }
+
+    let cost_type_ids = [
+        system.fuel_cost_type_id,
+        system.heating_cost_type_id,
+        system.hot_water_cost_type_id,
+    ];
+    if cost_type_ids[0] == cost_type_ids[1]
+        || cost_type_ids[0] == cost_type_ids[2]
+        || cost_type_ids[1] == cost_type_ids[2]
+    {
+        return Ok(Some(
+            "Fuel, heating and hot water cost types must be different".to_string(),
+        ));
+    }
+
+    for cost_type_id_val in cost_type_ids {
+        match cost_types::table
+            .filter(cost_types::id.eq(cost_type_id_val))
+            .select(CostType::as_select())
+            .first::<CostType>(conn)
+            .optional()?
+        {
+            None => return Ok(Some(format!("Cost type with ID {} not found", cost_type_id_val))),
+            Some(cost_type) if cost_type.building_id != system_building_id => {
+                return Ok(Some(format!(
+                    "Cost type '{}' belongs to a different building",
+                    cost_type.name
+                )));
+            }
+            Some(_) => (),
+        }
+    }
+
+    // Each fuel cost type can only be split once
+    let other_systems = heating_systems::table
+        .filter(heating_systems::fuel_cost_type_id.eq(system.fuel_cost_type_id))
+        .select(HeatingSystem::as_select())
+        .load::<HeatingSystem>(conn)?;
+    if let Some(other) = other_systems.iter().find(|other| other.id != system_id) {
+        return Ok(Some(format!(
+            "The fuel cost type is already split by heating system '{}'",
+            other.name
+        )));
+    }
+
+    for (meter_id_val, heat_meter) in [
+        (system.fuel_meter_id, false),
+        (system.hot_water_heat_meter_id, true),
+    ] {
+        let Some(meter_id_val) = meter_id_val else {
+            continue;
+        };
+
+        let meter = match meters::table
+            .filter(meters::id.eq(meter_id_val))
+            .first::<Meter>(conn)
+            .optional()?
+        {
+            Some(meter) => meter,
+            None => return Ok(Some(format!("Meter with ID {} not found", meter_id_val))),
+        };
+
+        if meter.building_id != system_building_id {
+            return Ok(Some(format!("Meter '{}' belongs to a different building", meter.name)));
+        }
+
+        if heat_meter && !units_match(&meter.unit, "kWh") {
+            return Ok(Some(format!(
+                "Hot water heat meter '{}' must measure kWh, not '{}'",
+                meter.name, meter.unit
+            )));
+        }
+
+        if !heat_meter && FuelUnit::parse(&meter.unit).is_none() {
+            return Ok(Some(format!(
+                "Fuel meter '{}' must measure l, m³, kg or kWh, not '{}'",
+                meter.name, meter.unit
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
+// GET /api/heating-systems?building_id=1
+#[get("")]
+async fn get_all_heating_systems(
+    filter: web::Query<BuildingFilter>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::heating_systems::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+
+    let mut query = heating_systems.into_boxed();
+    if let Some(building_val) = filter.building_id {
+        query = query.filter(building_id.eq(building_val));
+    }
+
+    match query
+        .order_by(name.asc())
+        .select(HeatingSystem::as_select())
+        .load::<HeatingSystem>(conn)
+    {
+        Ok(results) => {
+            let dtos: Vec<HeatingSystemDto> = results.into_iter().map(HeatingSystemDto::from).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!("Error loading heating systems: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error loading heating systems: {}", e))
+        }
+    }
+}
+
+// GET /api/heating-systems/{id}
+#[get("/{id}")]
+async fn get_heating_system_by_id(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::heating_systems::dsl::*;
+
+    let system_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match heating_systems
+        .filter(id.eq(system_id))
+        .select(HeatingSystem::as_select())
+        .first::<HeatingSystem>(conn)
+    {
+        Ok(system) => HttpResponse::Ok().json(HeatingSystemDto::from(system)),
+        Err(diesel::NotFound) => {
+            HttpResponse::NotFound().json(format!("Heating system with ID {} not found", system_id))
+        }
+        Err(e) => {
+            error!("Error finding heating system {}: {}", system_id, e);
+            HttpResponse::InternalServerError().json(format!("Error finding heating system: {}", e))
+        }
+    }
+}
+
+// POST /api/heating-systems
+#[post("")]
+async fn create_heating_system(
+    new_system_json: web::Json<NewHeatingSystem>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::heating_systems::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let mut new_system = new_system_json.0;
+
+    let system_building_id = match resolve_building_id(conn, new_system.building_id) {
+        Ok(building_val) => building_val,
+        Err(e) => return e.response(),
+    };
+    new_system.building_id = Some(system_building_id);
+
+    match validate_heating_system(None, &new_system, system_building_id, conn) {
+        Ok(None) => (),
+        Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+        Err(e) => {
+            error!("Error validating heating system: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error validating heating system: {}", e));
+        }
+    }
+
+    match diesel::insert_into(heating_systems)
+        .values(&new_system)
+        .execute(conn)
+    {
+        Ok(_) => match heating_systems
+            .order_by(id.desc())
+            .select(HeatingSystem::as_select())
+            .first::<HeatingSystem>(conn)
+        {
+            Ok(created) => HttpResponse::Created().json(HeatingSystemDto::from(created)),
+            Err(e) => {
+                error!("Error retrieving created heating system: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Heating system created but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error creating heating system: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error creating heating system: {}", e))
+        }
+    }
+}
+
+// PUT /api/heating-systems/{id}
+#[put("/{id}")]
+async fn update_heating_system(
+    path: web::Path<i32>,
+    update_json: web::Json<HeatingSystemUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::heating_systems::dsl::*;
+
+    let system_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+    let update = update_json.0;
+
+    // Get existing record for validation
+    let existing = match heating_systems
+        .filter(id.eq(system_id))
+        .select(HeatingSystem::as_select())
+        .first::<HeatingSystem>(conn)
+    {
+        Ok(existing) => existing,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound()
+                .json(format!("Heating system with ID {} not found", system_id));
+        }
+        Err(e) => {
+            error!("Error finding heating system {}: {}", system_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error finding heating system: {}", e));
+        }
+    };
+
+    // Validate the system as it will be after the update
+    let updated_system = NewHeatingSystem {
+        building_id: Some(existing.building_id),
+        name: update.name.clone().unwrap_or(existing.name),
+        fuel_cost_type_id: update.fuel_cost_type_id.unwrap_or(existing.fuel_cost_type_id),
+        heating_cost_type_id: update.heating_cost_type_id.unwrap_or(existing.heating_cost_type_id),
+        hot_water_cost_type_id: update
+            .hot_water_cost_type_id
+            .unwrap_or(existing.hot_water_cost_type_id),
+        fuel_meter_id: update.fuel_meter_id.unwrap_or(existing.fuel_meter_id),
+        hot_water_heat_meter_id: update
+            .hot_water_heat_meter_id
+            .unwrap_or(existing.hot_water_heat_meter_id),
+        hot_water_temperature: update
+            .hot_water_temperature
+            .unwrap_or(existing.hot_water_temperature),
+    };
+
+    match validate_heating_system(existing.id, &updated_system, existing.building_id, conn) {
+        Ok(None) => (),
+        Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+        Err(e) => {
+            error!("Error validating heating system: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error validating heating system: {}", e));
+        }
+    }
+
+    match diesel::update(heating_systems.filter(id.eq(system_id)))
+        .set(&update)
+        .execute(conn)
+    {
+        Ok(_) => match heating_systems
+            .filter(id.eq(system_id))
+            .select(HeatingSystem::as_select())
+            .first::<HeatingSystem>(conn)
+        {
+            Ok(updated) => HttpResponse::Ok().json(HeatingSystemDto::from(updated)),
+            Err(e) => {
+                error!("Error retrieving updated heating system: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Heating system updated but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error updating heating system {}: {}", system_id, e);
+            HttpResponse::InternalServerError().json(format!("Error updating heating system: {}", e))
+        }
+    }
+}
+
+// DELETE /api/heating-systems/{id}
+// The fuel costs are allocated as they are again afterwards
+#[delete("/{id}")]
+async fn delete_heating_system(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::heating_systems::dsl::*;
+
+    let system_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(heating_systems.filter(id.eq(system_id))).execute(conn) {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json("Heating system deleted successfully")
+            } else {
+                HttpResponse::NotFound()
+                    .json(format!("Heating system with ID {} not found", system_id))
+            }
+        }
+        Err(e) => {
+            error!("Error deleting heating system {}: {}", system_id, e);
+            HttpResponse::InternalServerError().json(format!("Error deleting heating system: {}", e))
+        }
+    }
+}
+
+// GET /api/heating-systems/{id}/energy?start_date=YYYY-MM-DD&end_date=YYYY-MM-DD
+// Fuel burned in the period with its energy content, useful heat and CO2 emissions
+#[get("/{id}/energy")]
+async fn get_heating_system_energy(
+    path: web::Path<i32>,
+    query: web::Query<FuelEnergyQuery>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::heating_systems::dsl::*;
+
+    let system_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    if query.end_date < query.start_date {
+        return HttpResponse::BadRequest().json("End date must not be before start date");
+    }
+
+    let system = match heating_systems
+        .filter(id.eq(system_id))
+        .select(HeatingSystem::as_select())
+        .first::<HeatingSystem>(conn)
+    {
+        Ok(system) => system,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound()
+                .json(format!("Heating system with ID {} not found", system_id));
+        }
+        Err(e) => {
+            error!("Error finding heating system {}: {}", system_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error finding heating system: {}", e));
+        }
+    };
+
+    match fuel::fuel_energy(conn, &system, query.start_date, query.end_date) {
+        Ok(energy) => HttpResponse::Ok().json(energy),
+        Err(FuelError::Undetermined(message)) => HttpResponse::UnprocessableEntity().json(message),
+        Err(FuelError::Database(e)) => {
+            error!("Error calculating fuel energy of heating system {}: {}", system_id, e);
+            HttpResponse::InternalServerError().json(format!("Error calculating fuel energy: {}", e))
+        }
+    }
+}
+
+// Helper function to build the fuel DTOs with the end of each version's validity
+fn fuel_dtos(fuels: Vec<HeatingSystemFuel>) -> Vec<HeatingSystemFuelDto> {
+    let valid_to_dates: Vec<_> = fuels.iter().map(|fuel| fuel::valid_to(&fuels, fuel)).collect();
+
+    fuels
+        .into_iter()
+        .zip(valid_to_dates)
+        .map(|(fuel, valid_to)| HeatingSystemFuelDto {
+            valid_to,
+            ..fuel.into()
+        })
+        .collect()
+}
+
+// Helper function to build the DTO of a single version, including the end of its validity
+fn fuel_dto(
+    fuel_version: HeatingSystemFuel,
+    conn: &mut SqliteConnection,
+) -> Result<HeatingSystemFuelDto, diesel::result::Error> {
+    let system_fuels = fuel::load_fuels(conn, fuel_version.heating_system_id)?;
+
+    Ok(HeatingSystemFuelDto {
+        valid_to: fuel::valid_to(&system_fuels, &fuel_version),
+        ..fuel_version.into()
+    })
+}
+
+// Helper function to validate a fuel version as it will be stored.
+// Returns an error message for the client if the values are not acceptable.
+fn validate_fuel(
+    fuel_id: Option<i32>,
+    fuel_version: &NewHeatingSystemFuel,
+    conn: &mut SqliteConnection,
+) -> Result<Option<String>, diesel::result::Error> {
+    use crate::schema::{heating_system_fuels, heating_systems};
+
+    if fuel_version.calorific_value <= 0.0 {
+        return Ok(Some("Calorific value must be greater than 0".to_string()));
+    }
+
+    if FuelUnit::from(fuel_version.fuel_unit.clone()) == FuelUnit::KilowattHour
+        && fuel_version.calorific_value != 1.0
+    {
+        return Ok(Some("Fuel measured in kWh has a calorific value of 1".to_string()));
+    }
+
+    if fuel_version.density.is_some_and(|density| density <= 0.0) {
+        return Ok(Some("Density must be greater than 0".to_string()));
+    }
+
+    if fuel_version.co2_emission_factor.is_some_and(|factor| factor < 0.0) {
+        return Ok(Some("CO2 emission factor cannot be negative".to_string()));
+    }
+
+    // Condensing boilers exceed 100 % relative to Hi, but not by more than about 10 %
+    if fuel_version
+        .efficiency
+        .is_some_and(|efficiency| efficiency <= 0.0 || efficiency > 1.1)
+    {
+        return Ok(Some("Efficiency must be greater than 0 and at most 1.1".to_string()));
+    }
+
+    let system_count = heating_systems::table
+        .filter(heating_systems::id.eq(fuel_version.heating_system_id))
+        .count()
+        .get_result::<i64>(conn)?;
+    if system_count == 0 {
+        return Ok(Some(format!(
+            "Heating system with ID {} not found",
+            fuel_version.heating_system_id
+        )));
+    }
+
+    let same_day = heating_system_fuels::table
+        .filter(heating_system_fuels::heating_system_id.eq(fuel_version.heating_system_id))
+        .filter(heating_system_fuels::valid_from.eq(fuel_version.valid_from))
+        .select(HeatingSystemFuel::as_select())
+        .load::<HeatingSystemFuel>(conn)?;
+    if same_day.iter().any(|other| other.id != fuel_id) {
+        return Ok(Some(format!(
+            "A fuel version valid from {} already exists",
+            fuel_version.valid_from
+        )));
+    }
+
+    Ok(None)
+}
+
+// GET /api/heating-system-fuels/fuel-types
+// Fuel types with the standard values used for left out fields
+#[get("/fuel-types")]
+async fn get_fuel_types() -> impl Responder {
+    let fuel_types: Vec<FuelDefaults> = FuelType::ALL
+        .iter()
+        .filter_map(|fuel_type| fuel_type.defaults())
+        .collect();
+
+    HttpResponse::Ok().json(fuel_types)
+}
+
+// GET /api/heating-system-fuels?heating_system_id=1
+#[get("")]
+async fn get_all_fuels(
+    filter: web::Query<HeatingSystemFuelFilter>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::heating_system_fuels::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+
+    let mut query = heating_system_fuels.into_boxed();
+    if let Some(system_val) = filter.heating_system_id {
+        query = query.filter(heating_system_id.eq(system_val));
+    }
+
+    match query
+        .order_by((heating_system_id.asc(), valid_from.asc()))
+        .select(HeatingSystemFuel::as_select())
+        .load::<HeatingSystemFuel>(conn)
+    {
+        Ok(results) => HttpResponse::Ok().json(fuel_dtos(results)),
+        Err(e) => {
+            error!("Error loading heating system fuels: {}", e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error loading heating system fuels: {}", e))
+        }
+    }
+}
+
+// POST /api/heating-system-fuels
+// Adds a new version; earlier versions stay valid until its valid_from date.
+// Values left out are taken from the standard values of the fuel type.
+#[post("")]
+async fn create_fuel(
+    input_json: web::Json<HeatingSystemFuelInput>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::heating_system_fuels::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let input = input_json.0;
+
+    let defaults = input.fuel_type.defaults();
+    let Some(unit_val) = input
+        .fuel_unit
+        .or(defaults.map(|standard| standard.fuel_unit))
+    else {
+        return HttpResponse::BadRequest().json("Fuel unit is required for fuel type other");
+    };
+    // Calorific value and density of the standard values only fit the standard unit
+    let standard = defaults.filter(|standard| standard.fuel_unit == unit_val);
+    let standard_calorific_value = if unit_val == FuelUnit::KilowattHour {
+        Some(1.0)
+    } else {
+        standard.map(|standard| standard.calorific_value)
+    };
+    let Some(calorific_val) = input.calorific_value.or(standard_calorific_value) else {
+        return HttpResponse::BadRequest().json(format!(
+            "Calorific value is required for {} measured in {}",
+            input.fuel_type, unit_val
+        ));
+    };
+
+    let new_fuel = NewHeatingSystemFuel {
+        heating_system_id: input.heating_system_id,
+        fuel_type: input.fuel_type.to_string(),
+        fuel_unit: unit_val.to_string(),
+        calorific_value: calorific_val,
+        density: input.density.or(standard.and_then(|standard| standard.density)),
+        co2_emission_factor: input
+            .co2_emission_factor
+            .or(defaults.map(|standard| standard.co2_emission_factor)),
+        efficiency: input.efficiency,
+        valid_from: input.valid_from,
+        notes: input.notes,
+    };
+
+    match validate_fuel(None, &new_fuel, conn) {
+        Ok(None) => (),
+        Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+        Err(e) => {
+            error!("Error validating heating system fuel: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error validating heating system fuel: {}", e));
+        }
+    }
+
+    match diesel::insert_into(heating_system_fuels)
+        .values(&new_fuel)
+        .execute(conn)
+    {
+        Ok(_) => match heating_system_fuels
+            .order_by(id.desc())
+            .select(HeatingSystemFuel::as_select())
+            .first::<HeatingSystemFuel>(conn)
+        {
+            Ok(created) => match fuel_dto(created, conn) {
+                Ok(dto) => HttpResponse::Created().json(dto),
+                Err(e) => {
+                    error!("Error loading heating system fuel versions: {}", e);
+                    HttpResponse::InternalServerError()
+                        .json(format!("Error loading heating system fuel versions: {}", e))
+                }
+            },
+            Err(e) => {
+                error!("Error retrieving created heating system fuel: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Heating system fuel created but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error creating heating system fuel: {}", e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error creating heating system fuel: {}", e))
+        }
+    }
+}
+
+// PUT /api/heating-system-fuels/{id}
+// Corrects an existing version; a change from a later date is a new version instead
+#[put("/{id}")]
+async fn update_fuel(
+    path: web::Path<i32>,
+    update_json: web::Json<HeatingSystemFuelUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::heating_system_fuels::dsl::*;
+
+    let fuel_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+    let update = update_json.0;
+
+    // Get existing record for validation
+    let existing = match heating_system_fuels
+        .filter(id.eq(fuel_id))
+        .select(HeatingSystemFuel::as_select())
+        .first::<HeatingSystemFuel>(conn)
+    {
+        Ok(existing) => existing,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound()
+                .json(format!("Heating system fuel with ID {} not found", fuel_id));
+        }
+        Err(e) => {
+            error!("Error finding heating system fuel {}: {}", fuel_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error finding heating system fuel: {}", e));
+        }
+    };
+
+    // The version as it will be after the update
+    let updated_fuel = NewHeatingSystemFuel {
+        heating_system_id: existing.heating_system_id,
+        fuel_type: update
+            .fuel_type
+            .map_or(existing.fuel_type, |fuel_type_val| fuel_type_val.to_string()),
+        fuel_unit: update
+            .fuel_unit
+            .map_or(existing.fuel_unit, |unit_val| unit_val.to_string()),
+        calorific_value: update.calorific_value.unwrap_or(existing.calorific_value),
+        density: update.density.unwrap_or(existing.density),
+        co2_emission_factor: update
+            .co2_emission_factor
+            .unwrap_or(existing.co2_emission_factor),
+        efficiency: update.efficiency.unwrap_or(existing.efficiency),
+        valid_from: update.valid_from.unwrap_or(existing.valid_from),
+        notes: update.notes.unwrap_or(existing.notes),
+    };
+
+    match validate_fuel(existing.id, &updated_fuel, conn) {
+        Ok(None) => (),
+        Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+        Err(e) => {
+            error!("Error validating heating system fuel: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error validating heating system fuel: {}", e));
+        }
+    }
+
+    match diesel::update(heating_system_fuels.filter(id.eq(fuel_id)))
+        .set(&updated_fuel)
+        .execute(conn)
+    {
+        Ok(_) => match heating_system_fuels
+            .filter(id.eq(fuel_id))
+            .select(HeatingSystemFuel::as_select())
+            .first::<HeatingSystemFuel>(conn)
+        {
+            Ok(updated) => match fuel_dto(updated, conn) {
+                Ok(dto) => HttpResponse::Ok().json(dto),
+                Err(e) => {
+                    error!("Error loading heating system fuel versions: {}", e);
+                    HttpResponse::InternalServerError()
+                        .json(format!("Error loading heating system fuel versions: {}", e))
+                }
+            },
+            Err(e) => {
+                error!("Error retrieving updated heating system fuel: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Heating system fuel updated but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error updating heating system fuel {}: {}", fuel_id, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error updating heating system fuel: {}", e))
+        }
+    }
+}
+
+// DELETE /api/heating-system-fuels/{id}
+#[delete("/{id}")]
+async fn delete_fuel(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::heating_system_fuels::dsl::*;
+
+    let fuel_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(heating_system_fuels.filter(id.eq(fuel_id))).execute(conn) {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json("Heating system fuel deleted successfully")
+            } else {
+                HttpResponse::NotFound()
+                    .json(format!("Heating system fuel with ID {} not found", fuel_id))
+            }
+        }
+        Err(e) => {
+            error!("Error deleting heating system fuel {}: {}", fuel_id, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error deleting heating system fuel: {}", e))
+        }
+    }
+}
+
+// GET /api/heating/oil-consumption?start_date=YYYY-MM-DD&end_date=YYYY-MM-DD&building_id=1
+#[get("/oil-consumption")]
+async fn get_oil_consumption(
+    query: web::Query<OilConsumptionQuery>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    let conn = &mut db::get_connection(&pool);
+
+    if query.end_date < query.start_date {
+        return HttpResponse::BadRequest().json("End date must not be before start date");
+    }
+
+    let building_val = match resolve_building_id(conn, query.building_id) {
+        Ok(building_val) => building_val,
+        Err(e) => return e.response(),
+    };
+
+    match heating::oil_consumption(conn, building_val, query.start_date, query.end_date) {
+        Ok(consumption) => HttpResponse::Ok().json(consumption),
+        Err(e) => {
+            error!("Error calculating oil consumption: {}", e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error calculating oil consumption: {}", e))
+        }
+    }
+}
+
+// GET /api/heating/degree-days?building_id=1
+// The building's own degree-day table, or the standard table if it has none
+#[get("/degree-days")]
+async fn get_degree_days(filter: web::Query<BuildingFilter>, pool: web::Data<DbPool>) -> impl Responder {
+    let conn = &mut db::get_connection(&pool);
+
+    let building_val = match resolve_building_id(conn, filter.building_id) {
+        Ok(building_val) => building_val,
+        Err(e) => return e.response(),
+    };
+
+    match interpolation::load_degree_days(conn, building_val) {
+        Ok(degree_days) => HttpResponse::Ok().json(degree_days),
+        Err(e) => {
+            error!("Error loading degree days: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error loading degree days: {}", e))
+        }
+    }
+}
+
+// PUT /api/heating/degree-days
+// Replaces the standard table with the building's own twelve monthly shares
+#[put("/degree-days")]
+async fn set_degree_days(
+    table_json: web::Json<DegreeDayTableInput>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::degree_day_shares::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let table = table_json.0;
+
+    // Input validation
+    if table.monthly_per_mille.len() != 12 {
+        return HttpResponse::BadRequest().json("Degree-day shares are required for all twelve months");
+    }
+
+    if table.monthly_per_mille.iter().any(|share| *share < 0.0) {
+        return HttpResponse::BadRequest().json("Degree-day shares must not be negative");
+    }
+
+    if table.monthly_per_mille.iter().sum::<f32>() <= 0.0 {
+        return HttpResponse::BadRequest().json("At least one month must have a degree-day share");
+    }
+
+    let building_val = match resolve_building_id(conn, table.building_id) {
+        Ok(building_val) => building_val,
+        Err(e) => return e.response(),
+    };
+
+    let shares: Vec<NewDegreeDayShare> = table
+        .monthly_per_mille
+        .iter()
+        .enumerate()
+        .map(|(index, share)| NewDegreeDayShare {
+            building_id: building_val,
+            month: index as i32 + 1,
+            per_mille: *share,
+        })
+        .collect();
+
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(degree_day_shares.filter(building_id.eq(building_val))).execute(conn)?;
+        diesel::insert_into(degree_day_shares).values(&shares).execute(conn)?;
+        interpolation::load_degree_days(conn, building_val)
+    });
+
+    match result {
+        Ok(degree_days) => HttpResponse::Ok().json(degree_days),
+        Err(e) => {
+            error!("Error saving degree days: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error saving degree days: {}", e))
+        }
+    }
+}
+
+// DELETE /api/heating/degree-days?building_id=1
+// Returns to the standard table
+#[delete("/degree-days")]
+async fn reset_degree_days(filter: web::Query<BuildingFilter>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::degree_day_shares::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+
+    let building_val = match resolve_building_id(conn, filter.building_id) {
+        Ok(building_val) => building_val,
+        Err(e) => return e.response(),
+    };
+
+    match diesel::delete(degree_day_shares.filter(building_id.eq(building_val))).execute(conn) {
+        Ok(_) => HttpResponse::Ok().json(interpolation::DegreeDays::default()),
+        Err(e) => {
+            error!("Error resetting degree days: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error resetting degree days: {}", e))
+        }
+    }
+}
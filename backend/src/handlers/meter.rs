@@ -1,10 +1,22 @@
 use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
 use diesel::prelude::*;
 use log::{error, info};
+use rust_decimal::Decimal;
 
 use crate::db;
-use crate::models::meter::{Meter, MeterDto, MeterInputDto, MeterUpdate, NewMeter};
+use crate::handlers::building::resolve_building_id;
+use crate::models::building::BuildingFilter;
+use crate::models::cost::CostType;
+use crate::models::decimal::SqlDecimal;
+use crate::models::meter::{
+    conversion_mismatch, BindingUnits, Meter, MeterCostType, MeterCostTypeDto,
+    MeterCostTypeInput, MeterDto, MeterInputDto, MeterUpdate, NewMeter, NewMeterCostType,
+    ResidualQuery, BURNER_HOURS_METER_TYPE,
+};
 use crate::models::property_unit::PropertyUnit;
+use crate::services::allocation::load_binding_units;
+use crate::services::interpolation;
+use crate::services::meter_hierarchy::MeterTree;
 use crate::DbPool;
 
 // Configure routes for meters
@@ -12,23 +24,136 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/api/meters")
             .service(get_all_meters)
+            .service(get_common_meters)
+            .service(get_residual_consumption)
             .service(get_meter_by_id)
             .service(get_meters_by_property_unit)
-            .service(get_common_meters)
             .service(create_meter)
             .service(update_meter)
-            .service(delete_meter),
+            .service(delete_meter)
+            .service(get_meter_cost_types)
+            .service(bind_cost_type)
+            .service(unbind_cost_type),
     );
 }
 
-// GET /api/meters
+// Helper function to validate the position of a meter in a meter hierarchy.
+// Returns an error message for the client if the parent or residual unit is not acceptable.
+fn validate_hierarchy(
+    meter_id: Option<i32>,
+    meter_unit: &str,
+    meter_building_id: i32,
+    parent_meter_id_val: Option<i32>,
+    residual_property_unit_id_val: Option<i32>,
+    conn: &mut SqliteConnection,
+) -> Result<Option<String>, diesel::result::Error> {
+    use crate::schema::meters::dsl::*;
+    use crate::schema::property_units;
+
+    if let Some(parent_id) = parent_meter_id_val {
+        if Some(parent_id) == meter_id {
+            return Ok(Some("A meter can't be its own main meter".to_string()));
+        }
+
+        let parent = match meters.filter(id.eq(parent_id)).first::<Meter>(conn) {
+            Ok(parent) => parent,
+            Err(diesel::NotFound) => {
+                return Ok(Some(format!("Main meter with ID {} not found", parent_id)));
+            }
+            Err(e) => return Err(e),
+        };
+
+        if parent.building_id != meter_building_id {
+            return Ok(Some("Main meter belongs to a different building".to_string()));
+        }
+
+        if parent.unit != meter_unit {
+            return Ok(Some(format!(
+                "Sub-meter unit '{}' doesn't match the main meter's unit '{}'",
+                meter_unit, parent.unit
+            )));
+        }
+
+        // Walk up from the new main meter; reaching this meter again would form a cycle
+        let mut ancestor = parent.parent_meter_id;
+        while let Some(ancestor_id) = ancestor {
+            if Some(ancestor_id) == meter_id {
+                return Ok(Some("Meter hierarchy must not contain cycles".to_string()));
+            }
+            ancestor = meters
+                .filter(id.eq(ancestor_id))
+                .select(parent_meter_id)
+                .first::<Option<i32>>(conn)
+                .optional()?
+                .flatten();
+        }
+    }
+
+    if let Some(unit_id) = residual_property_unit_id_val {
+        match property_units::table
+            .filter(property_units::id.eq(unit_id))
+            .select(property_units::building_id)
+            .first::<i32>(conn)
+            .optional()?
+        {
+            None => return Ok(Some(format!("Property unit with ID {} not found", unit_id))),
+            Some(unit_building_id) if unit_building_id != meter_building_id => {
+                return Ok(Some(format!(
+                    "Property unit with ID {} belongs to a different building",
+                    unit_id
+                )));
+            }
+            Some(_) => (),
+        }
+    }
+
+    Ok(None)
+}
+
+// Helper function to load the cost types a meter is bound to
+fn load_cost_types_for_meter(
+    meter_id_val: i32,
+    conn: &mut SqliteConnection,
+) -> Vec<MeterCostTypeDto> {
+    use crate::schema::cost_types;
+    use crate::schema::meter_cost_types::dsl::*;
+
+    match meter_cost_types
+        .filter(meter_id.eq(meter_id_val))
+        .inner_join(cost_types::table)
+        .order_by(cost_types::name.asc())
+        .select((MeterCostType::as_select(), CostType::as_select()))
+        .load::<(MeterCostType, CostType)>(conn)
+    {
+        Ok(bindings) => bindings
+            .into_iter()
+            .map(|(binding, cost_type)| MeterCostTypeDto {
+                cost_type_id: binding.cost_type_id,
+                cost_type_name: cost_type.name,
+                cost_type_unit: cost_type.unit,
+                conversion_factor: binding.conversion_factor,
+            })
+            .collect(),
+        Err(e) => {
+            error!("Error loading cost types for meter {}: {}", meter_id_val, e);
+            Vec::new()
+        }
+    }
+}
+
+// GET /api/meters?building_id=1
 #[get("")]
-async fn get_all_meters(pool: web::Data<DbPool>) -> impl Responder {
+async fn get_all_meters(filter: web::Query<BuildingFilter>, pool: web::Data<DbPool>) -> impl Responder {
     use crate::schema::meters::dsl::*;
 
     let conn = &mut db::get_connection(&pool);
 
-    match meters.order_by(name.asc()).load::<Meter>(conn) {
+    let mut query = meters.into_boxed();
+    if let Some(building_val) = filter.building_id {
+        query = query.filter(building_id.eq(building_val));
+    }
+
+    match query.order_by(name.asc()).load::<Meter>(conn) {
         Ok(results) => {
             let dtos: Vec<MeterDto> = results.into_iter().map(|meter| meter.into()).collect();
             HttpResponse::Ok().json(dtos)
@@ -111,14 +236,19 @@ async fn get_meters_by_property_unit(
     }
 }
 
-// GET /api/meters/common
+// GET /api/meters/common?building_id=1
 #[get("/common")]
-async fn get_common_meters(pool: web::Data<DbPool>) -> impl Responder {
+async fn get_common_meters(filter: web::Query<BuildingFilter>, pool: web::Data<DbPool>) -> impl Responder {
     use crate::schema::meters::dsl::*;
 
     let conn = &mut db::get_connection(&pool);
 
-    match meters
+    let mut query = meters.into_boxed();
+    if let Some(building_val) = filter.building_id {
+        query = query.filter(building_id.eq(building_val));
+    }
+
+    match query
         .filter(assignment_type.eq("common"))
         .order_by(name.asc())
         .load::<Meter>(conn)
@@ -159,14 +289,46 @@ async fn create_meter(
         return HttpResponse::BadRequest().json("Meter unit cannot be empty");
     }
 
-    // Check if property unit exists if this is a unit meter
-    if new_meter.assignment_type == MeterAssignment::Unit {
+    if new_meter.meter_type == BURNER_HOURS_METER_TYPE && new_meter.burner_stage.is_none() {
+        return HttpResponse::BadRequest().json("Burner stage is required for burner-hour meters");
+    }
+
+    if new_meter.burner_stage.is_some_and(|stage| stage != 1 && stage != 2) {
+        return HttpResponse::BadRequest().json("Burner stage must be 1 or 2");
+    }
+
+    if new_meter
+        .residual_warning_percent
+        .is_some_and(|percent| !(0.0..=100.0).contains(&percent))
+    {
+        return HttpResponse::BadRequest()
+            .json("Residual warning limit must be between 0 and 100 percent");
+    }
+
+    if new_meter.assignment_type == MeterAssignment::Unit
+        && new_meter.residual_property_unit_id.is_some()
+    {
+        return HttpResponse::BadRequest()
+            .json("Only common meters can pass their residual to a property unit");
+    }
+
+    // Unit meters belong to their unit's building, common meters to the given one
+    let meter_building_id = if new_meter.assignment_type == MeterAssignment::Unit {
         if let Some(property_unit_id_val) = new_meter.property_unit_id {
             match property_units::table
                 .filter(property_units::id.eq(property_unit_id_val))
                 .first::<PropertyUnit>(conn)
             {
-                Ok(_) => (),
+                Ok(property_unit) => {
+                    if new_meter
+                        .building_id
+                        .is_some_and(|building_val| building_val != property_unit.building_id)
+                    {
+                        return HttpResponse::BadRequest()
+                            .json("Unit meters belong to the building of their property unit");
+                    }
+                    property_unit.building_id
+                }
                 Err(diesel::NotFound) => {
                     return HttpResponse::BadRequest().json(format!(
                         "Property unit with ID {} not found",
@@ -183,9 +345,34 @@ async fn create_meter(
             return HttpResponse::BadRequest()
                 .json("Property unit ID is required for meters assigned to a unit");
         }
+    } else {
+        match resolve_building_id(conn, new_meter.building_id) {
+            Ok(building_val) => building_val,
+            Err(e) => return e.response(),
+        }
+    };
+
+    match validate_hierarchy(
+        None,
+        &new_meter.unit,
+        meter_building_id,
+        new_meter.parent_meter_id,
+        new_meter.residual_property_unit_id,
+        conn,
+    ) {
+        Ok(None) => (),
+        Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+        Err(e) => {
+            error!("Error checking meter hierarchy: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking meter hierarchy: {}", e));
+        }
     }
 
-    let new_meter = NewMeter::from(new_meter.into_inner());
+    let new_meter = NewMeter {
+        building_id: Some(meter_building_id),
+        ..NewMeter::from(new_meter.into_inner())
+    };
 
     match diesel::insert_into(meters).values(&new_meter).execute(conn) {
         Ok(_) => {
@@ -241,13 +428,34 @@ async fn update_meter(
         }
     }
 
+    if let Some(Some(stage_val)) = meter_update.burner_stage {
+        if stage_val != 1 && stage_val != 2 {
+            return HttpResponse::BadRequest().json("Burner stage must be 1 or 2");
+        }
+    }
+
+    if let Some(Some(percent)) = meter_update.residual_warning_percent {
+        if !(0.0..=100.0).contains(&percent) {
+            return HttpResponse::BadRequest()
+                .json("Residual warning limit must be between 0 and 100 percent");
+        }
+    }
+
+    if let Some(ref method_val) = meter_update.interpolation_method {
+        if method_val != "linear" && method_val != "seasonal" {
+            return HttpResponse::BadRequest()
+                .json("Interpolation method must be either 'linear' or 'seasonal'");
+        }
+    }
+
     // Check if property unit exists if it's being updated
+    let mut new_unit_building_id = None;
     if let Some(Some(property_unit_id_val)) = meter_update.property_unit_id {
         match property_units::table
             .filter(property_units::id.eq(property_unit_id_val))
             .first::<PropertyUnit>(conn)
         {
-            Ok(_) => (),
+            Ok(property_unit) => new_unit_building_id = Some(property_unit.building_id),
             Err(diesel::NotFound) => {
                 return HttpResponse::BadRequest().json(format!(
                     "Property unit with ID {} not found",
@@ -281,6 +489,13 @@ async fn update_meter(
     let current_meter = meters.filter(id.eq(meter_id)).first::<Meter>(conn).unwrap();
     let mut update = meter_update.into_inner();
 
+    // Burner-hour meters must keep a burner stage
+    let type_after = update.meter_type.as_deref().unwrap_or(&current_meter.meter_type);
+    let stage_after = update.burner_stage.unwrap_or(current_meter.burner_stage);
+    if type_after == BURNER_HOURS_METER_TYPE && stage_after.is_none() {
+        return HttpResponse::BadRequest().json("Burner stage is required for burner-hour meters");
+    }
+
     // If changing to "unit" assignment type, we must have a property_unit_id
     if let Some(ref assignment_type_val) = update.assignment_type {
         if assignment_type_val == "unit"
@@ -297,6 +512,76 @@ async fn update_meter(
         }
     }
 
+    // Only common meters can pass their residual to a unit
+    let assignment_after = update
+        .assignment_type
+        .as_deref()
+        .unwrap_or(&current_meter.assignment_type);
+    let residual_unit_after = update
+        .residual_property_unit_id
+        .unwrap_or(current_meter.residual_property_unit_id);
+    if assignment_after == "unit" && residual_unit_after.is_some() {
+        if update.residual_property_unit_id.is_some() {
+            return HttpResponse::BadRequest()
+                .json("Only common meters can pass their residual to a property unit");
+        }
+        update.residual_property_unit_id = Some(None);
+    }
+
+    // Unit meters follow their unit's building; common meters may be moved to another building
+    let building_after = if assignment_after == "unit" {
+        if update.building_id.is_some() {
+            return HttpResponse::BadRequest()
+                .json("Unit meters belong to the building of their property unit");
+        }
+        new_unit_building_id.unwrap_or(current_meter.building_id)
+    } else {
+        match update.building_id {
+            Some(building_val) => match resolve_building_id(conn, Some(building_val)) {
+                Ok(building_val) => building_val,
+                Err(e) => return e.response(),
+            },
+            None => current_meter.building_id,
+        }
+    };
+    update.building_id = Some(building_after);
+
+    let unit_after = update.unit.as_deref().unwrap_or(&current_meter.unit);
+    if update.unit.is_some() {
+        let conflicts = binding_conflicts(conn, current_meter.building_id, |binding| {
+            binding.meter_unit = unit_after.to_string();
+            binding.meter_id == meter_id
+        });
+        match conflicts {
+            Ok(conflicts) if conflicts.is_empty() => (),
+            Ok(conflicts) => return HttpResponse::BadRequest().json(conflicts.join("; ")),
+            Err(e) => {
+                error!("Error checking cost type bindings: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(format!("Error checking cost type bindings: {}", e));
+            }
+        }
+    }
+    let parent_after = update
+        .parent_meter_id
+        .unwrap_or(current_meter.parent_meter_id);
+    match validate_hierarchy(
+        current_meter.id,
+        unit_after,
+        building_after,
+        parent_after,
+        update.residual_property_unit_id.flatten(),
+        conn,
+    ) {
+        Ok(None) => (),
+        Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+        Err(e) => {
+            error!("Error checking meter hierarchy: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking meter hierarchy: {}", e));
+        }
+    }
+
     match diesel::update(meters.filter(id.eq(meter_id)))
         .set(update)
         .execute(conn)
@@ -322,6 +607,25 @@ async fn update_meter(
     }
 }
 
+// Bindings of a building that no longer fit after `change` swapped in a new unit; `change`
+// returns whether the binding is affected at all
+pub(crate) fn binding_conflicts(
+    conn: &mut SqliteConnection,
+    building_id: i32,
+    change: impl Fn(&mut BindingUnits) -> bool,
+) -> Result<Vec<String>, diesel::result::Error> {
+    Ok(load_binding_units(conn, building_id)?
+        .into_iter()
+        .filter_map(|mut binding| {
+            if change(&mut binding) {
+                binding.mismatch()
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
 // DELETE /api/meters/{id}
 #[delete("/{id}")]
 async fn delete_meter(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
@@ -345,6 +649,16 @@ async fn delete_meter(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Res
         return HttpResponse::NotFound().json(format!("Meter with ID {} not found", meter_id));
     }
 
+    // Sub-meters of a deleted main meter become standalone meters
+    if let Err(e) = diesel::update(meters.filter(parent_meter_id.eq(meter_id)))
+        .set(parent_meter_id.eq(None::<i32>))
+        .execute(conn)
+    {
+        error!("Error detaching sub-meters of meter {}: {}", meter_id, e);
+        return HttpResponse::InternalServerError()
+            .json(format!("Error detaching sub-meters: {}", e));
+    }
+
     match diesel::delete(meters.filter(id.eq(meter_id))).execute(conn) {
         Ok(_) => {
             info!("Deleted meter with ID: {}", meter_id);
@@ -356,3 +670,206 @@ async fn delete_meter(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Res
         }
     }
 }
+
+// GET /api/meters/{id}/residual?start_date=YYYY-MM-DD&end_date=YYYY-MM-DD
+// Consumption of a main meter minus its sub-meters, with plausibility warnings
+#[get("/{id}/residual")]
+async fn get_residual_consumption(
+    path: web::Path<i32>,
+    query: web::Query<ResidualQuery>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::meters::dsl::*;
+
+    let meter_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    if query.end_date < query.start_date {
+        return HttpResponse::BadRequest().json("End date must not be before start date");
+    }
+
+    let meter = match meters.filter(id.eq(meter_id)).first::<Meter>(conn) {
+        Ok(meter) => meter,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound().json(format!("Meter with ID {} not found", meter_id));
+        }
+        Err(e) => {
+            error!("Error finding meter {}: {}", meter_id, e);
+            return HttpResponse::InternalServerError().json(format!("Error finding meter: {}", e));
+        }
+    };
+
+    let tree = match interpolation::load_degree_days(conn, meter.building_id)
+        .and_then(|degree_days| MeterTree::load(conn, &meter, &degree_days))
+    {
+        Ok(tree) => tree,
+        Err(e) => {
+            error!("Error loading sub-meters of meter {}: {}", meter_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error loading sub-meters: {}", e));
+        }
+    };
+
+    match tree.residual(query.start_date, query.end_date, false) {
+        Some(residual) => HttpResponse::Ok().json(residual),
+        None => HttpResponse::UnprocessableEntity().json(format!(
+            "Not enough readings to determine the consumption of meter '{}'",
+            meter.name
+        )),
+    }
+}
+
+// GET /api/meters/{id}/cost-types
+#[get("/{id}/cost-types")]
+async fn get_meter_cost_types(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    let meter_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    HttpResponse::Ok().json(load_cost_types_for_meter(meter_id, conn))
+}
+
+// PUT /api/meters/{id}/cost-types/{cost_type_id}
+// Binds the meter to the cost type or updates the conversion factor of an existing binding
+#[put("/{id}/cost-types/{cost_type_id}")]
+async fn bind_cost_type(
+    path: web::Path<(i32, i32)>,
+    input: web::Json<MeterCostTypeInput>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::meter_cost_types::dsl::*;
+    use crate::schema::{cost_types, meters};
+
+    let (meter_id_val, cost_type_id_val) = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    if input.conversion_factor.is_some_and(|factor| factor <= Decimal::ZERO) {
+        return HttpResponse::BadRequest().json("Conversion factor must be greater than 0");
+    }
+
+    let meter = match meters::table
+        .filter(meters::id.eq(meter_id_val))
+        .first::<Meter>(conn)
+    {
+        Ok(meter) => meter,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound().json(format!("Meter with ID {} not found", meter_id_val));
+        }
+        Err(e) => {
+            error!("Error finding meter {}: {}", meter_id_val, e);
+            return HttpResponse::InternalServerError().json(format!("Error finding meter: {}", e));
+        }
+    };
+
+    let cost_type = match cost_types::table
+        .filter(cost_types::id.eq(cost_type_id_val))
+        .select(CostType::as_select())
+        .first::<CostType>(conn)
+    {
+        Ok(cost_type) => cost_type,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound()
+                .json(format!("Cost type with ID {} not found", cost_type_id_val));
+        }
+        Err(e) => {
+            error!("Error finding cost type {}: {}", cost_type_id_val, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error finding cost type: {}", e));
+        }
+    };
+
+    if meter.building_id != cost_type.building_id {
+        return HttpResponse::BadRequest().json(format!(
+            "Meter '{}' and cost type '{}' belong to different buildings",
+            meter.name, cost_type.name
+        ));
+    }
+
+    // Meter values are billed in the cost type's unit, so only different units take a factor
+    let factor = input.conversion_factor.unwrap_or(Decimal::ONE);
+    if let Some(message) = conversion_mismatch(
+        &meter.name,
+        &meter.unit,
+        &cost_type.name,
+        cost_type.unit.as_deref(),
+        factor,
+    ) {
+        return HttpResponse::BadRequest().json(message);
+    }
+
+    let existing = match meter_cost_types
+        .filter(meter_id.eq(meter_id_val))
+        .filter(cost_type_id.eq(cost_type_id_val))
+        .first::<MeterCostType>(conn)
+        .optional()
+    {
+        Ok(existing) => existing,
+        Err(e) => {
+            error!("Error checking meter cost type binding: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking meter cost type binding: {}", e));
+        }
+    };
+
+    let result = match existing {
+        Some(_) => diesel::update(
+            meter_cost_types
+                .filter(meter_id.eq(meter_id_val))
+                .filter(cost_type_id.eq(cost_type_id_val)),
+        )
+        .set(conversion_factor.eq(SqlDecimal(factor)))
+        .execute(conn),
+        None => diesel::insert_into(meter_cost_types)
+            .values(NewMeterCostType {
+                meter_id: meter_id_val,
+                cost_type_id: cost_type_id_val,
+                conversion_factor: factor,
+            })
+            .execute(conn),
+    };
+
+    match result {
+        Ok(_) => {
+            let bindings = load_cost_types_for_meter(meter_id_val, conn);
+            if existing.is_some() {
+                HttpResponse::Ok().json(bindings)
+            } else {
+                HttpResponse::Created().json(bindings)
+            }
+        }
+        Err(e) => {
+            error!("Error binding meter {} to cost type {}: {}", meter_id_val, cost_type_id_val, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error binding meter to cost type: {}", e))
+        }
+    }
+}
+
+// DELETE /api/meters/{id}/cost-types/{cost_type_id}
+#[delete("/{id}/cost-types/{cost_type_id}")]
+async fn unbind_cost_type(path: web::Path<(i32, i32)>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::meter_cost_types::dsl::*;
+
+    let (meter_id_val, cost_type_id_val) = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(
+        meter_cost_types
+            .filter(meter_id.eq(meter_id_val))
+            .filter(cost_type_id.eq(cost_type_id_val)),
+    )
+    .execute(conn)
+    {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json(load_cost_types_for_meter(meter_id_val, conn))
+            } else {
+                HttpResponse::NotFound().json("Cost type not bound to this meter")
+            }
+        }
+        Err(e) => {
+            error!("Error unbinding meter {} from cost type {}: {}", meter_id_val, cost_type_id_val, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error unbinding meter from cost type: {}", e))
+        }
+    }
+}
@@ -1,14 +1,16 @@
 use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::NaiveDate;
 use diesel::prelude::*;
 use log::{error, info};
+use rust_decimal::Decimal;
 
 use crate::db;
-use crate::models::meter::Meter;
+use crate::models::meter::{InterpolationMethod, Meter};
 use crate::models::meter_reading::{
-    MeterReading, MeterReadingDto, MeterReadingInputDto, MeterReadingUpdate,
+    ConsumptionQuery, MeterReading, MeterReadingDto, MeterReadingInputDto, MeterReadingUpdate,
     MeterReadingWithConsumption, NewMeterReading,
 };
+use crate::services::interpolation::{self, DayWeighting};
 use crate::DbPool;
 
 // Configure routes for meter readings
@@ -210,12 +212,12 @@ async fn create_reading(
     let conn = &mut db::get_connection(&pool);
 
     // Input validation
-    if new_reading.value < 0.0 {
+    if new_reading.value < Decimal::ZERO {
         return HttpResponse::BadRequest().json("Reading value cannot be negative");
     }
 
     // Check if meter exists
-    let _meter = match meters::table
+    match meters::table
         .filter(meters::id.eq(new_reading.meter_id))
         .first::<Meter>(conn)
     {
@@ -229,7 +231,7 @@ async fn create_reading(
             return HttpResponse::InternalServerError()
                 .json(format!("Error checking if meter exists: {}", e));
         }
-    };
+    }
 
     // Check if a reading with this date already exists for this meter
     let reading_date_timestamp = new_reading.reading_date.and_hms_opt(0, 0, 0).unwrap();
@@ -283,7 +285,7 @@ async fn create_reading(
     let new_reading = NewMeterReading::from(new_reading.into_inner());
 
     match diesel::insert_into(meter_readings)
-        .values(&new_reading)
+        .values(new_reading)
         .execute(conn)
     {
         Ok(_) => {
@@ -326,7 +328,7 @@ async fn update_reading(
 
     // Input validation
     if let Some(value_val) = reading_update.value {
-        if value_val < 0.0 {
+        if value_val < Decimal::ZERO {
             return HttpResponse::BadRequest().json("Reading value cannot be negative");
         }
     }
@@ -511,9 +513,15 @@ async fn delete_reading(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl R
     }
 }
 
-// GET /api/meter-readings/consumption/{meter_id}
+// GET /api/meter-readings/consumption/{meter_id}?start_date=YYYY-MM-DD&end_date=YYYY-MM-DD
+// With a date range, the readings are limited to it and framed by the meter values at the
+// beginning of the start date and the end of the end date, interpolated if not read on that day.
 #[get("/consumption/{meter_id}")]
-async fn calculate_consumption(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+async fn calculate_consumption(
+    path: web::Path<i32>,
+    query: web::Query<ConsumptionQuery>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
     use crate::schema::meter_readings::dsl::*;
     use crate::schema::meters;
 
@@ -525,7 +533,7 @@ async fn calculate_consumption(path: web::Path<i32>, pool: web::Data<DbPool>) ->
         .filter(meters::id.eq(meter_id_val))
         .first::<Meter>(conn)
     {
-        Ok(m) => m,
+        Ok(meter) => meter,
         Err(diesel::NotFound) => {
             return HttpResponse::NotFound()
                 .json(format!("Meter with ID {} not found", meter_id_val));
@@ -555,37 +563,70 @@ async fn calculate_consumption(path: web::Path<i32>, pool: web::Data<DbPool>) ->
         return HttpResponse::Ok().json(Vec::<MeterReadingWithConsumption>::new());
     }
 
-    // Calculate consumption between consecutive readings
-    let mut result = Vec::new();
-    let mut prev_reading: Option<&MeterReading> = None;
-
-    for reading in &readings {
-        let mut consumption = None;
-        let mut days_since_last = None;
-
-        if let Some(prev) = prev_reading {
-            // Calculate consumption since previous reading
-            consumption = Some(reading.value - prev.value);
-
-            // Calculate days between readings
-            let duration = reading
-                .reading_date
-                .signed_duration_since(prev.reading_date);
-            days_since_last = Some(duration.num_days());
-        }
-
-        result.push(MeterReadingWithConsumption {
-            id: reading.id.unwrap_or(0),
-            meter_id: reading.meter_id,
-            reading_date: reading.reading_date.date(),
-            value: reading.value,
-            notes: reading.notes.clone(),
-            consumption,
-            days_since_last_reading: days_since_last,
+    let mut entries: Vec<MeterReadingWithConsumption> =
+        readings.iter().map(MeterReadingWithConsumption::from).collect();
+
+    if let (Some(start_date), Some(end_date)) = (query.start_date, query.end_date) {
+        if end_date < start_date {
+            return HttpResponse::BadRequest().json("End date must not be before start date");
+        }
+
+        // The end of the end date is the beginning of the following day
+        let boundary_dates = [start_date, end_date.succ_opt().unwrap_or(end_date)];
+        let method = InterpolationMethod::from(meter.interpolation_method.clone());
+        let weighting = match interpolation::load_degree_days(conn, meter.building_id) {
+            Ok(degree_days) => DayWeighting::for_method(method, &degree_days),
+            Err(e) => {
+                error!("Error loading degree days: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(format!("Error loading degree days: {}", e));
+            }
+        };
+        let deduplicated = match interpolation::load_readings(conn, meter_id_val) {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Error loading meter readings: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(format!("Error loading meter readings: {}", e));
+            }
+        };
+
+        entries.retain(|entry| {
+            entry.reading_date >= boundary_dates[0] && entry.reading_date <= boundary_dates[1]
         });
+        for boundary_date in boundary_dates {
+            if entries.iter().any(|entry| entry.reading_date == boundary_date) {
+                continue;
+            }
+            match interpolation::state_at(&deduplicated, boundary_date, &weighting) {
+                Some(state) => entries.push(MeterReadingWithConsumption {
+                    id: 0,
+                    meter_id: meter_id_val,
+                    reading_date: state.date,
+                    value: state.value,
+                    notes: None,
+                    consumption: None,
+                    days_since_last_reading: None,
+                    estimated: true,
+                }),
+                None => {
+                    return HttpResponse::BadRequest().json(format!(
+                        "Not enough readings to estimate the meter value on {}",
+                        boundary_date
+                    ));
+                }
+            }
+        }
+        entries.sort_by_key(|entry| entry.reading_date);
+    }
 
-        prev_reading = Some(reading);
+    // Calculate consumption between consecutive entries
+    for index in 1..entries.len() {
+        let (previous_value, previous_date) = (entries[index - 1].value, entries[index - 1].reading_date);
+        let entry = &mut entries[index];
+        entry.consumption = Some(entry.value - previous_value);
+        entry.days_since_last_reading = Some((entry.reading_date - previous_date).num_days());
     }
 
-    HttpResponse::Ok().json(result)
+    HttpResponse::Ok().json(entries)
 }
@@ -1,6 +1,10 @@
+pub mod building;
 pub mod cost;
 pub mod meter;
 pub mod meter_reading;
 pub mod property_unit;
 pub mod tenant;
 pub mod billing;
+pub mod prepayment;
+pub mod heating;
+pub mod oil_tank;
@@ -0,0 +1,615 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use diesel::prelude::*;
+use log::error;
+use rust_decimal::Decimal;
+
+use crate::db;
+use crate::models::billing::BillingPeriod;
+use crate::models::cost::CostType;
+use crate::models::oil_tank::{
+    NewOilDelivery, NewOilTank, NewOilTankReading, OilDelivery, OilDeliveryDto, OilDeliveryUpdate,
+    OilTank, OilTankDto, OilTankReading, OilTankReadingDto, OilTankReadingUpdate, OilTankUpdate,
+};
+use crate::services::{allocation, oil_tank};
+use crate::DbPool;
+
+// Configure routes for oil tanks, their deliveries and level readings
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/oil-tanks")
+            .service(get_all_tanks)
+            .service(get_tank)
+            .service(create_tank)
+            .service(update_tank)
+            .service(delete_tank)
+            .service(get_fuel_cost),
+    );
+
+    cfg.service(
+        web::scope("/api/oil-deliveries")
+            .service(get_deliveries_by_tank)
+            .service(create_delivery)
+            .service(update_delivery)
+            .service(delete_delivery),
+    );
+
+    cfg.service(
+        web::scope("/api/oil-tank-readings")
+            .service(get_readings_by_tank)
+            .service(create_reading)
+            .service(update_reading)
+            .service(delete_reading),
+    );
+}
+
+// Helper function to check that a tank's cost type exists and is not consumption-based.
+// Returns an error message for the client if the cost type can't receive the fuel cost.
+fn validate_tank_cost_type(
+    cost_type_id_val: i32,
+    conn: &mut SqliteConnection,
+) -> Result<Option<String>, diesel::result::Error> {
+    use crate::schema::cost_types::dsl::*;
+
+    match cost_types
+        .filter(id.eq(cost_type_id_val))
+        .select(CostType::as_select())
+        .first::<CostType>(conn)
+    {
+        Ok(cost_type) if cost_type.is_consumption_based => Ok(Some(format!(
+            "Cost type '{}' is consumption-based and can't receive the tank's fuel cost",
+            cost_type.name
+        ))),
+        Ok(_) => Ok(None),
+        Err(diesel::NotFound) => Ok(Some(format!(
+            "Cost type with ID {} not found",
+            cost_type_id_val
+        ))),
+        Err(e) => Err(e),
+    }
+}
+
+// Helper function to check that a tank exists
+fn tank_exists(tank_id_val: i32, conn: &mut SqliteConnection) -> Result<bool, diesel::result::Error> {
+    use crate::schema::oil_tanks::dsl::*;
+
+    oil_tanks
+        .filter(id.eq(tank_id_val))
+        .count()
+        .get_result::<i64>(conn)
+        .map(|count| count > 0)
+}
+
+fn valid_reading_type(reading_type: &str) -> bool {
+    reading_type == "dip" || reading_type == "gauge"
+}
+
+// GET /api/oil-tanks
+#[get("")]
+async fn get_all_tanks(pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::oil_tanks::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+
+    match oil_tanks.order_by(name.asc()).load::<OilTank>(conn) {
+        Ok(results) => {
+            let dtos: Vec<OilTankDto> = results.into_iter().map(OilTankDto::from).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!("Error loading oil tanks: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error loading oil tanks: {}", e))
+        }
+    }
+}
+
+// GET /api/oil-tanks/{id}
+#[get("/{id}")]
+async fn get_tank(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::oil_tanks::dsl::*;
+
+    let tank_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match oil_tanks.filter(id.eq(tank_id)).first::<OilTank>(conn) {
+        Ok(tank) => HttpResponse::Ok().json(OilTankDto::from(tank)),
+        Err(diesel::NotFound) => {
+            HttpResponse::NotFound().json(format!("Oil tank with ID {} not found", tank_id))
+        }
+        Err(e) => {
+            error!("Error finding oil tank {}: {}", tank_id, e);
+            HttpResponse::InternalServerError().json(format!("Error finding oil tank: {}", e))
+        }
+    }
+}
+
+// POST /api/oil-tanks
+#[post("")]
+async fn create_tank(new_tank_json: web::Json<NewOilTank>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::oil_tanks::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let new_tank = new_tank_json.0;
+
+    // Input validation
+    if new_tank.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json("Oil tank name cannot be empty");
+    }
+
+    if new_tank.capacity_litres <= Decimal::ZERO {
+        return HttpResponse::BadRequest().json("Capacity must be greater than 0");
+    }
+
+    if let Some(cost_type_id_val) = new_tank.cost_type_id {
+        match validate_tank_cost_type(cost_type_id_val, conn) {
+            Ok(None) => (),
+            Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+            Err(e) => {
+                error!("Error checking cost type {}: {}", cost_type_id_val, e);
+                return HttpResponse::InternalServerError()
+                    .json(format!("Error checking cost type: {}", e));
+            }
+        }
+    }
+
+    match diesel::insert_into(oil_tanks).values(new_tank).execute(conn) {
+        Ok(_) => match oil_tanks.order_by(id.desc()).first::<OilTank>(conn) {
+            Ok(created) => HttpResponse::Created().json(OilTankDto::from(created)),
+            Err(e) => {
+                error!("Error retrieving created oil tank: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(format!("Oil tank created but could not be retrieved: {}", e))
+            }
+        },
+        Err(e) => {
+            error!("Error creating oil tank: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error creating oil tank: {}", e))
+        }
+    }
+}
+
+// PUT /api/oil-tanks/{id}
+#[put("/{id}")]
+async fn update_tank(
+    path: web::Path<i32>,
+    update_json: web::Json<OilTankUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::oil_tanks::dsl::*;
+
+    let tank_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+    let update = update_json.0;
+
+    // Input validation
+    if let Some(ref name_val) = update.name {
+        if name_val.trim().is_empty() {
+            return HttpResponse::BadRequest().json("Oil tank name cannot be empty");
+        }
+    }
+
+    if let Some(capacity_val) = update.capacity_litres {
+        if capacity_val <= Decimal::ZERO {
+            return HttpResponse::BadRequest().json("Capacity must be greater than 0");
+        }
+    }
+
+    if let Some(Some(cost_type_id_val)) = update.cost_type_id {
+        match validate_tank_cost_type(cost_type_id_val, conn) {
+            Ok(None) => (),
+            Ok(Some(message)) => return HttpResponse::BadRequest().json(message),
+            Err(e) => {
+                error!("Error checking cost type {}: {}", cost_type_id_val, e);
+                return HttpResponse::InternalServerError()
+                    .json(format!("Error checking cost type: {}", e));
+            }
+        }
+    }
+
+    match diesel::update(oil_tanks.filter(id.eq(tank_id)))
+        .set(update)
+        .execute(conn)
+    {
+        Ok(0) => HttpResponse::NotFound().json(format!("Oil tank with ID {} not found", tank_id)),
+        Ok(_) => match oil_tanks.filter(id.eq(tank_id)).first::<OilTank>(conn) {
+            Ok(updated) => HttpResponse::Ok().json(OilTankDto::from(updated)),
+            Err(e) => {
+                error!("Error retrieving updated oil tank: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(format!("Oil tank updated but could not be retrieved: {}", e))
+            }
+        },
+        Err(e) => {
+            error!("Error updating oil tank {}: {}", tank_id, e);
+            HttpResponse::InternalServerError().json(format!("Error updating oil tank: {}", e))
+        }
+    }
+}
+
+// DELETE /api/oil-tanks/{id}
+// Deliveries and level readings of the tank are deleted with it
+#[delete("/{id}")]
+async fn delete_tank(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::oil_tanks::dsl::*;
+
+    let tank_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(oil_tanks.filter(id.eq(tank_id))).execute(conn) {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json("Oil tank deleted successfully")
+            } else {
+                HttpResponse::NotFound().json(format!("Oil tank with ID {} not found", tank_id))
+            }
+        }
+        Err(e) => {
+            error!("Error deleting oil tank {}: {}", tank_id, e);
+            HttpResponse::InternalServerError().json(format!("Error deleting oil tank: {}", e))
+        }
+    }
+}
+
+// GET /api/oil-tanks/{id}/fuel-cost/{billing_period_id}
+// Consumed litres of the billing period and their FIFO valuation
+#[get("/{id}/fuel-cost/{billing_period_id}")]
+async fn get_fuel_cost(path: web::Path<(i32, i32)>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::{billing_periods, oil_tanks};
+
+    let (tank_id, billing_period_id) = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    let tank = match oil_tanks::table
+        .filter(oil_tanks::id.eq(tank_id))
+        .first::<OilTank>(conn)
+    {
+        Ok(tank) => tank,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound().json(format!("Oil tank with ID {} not found", tank_id));
+        }
+        Err(e) => {
+            error!("Error finding oil tank {}: {}", tank_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error finding oil tank: {}", e));
+        }
+    };
+
+    let billing_period = match billing_periods::table
+        .filter(billing_periods::id.eq(billing_period_id))
+        .first::<BillingPeriod>(conn)
+    {
+        Ok(billing_period) => billing_period,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound()
+                .json(format!("Billing period with ID {} not found", billing_period_id));
+        }
+        Err(e) => {
+            error!("Error finding billing period {}: {}", billing_period_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error finding billing period: {}", e));
+        }
+    };
+
+    let (start_date, end_date) = match allocation::parse_period(&billing_period) {
+        Ok(period) => period,
+        Err(e) => return HttpResponse::BadRequest().json(e.to_string()),
+    };
+
+    match oil_tank::fuel_cost(conn, &tank, start_date, end_date) {
+        Ok(Some(fuel_cost)) => HttpResponse::Ok().json(fuel_cost),
+        Ok(None) => HttpResponse::UnprocessableEntity()
+            .json("At least two level readings are needed to determine the consumption"),
+        Err(e) => {
+            error!("Error calculating fuel cost of oil tank {}: {}", tank_id, e);
+            HttpResponse::InternalServerError().json(format!("Error calculating fuel cost: {}", e))
+        }
+    }
+}
+
+// GET /api/oil-deliveries/tank/{id}
+#[get("/tank/{id}")]
+async fn get_deliveries_by_tank(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::oil_deliveries::dsl::*;
+
+    let tank_id_val = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match oil_deliveries
+        .filter(tank_id.eq(tank_id_val))
+        .order_by(delivery_date.asc())
+        .load::<OilDelivery>(conn)
+    {
+        Ok(results) => {
+            let dtos: Vec<OilDeliveryDto> = results.into_iter().map(OilDeliveryDto::from).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!("Error loading deliveries of oil tank {}: {}", tank_id_val, e);
+            HttpResponse::InternalServerError().json(format!("Error loading oil deliveries: {}", e))
+        }
+    }
+}
+
+// POST /api/oil-deliveries
+#[post("")]
+async fn create_delivery(
+    new_delivery_json: web::Json<NewOilDelivery>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::oil_deliveries::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let new_delivery = new_delivery_json.0;
+
+    // Input validation
+    if new_delivery.litres <= Decimal::ZERO {
+        return HttpResponse::BadRequest().json("Delivered litres must be greater than 0");
+    }
+
+    if new_delivery.total_price < Decimal::ZERO {
+        return HttpResponse::BadRequest().json("Total price cannot be negative");
+    }
+
+    match tank_exists(new_delivery.tank_id, conn) {
+        Ok(true) => (),
+        Ok(false) => {
+            return HttpResponse::BadRequest()
+                .json(format!("Oil tank with ID {} not found", new_delivery.tank_id));
+        }
+        Err(e) => {
+            error!("Error checking oil tank {}: {}", new_delivery.tank_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking oil tank: {}", e));
+        }
+    }
+
+    match diesel::insert_into(oil_deliveries)
+        .values(new_delivery)
+        .execute(conn)
+    {
+        Ok(_) => match oil_deliveries.order_by(id.desc()).first::<OilDelivery>(conn) {
+            Ok(created) => HttpResponse::Created().json(OilDeliveryDto::from(created)),
+            Err(e) => {
+                error!("Error retrieving created oil delivery: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(format!("Oil delivery created but could not be retrieved: {}", e))
+            }
+        },
+        Err(e) => {
+            error!("Error creating oil delivery: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error creating oil delivery: {}", e))
+        }
+    }
+}
+
+// PUT /api/oil-deliveries/{id}
+#[put("/{id}")]
+async fn update_delivery(
+    path: web::Path<i32>,
+    update_json: web::Json<OilDeliveryUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::oil_deliveries::dsl::*;
+
+    let delivery_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+    let update = update_json.0;
+
+    // Input validation
+    if let Some(litres_val) = update.litres {
+        if litres_val <= Decimal::ZERO {
+            return HttpResponse::BadRequest().json("Delivered litres must be greater than 0");
+        }
+    }
+
+    if let Some(total_price_val) = update.total_price {
+        if total_price_val < Decimal::ZERO {
+            return HttpResponse::BadRequest().json("Total price cannot be negative");
+        }
+    }
+
+    match diesel::update(oil_deliveries.filter(id.eq(delivery_id)))
+        .set(update)
+        .execute(conn)
+    {
+        Ok(0) => HttpResponse::NotFound()
+            .json(format!("Oil delivery with ID {} not found", delivery_id)),
+        Ok(_) => match oil_deliveries.filter(id.eq(delivery_id)).first::<OilDelivery>(conn) {
+            Ok(updated) => HttpResponse::Ok().json(OilDeliveryDto::from(updated)),
+            Err(e) => {
+                error!("Error retrieving updated oil delivery: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(format!("Oil delivery updated but could not be retrieved: {}", e))
+            }
+        },
+        Err(e) => {
+            error!("Error updating oil delivery {}: {}", delivery_id, e);
+            HttpResponse::InternalServerError().json(format!("Error updating oil delivery: {}", e))
+        }
+    }
+}
+
+// DELETE /api/oil-deliveries/{id}
+#[delete("/{id}")]
+async fn delete_delivery(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::oil_deliveries::dsl::*;
+
+    let delivery_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(oil_deliveries.filter(id.eq(delivery_id))).execute(conn) {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json("Oil delivery deleted successfully")
+            } else {
+                HttpResponse::NotFound()
+                    .json(format!("Oil delivery with ID {} not found", delivery_id))
+            }
+        }
+        Err(e) => {
+            error!("Error deleting oil delivery {}: {}", delivery_id, e);
+            HttpResponse::InternalServerError().json(format!("Error deleting oil delivery: {}", e))
+        }
+    }
+}
+
+// GET /api/oil-tank-readings/tank/{id}
+#[get("/tank/{id}")]
+async fn get_readings_by_tank(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::oil_tank_readings::dsl::*;
+
+    let tank_id_val = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match oil_tank_readings
+        .filter(tank_id.eq(tank_id_val))
+        .order_by(reading_date.asc())
+        .load::<OilTankReading>(conn)
+    {
+        Ok(results) => {
+            let dtos: Vec<OilTankReadingDto> =
+                results.into_iter().map(OilTankReadingDto::from).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!("Error loading level readings of oil tank {}: {}", tank_id_val, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error loading tank level readings: {}", e))
+        }
+    }
+}
+
+// POST /api/oil-tank-readings
+#[post("")]
+async fn create_reading(
+    new_reading_json: web::Json<NewOilTankReading>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::oil_tank_readings::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let new_reading = new_reading_json.0;
+
+    // Input validation
+    if new_reading.litres < Decimal::ZERO {
+        return HttpResponse::BadRequest().json("Tank level cannot be negative");
+    }
+
+    if !valid_reading_type(&new_reading.reading_type) {
+        return HttpResponse::BadRequest().json("Reading type must be 'dip' or 'gauge'");
+    }
+
+    match tank_exists(new_reading.tank_id, conn) {
+        Ok(true) => (),
+        Ok(false) => {
+            return HttpResponse::BadRequest()
+                .json(format!("Oil tank with ID {} not found", new_reading.tank_id));
+        }
+        Err(e) => {
+            error!("Error checking oil tank {}: {}", new_reading.tank_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking oil tank: {}", e));
+        }
+    }
+
+    match diesel::insert_into(oil_tank_readings)
+        .values(new_reading)
+        .execute(conn)
+    {
+        Ok(_) => match oil_tank_readings
+            .order_by(id.desc())
+            .first::<OilTankReading>(conn)
+        {
+            Ok(created) => HttpResponse::Created().json(OilTankReadingDto::from(created)),
+            Err(e) => {
+                error!("Error retrieving created tank level reading: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Tank level reading created but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error creating tank level reading: {}", e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error creating tank level reading: {}", e))
+        }
+    }
+}
+
+// PUT /api/oil-tank-readings/{id}
+#[put("/{id}")]
+async fn update_reading(
+    path: web::Path<i32>,
+    update_json: web::Json<OilTankReadingUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::oil_tank_readings::dsl::*;
+
+    let reading_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+    let update = update_json.0;
+
+    // Input validation
+    if let Some(litres_val) = update.litres {
+        if litres_val < Decimal::ZERO {
+            return HttpResponse::BadRequest().json("Tank level cannot be negative");
+        }
+    }
+
+    if let Some(ref reading_type_val) = update.reading_type {
+        if !valid_reading_type(reading_type_val) {
+            return HttpResponse::BadRequest().json("Reading type must be 'dip' or 'gauge'");
+        }
+    }
+
+    match diesel::update(oil_tank_readings.filter(id.eq(reading_id)))
+        .set(update)
+        .execute(conn)
+    {
+        Ok(0) => HttpResponse::NotFound()
+            .json(format!("Tank level reading with ID {} not found", reading_id)),
+        Ok(_) => match oil_tank_readings
+            .filter(id.eq(reading_id))
+            .first::<OilTankReading>(conn)
+        {
+            Ok(updated) => HttpResponse::Ok().json(OilTankReadingDto::from(updated)),
+            Err(e) => {
+                error!("Error retrieving updated tank level reading: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Tank level reading updated but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error updating tank level reading {}: {}", reading_id, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error updating tank level reading: {}", e))
+        }
+    }
+}
+
+// DELETE /api/oil-tank-readings/{id}
+#[delete("/{id}")]
+async fn delete_reading(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::oil_tank_readings::dsl::*;
+
+    let reading_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(oil_tank_readings.filter(id.eq(reading_id))).execute(conn) {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json("Tank level reading deleted successfully")
+            } else {
+                HttpResponse::NotFound()
+                    .json(format!("Tank level reading with ID {} not found", reading_id))
+            }
+        }
+        Err(e) => {
+            error!("Error deleting tank level reading {}: {}", reading_id, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error deleting tank level reading: {}", e))
+        }
+    }
+}
@@ -0,0 +1,418 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use diesel::prelude::*;
+use log::error;
+use rust_decimal::Decimal;
+
+use crate::db;
+use crate::models::billing::BillingPeriod;
+use crate::models::prepayment::{
+    NewPrepayment, NewPrepaymentSchedule, Prepayment, PrepaymentDto, PrepaymentSchedule,
+    PrepaymentScheduleDto, PrepaymentScheduleUpdate, PrepaymentUpdate,
+};
+use crate::models::tenant::Tenant;
+use crate::DbPool;
+
+// Configure routes for prepayment schedules and received prepayments
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/prepayment-schedules")
+            .service(get_schedules_by_tenant)
+            .service(create_schedule)
+            .service(update_schedule)
+            .service(delete_schedule),
+    );
+
+    cfg.service(
+        web::scope("/api/prepayments")
+            .service(get_prepayments_by_tenant)
+            .service(get_prepayments_by_billing_period)
+            .service(create_prepayment)
+            .service(update_prepayment)
+            .service(delete_prepayment),
+    );
+}
+
+// Helper function to check that a tenant exists
+fn tenant_exists(tenant_id_val: i32, conn: &mut SqliteConnection) -> Result<bool, diesel::result::Error> {
+    use crate::schema::tenants::dsl::*;
+
+    tenants
+        .filter(id.eq(tenant_id_val))
+        .first::<Tenant>(conn)
+        .optional()
+        .map(|tenant| tenant.is_some())
+}
+
+// GET /api/prepayment-schedules/tenant/{id}
+#[get("/tenant/{id}")]
+async fn get_schedules_by_tenant(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::prepayment_schedules::dsl::*;
+
+    let tenant_id_param = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match prepayment_schedules
+        .filter(tenant_id.eq(tenant_id_param))
+        .order_by(valid_from.desc())
+        .load::<PrepaymentSchedule>(conn)
+    {
+        Ok(results) => {
+            let dtos: Vec<PrepaymentScheduleDto> = results.into_iter().map(|s| s.into()).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!(
+                "Error loading prepayment schedules for tenant {}: {}",
+                tenant_id_param, e
+            );
+            HttpResponse::InternalServerError()
+                .json(format!("Error loading prepayment schedules: {}", e))
+        }
+    }
+}
+
+// POST /api/prepayment-schedules
+#[post("")]
+async fn create_schedule(
+    new_schedule_json: web::Json<NewPrepaymentSchedule>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::prepayment_schedules::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let new_schedule = new_schedule_json.0;
+
+    // Input validation
+    if new_schedule.monthly_amount < Decimal::ZERO {
+        return HttpResponse::BadRequest().json("Monthly amount cannot be negative");
+    }
+
+    if let Some(valid_to_val) = new_schedule.valid_to {
+        if valid_to_val < new_schedule.valid_from {
+            return HttpResponse::BadRequest().json("Valid to date must be after valid from date");
+        }
+    }
+
+    match tenant_exists(new_schedule.tenant_id, conn) {
+        Ok(true) => (),
+        Ok(false) => {
+            return HttpResponse::BadRequest()
+                .json(format!("Tenant with ID {} not found", new_schedule.tenant_id));
+        }
+        Err(e) => {
+            error!("Error checking if tenant exists: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking if tenant exists: {}", e));
+        }
+    }
+
+    match diesel::insert_into(prepayment_schedules)
+        .values(new_schedule)
+        .execute(conn)
+    {
+        Ok(_) => match prepayment_schedules
+            .order_by(id.desc())
+            .first::<PrepaymentSchedule>(conn)
+        {
+            Ok(created) => HttpResponse::Created().json(PrepaymentScheduleDto::from(created)),
+            Err(e) => {
+                error!("Error retrieving created prepayment schedule: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Prepayment schedule created but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error creating prepayment schedule: {}", e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error creating prepayment schedule: {}", e))
+        }
+    }
+}
+
+// PUT /api/prepayment-schedules/{id}
+#[put("/{id}")]
+async fn update_schedule(
+    path: web::Path<i32>,
+    update_json: web::Json<PrepaymentScheduleUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::prepayment_schedules::dsl::*;
+
+    let schedule_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+    let update = update_json.0;
+
+    // Get existing record for validation
+    let existing = match prepayment_schedules
+        .filter(id.eq(schedule_id))
+        .first::<PrepaymentSchedule>(conn)
+    {
+        Ok(existing) => existing,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound()
+                .json(format!("Prepayment schedule with ID {} not found", schedule_id));
+        }
+        Err(e) => {
+            error!("Error finding prepayment schedule {}: {}", schedule_id, e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error finding prepayment schedule: {}", e));
+        }
+    };
+
+    // Input validation
+    if let Some(amount_val) = update.monthly_amount {
+        if amount_val < Decimal::ZERO {
+            return HttpResponse::BadRequest().json("Monthly amount cannot be negative");
+        }
+    }
+
+    let from_date = update.valid_from.unwrap_or(existing.valid_from);
+    let to_date = update.valid_to.unwrap_or(existing.valid_to);
+    if let Some(to_date) = to_date {
+        if to_date < from_date {
+            return HttpResponse::BadRequest().json("Valid to date must be after valid from date");
+        }
+    }
+
+    match diesel::update(prepayment_schedules.filter(id.eq(schedule_id)))
+        .set(update)
+        .execute(conn)
+    {
+        Ok(_) => match prepayment_schedules
+            .filter(id.eq(schedule_id))
+            .first::<PrepaymentSchedule>(conn)
+        {
+            Ok(updated) => HttpResponse::Ok().json(PrepaymentScheduleDto::from(updated)),
+            Err(e) => {
+                error!("Error retrieving updated prepayment schedule: {}", e);
+                HttpResponse::InternalServerError().json(format!(
+                    "Prepayment schedule updated but could not be retrieved: {}",
+                    e
+                ))
+            }
+        },
+        Err(e) => {
+            error!("Error updating prepayment schedule {}: {}", schedule_id, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error updating prepayment schedule: {}", e))
+        }
+    }
+}
+
+// DELETE /api/prepayment-schedules/{id}
+#[delete("/{id}")]
+async fn delete_schedule(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::prepayment_schedules::dsl::*;
+
+    let schedule_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(prepayment_schedules.filter(id.eq(schedule_id))).execute(conn) {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json("Prepayment schedule deleted successfully")
+            } else {
+                HttpResponse::NotFound()
+                    .json(format!("Prepayment schedule with ID {} not found", schedule_id))
+            }
+        }
+        Err(e) => {
+            error!("Error deleting prepayment schedule {}: {}", schedule_id, e);
+            HttpResponse::InternalServerError()
+                .json(format!("Error deleting prepayment schedule: {}", e))
+        }
+    }
+}
+
+// GET /api/prepayments/tenant/{id}
+#[get("/tenant/{id}")]
+async fn get_prepayments_by_tenant(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::prepayments::dsl::*;
+
+    let tenant_id_param = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match prepayments
+        .filter(tenant_id.eq(tenant_id_param))
+        .order_by(received_date.desc())
+        .load::<Prepayment>(conn)
+    {
+        Ok(results) => {
+            let dtos: Vec<PrepaymentDto> = results.into_iter().map(|p| p.into()).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!("Error loading prepayments for tenant {}: {}", tenant_id_param, e);
+            HttpResponse::InternalServerError().json(format!("Error loading prepayments: {}", e))
+        }
+    }
+}
+
+// GET /api/prepayments/billing-period/{id}
+#[get("/billing-period/{id}")]
+async fn get_prepayments_by_billing_period(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::prepayments::dsl::*;
+
+    let period_id_param = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match prepayments
+        .filter(billing_period_id.eq(period_id_param))
+        .order_by(received_date.asc())
+        .load::<Prepayment>(conn)
+    {
+        Ok(results) => {
+            let dtos: Vec<PrepaymentDto> = results.into_iter().map(|p| p.into()).collect();
+            HttpResponse::Ok().json(dtos)
+        }
+        Err(e) => {
+            error!(
+                "Error loading prepayments for billing period {}: {}",
+                period_id_param, e
+            );
+            HttpResponse::InternalServerError().json(format!("Error loading prepayments: {}", e))
+        }
+    }
+}
+
+// POST /api/prepayments
+#[post("")]
+async fn create_prepayment(
+    new_prepayment_json: web::Json<NewPrepayment>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::billing_periods;
+    use crate::schema::prepayments::dsl::*;
+
+    let conn = &mut db::get_connection(&pool);
+    let new_prepayment = new_prepayment_json.0;
+
+    // Input validation
+    if new_prepayment.amount <= Decimal::ZERO {
+        return HttpResponse::BadRequest().json("Amount must be greater than 0");
+    }
+
+    match tenant_exists(new_prepayment.tenant_id, conn) {
+        Ok(true) => (),
+        Ok(false) => {
+            return HttpResponse::BadRequest()
+                .json(format!("Tenant with ID {} not found", new_prepayment.tenant_id));
+        }
+        Err(e) => {
+            error!("Error checking if tenant exists: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking if tenant exists: {}", e));
+        }
+    }
+
+    match billing_periods::table
+        .filter(billing_periods::id.eq(new_prepayment.billing_period_id))
+        .first::<BillingPeriod>(conn)
+    {
+        Ok(_) => (),
+        Err(diesel::NotFound) => {
+            return HttpResponse::BadRequest().json(format!(
+                "Billing period with ID {} not found",
+                new_prepayment.billing_period_id
+            ));
+        }
+        Err(e) => {
+            error!("Error checking if billing period exists: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(format!("Error checking if billing period exists: {}", e));
+        }
+    }
+
+    match diesel::insert_into(prepayments)
+        .values(new_prepayment)
+        .execute(conn)
+    {
+        Ok(_) => match prepayments.order_by(id.desc()).first::<Prepayment>(conn) {
+            Ok(created) => HttpResponse::Created().json(PrepaymentDto::from(created)),
+            Err(e) => {
+                error!("Error retrieving created prepayment: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(format!("Prepayment created but could not be retrieved: {}", e))
+            }
+        },
+        Err(e) => {
+            error!("Error creating prepayment: {}", e);
+            HttpResponse::InternalServerError().json(format!("Error creating prepayment: {}", e))
+        }
+    }
+}
+
+// PUT /api/prepayments/{id}
+#[put("/{id}")]
+async fn update_prepayment(
+    path: web::Path<i32>,
+    update_json: web::Json<PrepaymentUpdate>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
+    use crate::schema::prepayments::dsl::*;
+
+    let prepayment_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+    let update = update_json.0;
+
+    // Input validation
+    if let Some(amount_val) = update.amount {
+        if amount_val <= Decimal::ZERO {
+            return HttpResponse::BadRequest().json("Amount must be greater than 0");
+        }
+    }
+
+    match diesel::update(prepayments.filter(id.eq(prepayment_id)))
+        .set(update)
+        .execute(conn)
+    {
+        Ok(count) => {
+            if count > 0 {
+                match prepayments.filter(id.eq(prepayment_id)).first::<Prepayment>(conn) {
+                    Ok(updated) => HttpResponse::Ok().json(PrepaymentDto::from(updated)),
+                    Err(e) => {
+                        error!("Error retrieving updated prepayment: {}", e);
+                        HttpResponse::InternalServerError()
+                            .json(format!("Prepayment updated but could not be retrieved: {}", e))
+                    }
+                }
+            } else {
+                HttpResponse::NotFound()
+                    .json(format!("Prepayment with ID {} not found", prepayment_id))
+            }
+        }
+        Err(e) => {
+            error!("Error updating prepayment {}: {}", prepayment_id, e);
+            HttpResponse::InternalServerError().json(format!("Error updating prepayment: {}", e))
+        }
+    }
+}
+
+// DELETE /api/prepayments/{id}
+#[delete("/{id}")]
+async fn delete_prepayment(path: web::Path<i32>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::prepayments::dsl::*;
+
+    let prepayment_id = path.into_inner();
+    let conn = &mut db::get_connection(&pool);
+
+    match diesel::delete(prepayments.filter(id.eq(prepayment_id))).execute(conn) {
+        Ok(count) => {
+            if count > 0 {
+                HttpResponse::Ok().json("Prepayment deleted successfully")
+            } else {
+                HttpResponse::NotFound()
+                    .json(format!("Prepayment with ID {} not found", prepayment_id))
+            }
+        }
+        Err(e) => {
+            error!("Error deleting prepayment {}: {}", prepayment_id, e);
+            HttpResponse::InternalServerError().json(format!("Error deleting prepayment: {}", e))
+        }
+    }
+}
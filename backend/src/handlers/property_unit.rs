@@ -3,6 +3,8 @@ use diesel::prelude::*;
 use log::{error, info};
 
 use crate::db;
+use crate::handlers::building::resolve_building_id;
+use crate::models::building::BuildingFilter;
 use crate::models::property_unit::{
     NewPropertyUnit, PropertyUnit, PropertyUnitDto, PropertyUnitUpdate,
 };
@@ -20,14 +22,22 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     );
 }
 
-// GET /api/property-units
+// GET /api/property-units?building_id=1
 #[get("")]
-async fn get_all_property_units(pool: web::Data<DbPool>) -> impl Responder {
+async fn get_all_property_units(
+    filter: web::Query<BuildingFilter>,
+    pool: web::Data<DbPool>,
+) -> impl Responder {
     use crate::schema::property_units::dsl::*;
 
     let conn = &mut db::get_connection(&pool);
 
-    match property_units
+    let mut query = property_units.into_boxed();
+    if let Some(building_val) = filter.building_id {
+        query = query.filter(building_id.eq(building_val));
+    }
+
+    match query
         .order_by(name.asc())
         .load::<PropertyUnit>(conn)
     {
@@ -69,7 +79,7 @@ async fn get_property_unit_by_id(path: web::Path<i32>, pool: web::Data<DbPool>)
 // POST /api/property-units
 #[post("")]
 async fn create_property_unit(
-    mut new_unit_json: web::Json<NewPropertyUnit>,
+    new_unit_json: web::Json<NewPropertyUnit>,
     pool: web::Data<DbPool>,
 ) -> impl Responder {
     use crate::schema::property_units::dsl::*;
@@ -86,9 +96,26 @@ async fn create_property_unit(
         return HttpResponse::BadRequest().json("Living area must be greater than 0");
     }
 
+    if new_unit.heated_area_m2.is_some_and(|area| area < 0.0) {
+        return HttpResponse::BadRequest().json("Heated area must not be negative");
+    }
+
+    if new_unit.owner_persons.is_some_and(|persons| persons <= 0) {
+        return HttpResponse::BadRequest().json("Owner persons must be greater than 0");
+    }
+
+    let unit_building_id = match resolve_building_id(conn, new_unit.building_id) {
+        Ok(building_val) => building_val,
+        Err(e) => return e.response(),
+    };
+
     let new_unit = NewPropertyUnit {
         name: new_unit.name.clone(),
         living_area_m2: new_unit.living_area_m2,
+        heated_area_m2: new_unit.heated_area_m2,
+        owner_occupied: new_unit.owner_occupied,
+        owner_persons: new_unit.owner_persons,
+        building_id: Some(unit_building_id),
     };
 
     match diesel::insert_into(property_units)
@@ -146,6 +173,18 @@ async fn update_property_unit(
         }
     }
 
+    if let Some(Some(area_val)) = unit_update.heated_area_m2 {
+        if area_val < 0.0 {
+            return HttpResponse::BadRequest().json("Heated area must not be negative");
+        }
+    }
+
+    if let Some(Some(persons_val)) = unit_update.owner_persons {
+        if persons_val <= 0 {
+            return HttpResponse::BadRequest().json("Owner persons must be greater than 0");
+        }
+    }
+
     // Check if the property unit exists
     let exists = match property_units
         .filter(id.eq(unit_id))
@@ -165,10 +204,27 @@ async fn update_property_unit(
             .json(format!("Property unit with ID {} not found", unit_id));
     }
 
-    match diesel::update(property_units.filter(id.eq(unit_id)))
-        .set(unit_update.into_inner())
-        .execute(conn)
-    {
+    if let Some(building_val) = unit_update.building_id {
+        if let Err(e) = resolve_building_id(conn, Some(building_val)) {
+            return e.response();
+        }
+    }
+
+    // The unit's meters move to the new building along with it
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        if let Some(building_val) = unit_update.building_id {
+            use crate::schema::meters;
+
+            diesel::update(meters::table.filter(meters::property_unit_id.eq(unit_id)))
+                .set(meters::building_id.eq(building_val))
+                .execute(conn)?;
+        }
+        diesel::update(property_units.filter(id.eq(unit_id)))
+            .set(unit_update.into_inner())
+            .execute(conn)
+    });
+
+    match result {
         Ok(_) => {
             // Get the updated property unit
             match property_units
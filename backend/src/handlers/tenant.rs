@@ -3,6 +3,7 @@ use diesel::prelude::*;
 use log::{error, info};
 
 use crate::db;
+use crate::models::building::BuildingFilter;
 use crate::models::property_unit::PropertyUnit;
 use crate::models::tenant::{NewTenant, Tenant, TenantDto, TenantUpdate};
 use crate::DbPool;
@@ -20,14 +21,27 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     );
 }
 
-// GET /api/tenants
+// GET /api/tenants?building_id=1
 #[get("")]
-async fn get_all_tenants(pool: web::Data<DbPool>) -> impl Responder {
+async fn get_all_tenants(filter: web::Query<BuildingFilter>, pool: web::Data<DbPool>) -> impl Responder {
+    use crate::schema::property_units;
     use crate::schema::tenants::dsl::*;
 
     let conn = &mut db::get_connection(&pool);
 
-    match tenants.order_by(name.asc()).load::<Tenant>(conn) {
+    // Tenants belong to the building of their property unit
+    let mut query = tenants.into_boxed();
+    if let Some(building_val) = filter.building_id {
+        query = query.filter(
+            property_unit_id.eq_any(
+                property_units::table
+                    .filter(property_units::building_id.eq(building_val))
+                    .select(property_units::id.assume_not_null()),
+            ),
+        );
+    }
+
+    match query.order_by(name.asc()).load::<Tenant>(conn) {
         Ok(results) => {
             let dtos: Vec<TenantDto> = results.into_iter().map(|tenant| tenant.into()).collect();
             HttpResponse::Ok().json(dtos)
@@ -130,6 +144,12 @@ async fn create_tenant(
         return HttpResponse::BadRequest().json("Number of persons must be greater than 0");
     }
 
+    if let (Some(move_in), Some(move_out)) = (new_tenant.move_in_date, new_tenant.move_out_date) {
+        if move_out < move_in {
+            return HttpResponse::BadRequest().json("Move-out date must not be before move-in date");
+        }
+    }
+
     // Check if the property unit exists
     match property_units::table
         .filter(property_units::id.eq(new_tenant.property_unit_id))
@@ -153,6 +173,8 @@ async fn create_tenant(
         name: new_tenant.name.clone(),
         number_of_persons: new_tenant.number_of_persons,
         property_unit_id: new_tenant.property_unit_id,
+        move_in_date: new_tenant.move_in_date,
+        move_out_date: new_tenant.move_out_date,
     };
 
     match diesel::insert_into(tenants)
@@ -228,9 +250,11 @@ async fn update_tenant(
     }
 
     // Check if the tenant exists
-    let exists = match tenants.filter(id.eq(tenant_id)).first::<Tenant>(conn) {
-        Ok(_) => true,
-        Err(diesel::NotFound) => false,
+    let existing_tenant = match tenants.filter(id.eq(tenant_id)).first::<Tenant>(conn) {
+        Ok(tenant) => tenant,
+        Err(diesel::NotFound) => {
+            return HttpResponse::NotFound().json(format!("Tenant with ID {} not found", tenant_id));
+        }
         Err(e) => {
             error!("Error checking if tenant exists: {}", e);
             return HttpResponse::InternalServerError()
@@ -238,8 +262,13 @@ async fn update_tenant(
         }
     };
 
-    if !exists {
-        return HttpResponse::NotFound().json(format!("Tenant with ID {} not found", tenant_id));
+    // Validate the resulting tenancy date range
+    let move_in = tenant_update.move_in_date.unwrap_or(existing_tenant.move_in_date);
+    let move_out = tenant_update.move_out_date.unwrap_or(existing_tenant.move_out_date);
+    if let (Some(move_in), Some(move_out)) = (move_in, move_out) {
+        if move_out < move_in {
+            return HttpResponse::BadRequest().json("Move-out date must not be before move-in date");
+        }
     }
 
     match diesel::update(tenants.filter(id.eq(tenant_id)))
@@ -9,6 +9,7 @@ mod db;
 mod handlers;
 mod models;
 mod schema;
+mod services;
 
 pub type DbPool = r2d2::Pool<ConnectionManager<SqliteConnection>>;
 
@@ -31,6 +32,13 @@ async fn main() -> std::io::Result<()> {
     // Run database migrations
     db::run_migrations(&pool);
 
+    // Batch jobs don't survive a restart
+    match handlers::billing::fail_interrupted_jobs(&mut db::get_connection(&pool)) {
+        Ok(0) => (),
+        Ok(count) => log::warn!("Marked {} interrupted billing jobs as failed", count),
+        Err(e) => log::error!("Error marking interrupted billing jobs: {:?}", e),
+    }
+
     // Start HTTP server
     let bind_address = env::var("BIND_ADDRESS").unwrap_or_else(|_| "127.0.0.1:8080".to_string());
 
@@ -49,11 +57,16 @@ async fn main() -> std::io::Result<()> {
             .wrap(cors)
             .app_data(web::Data::new(pool.clone()))
             // Register API routes
+            .configure(handlers::building::configure)
             .configure(handlers::property_unit::configure)
             .configure(handlers::tenant::configure)
             .configure(handlers::meter::configure)
             .configure(handlers::meter_reading::configure)
             .configure(handlers::cost::configure)
+            .configure(handlers::prepayment::configure)
+            .configure(handlers::heating::configure)
+            .configure(handlers::oil_tank::configure)
+            // The billing routes use the bare "/api" scope and must be registered last
             .configure(handlers::billing::configure)
     })
     .bind(bind_address)?
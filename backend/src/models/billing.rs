@@ -1,15 +1,18 @@
 use chrono::NaiveDate;
 use diesel::prelude::*;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::{billing_periods, billing_statements};
+use crate::models::decimal::{NullableSqlDecimal, SqlDecimal};
+use crate::schema::{billing_jobs, billing_periods, billing_statement_items, billing_statements};
 use crate::models::tenant::Tenant;
+use std::fmt;
 
 #[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
 #[diesel(table_name = billing_periods)]
 pub struct BillingPeriod {
     pub id: Option<i32>,
-    pub property_unit_id: i32,
+    pub building_id: i32,    // Costs are computed for the whole building
     pub start_date: String,  // ISO 8601 format (YYYY-MM-DD)
     pub end_date: String,    // ISO 8601 format (YYYY-MM-DD)
     pub name: String,
@@ -20,7 +23,7 @@ pub struct BillingPeriod {
 #[derive(Insertable, Deserialize, Debug)]
 #[diesel(table_name = billing_periods)]
 pub struct NewBillingPeriod {
-    pub property_unit_id: i32,
+    pub building_id: Option<i32>, // Required when there is more than one building
     pub start_date: String,  // ISO 8601 format (YYYY-MM-DD)
     pub end_date: String,    // ISO 8601 format (YYYY-MM-DD)
     pub name: String,
@@ -42,9 +45,14 @@ pub struct BillingStatement {
     pub id: Option<i32>,
     pub billing_period_id: i32,
     pub tenant_id: i32,
-    pub total_amount: f32,
+    pub total_amount: Decimal,
     pub generated_at: String,
     pub html_content: Option<String>,
+    pub prepayments_amount: Decimal, // Prepayments deducted from the total
+    pub balance_amount: Decimal,     // Positive: Nachzahlung, negative: Guthaben
+    #[serde(skip_serializing)]
+    pub calculation_trace: Option<String>, // JSON, served separately; not set on older statements
+    pub billing_job_id: Option<i32>,       // Batch run that generated the statement
 }
 
 #[derive(Insertable, Deserialize, Debug)]
@@ -52,9 +60,108 @@ pub struct BillingStatement {
 pub struct NewBillingStatement {
     pub billing_period_id: i32,
     pub tenant_id: i32,
-    pub total_amount: f32,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub total_amount: Decimal,
     pub generated_at: String,
     pub html_content: Option<String>,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub prepayments_amount: Decimal, // Prepayments deducted from the total
+    #[diesel(serialize_as = SqlDecimal)]
+    pub balance_amount: Decimal,     // Positive: Nachzahlung, negative: Guthaben
+    pub calculation_trace: Option<String>,
+    pub billing_job_id: Option<i32>,
+}
+
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Serialize, Deserialize, Clone)]
+#[diesel(belongs_to(BillingStatement))]
+#[diesel(table_name = billing_statement_items)]
+pub struct BillingStatementItem {
+    pub id: Option<i32>,
+    pub billing_statement_id: i32,
+    pub cost_type_id: i32,
+    pub cost_type_name: String,
+    pub allocation_key: String,
+    pub total_amount: Decimal,     // Building total of the cost type
+    pub total_quantity: Decimal,   // Sum of all key quantities
+    pub tenant_quantity: Decimal,  // Key quantity of the tenant
+    pub share_amount: Decimal,     // Tenant's share of the total
+    pub position: i32,
+    pub tariff_id: Option<i32>,             // Set for tariff segments of consumption-based costs
+    pub price_per_unit: Option<Decimal>,
+    pub segment_start: Option<NaiveDate>,
+    pub segment_end: Option<NaiveDate>,
+    pub consumption_estimated: bool,        // Tenant's consumption based on interpolated readings
+    pub cost_part: Option<String>,          // consumption or base for split heating costs
+    pub cost_part_percent: Option<f32>,
+    pub common_consumption: bool,           // Cost of common meters, allocated by the line's key
+    pub fixed_cost_id: Option<i32>,         // Set for fixed cost invoices apportioned to the period
+    pub invoice_amount: Option<Decimal>,
+    pub invoice_start: Option<NaiveDate>,
+    pub invoice_end: Option<NaiveDate>,
+    pub invoice_days: Option<i32>,
+    pub apportioned_days: Option<i32>,      // Days of the invoice period inside the billing period
+    pub source_cost_type_id: Option<i32>,   // Set if the amount was split off another cost type
+    pub source_cost_type_name: Option<String>,
+    pub source_share_percent: Option<Decimal>,
+    pub co2_cost: Option<Decimal>,            // Tenant's part of the CO2 costs (CO2KostAufG)
+    pub co2_landlord_amount: Option<Decimal>, // Landlord's part, already taken off the share
+    pub transfer_name: Option<String>,           // Cost transfer moving costs into or out of the item
+    pub transfer_cost_type_name: Option<String>, // Cost type on the other side of the transfer
+    pub transfer_amount: Option<Decimal>,        // Building amount moved in (positive) or out (negative)
+}
+
+#[derive(Insertable, Deserialize, Debug)]
+#[diesel(table_name = billing_statement_items)]
+pub struct NewBillingStatementItem {
+    pub billing_statement_id: i32,
+    pub cost_type_id: i32,
+    pub cost_type_name: String,
+    pub allocation_key: String,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub total_amount: Decimal,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub total_quantity: Decimal,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub tenant_quantity: Decimal,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub share_amount: Decimal,
+    pub position: i32,
+    pub tariff_id: Option<i32>,
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub price_per_unit: Option<Decimal>,
+    pub segment_start: Option<NaiveDate>,
+    pub segment_end: Option<NaiveDate>,
+    pub consumption_estimated: bool,
+    pub cost_part: Option<String>,
+    pub cost_part_percent: Option<f32>,
+    pub common_consumption: bool,
+    pub fixed_cost_id: Option<i32>,
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub invoice_amount: Option<Decimal>,
+    pub invoice_start: Option<NaiveDate>,
+    pub invoice_end: Option<NaiveDate>,
+    pub invoice_days: Option<i32>,
+    pub apportioned_days: Option<i32>,
+    pub source_cost_type_id: Option<i32>,
+    pub source_cost_type_name: Option<String>,
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub source_share_percent: Option<Decimal>,
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub co2_cost: Option<Decimal>,
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub co2_landlord_amount: Option<Decimal>,
+    pub transfer_name: Option<String>,
+    pub transfer_cost_type_name: Option<String>,
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub transfer_amount: Option<Decimal>,
+}
+
+// Statement together with its itemized lines for API responses
+#[derive(Serialize, Debug)]
+pub struct BillingStatementDto {
+    #[serde(flatten)]
+    pub statement: BillingStatement,
+    pub items: Vec<BillingStatementItem>,
 }
 
 // Additional struct for API requests
@@ -63,3 +170,108 @@ pub struct GenerateStatementRequest {
     pub billing_period_id: i32,
     pub tenant_id: i32,
 }
+
+// Dry run of the statements of a billing period, for all tenants or a single one
+#[derive(Deserialize, Debug)]
+pub struct PreviewStatementRequest {
+    pub billing_period_id: i32,
+    pub tenant_id: Option<i32>,
+}
+
+// State of a batch generation job
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BillingJobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl fmt::Display for BillingJobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BillingJobStatus::Pending => write!(f, "pending"),
+            BillingJobStatus::Running => write!(f, "running"),
+            BillingJobStatus::Completed => write!(f, "completed"),
+            BillingJobStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl From<String> for BillingJobStatus {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "running" => BillingJobStatus::Running,
+            "completed" => BillingJobStatus::Completed,
+            "failed" => BillingJobStatus::Failed,
+            _ => BillingJobStatus::Pending,
+        }
+    }
+}
+
+// Batch run generating the statements of all tenants of a billing period at once
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Serialize, Deserialize, Clone)]
+#[diesel(belongs_to(BillingPeriod))]
+#[diesel(table_name = billing_jobs)]
+pub struct BillingJob {
+    pub id: Option<i32>,
+    pub billing_period_id: i32,
+    pub status: String,
+    pub total_tenants: i32,
+    pub processed_tenants: i32,
+    pub errors: Option<String>,         // One problem per line
+    pub owner_overview: Option<String>, // JSON, set once the job has completed
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    pub notes: Option<String>, // One note per line, set once the job has completed
+}
+
+#[derive(Insertable, Deserialize, Debug)]
+#[diesel(table_name = billing_jobs)]
+pub struct NewBillingJob {
+    pub billing_period_id: i32,
+    pub status: String,
+}
+
+// Job state for polling, with the statements it generated
+#[derive(Serialize, Debug)]
+pub struct BillingJobDto {
+    pub id: i32,
+    pub billing_period_id: i32,
+    pub status: BillingJobStatus,
+    pub total_tenants: i32,
+    pub processed_tenants: i32,
+    pub errors: Vec<String>,
+    pub notes: Vec<String>, // Estimates and fallbacks behind the statements, e.g. extrapolated meter values
+    pub owner_overview: Option<serde_json::Value>,
+    pub statement_ids: Vec<i32>,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+}
+
+impl From<BillingJob> for BillingJobDto {
+    fn from(job: BillingJob) -> Self {
+        BillingJobDto {
+            id: job.id.unwrap_or(0),
+            billing_period_id: job.billing_period_id,
+            status: BillingJobStatus::from(job.status),
+            total_tenants: job.total_tenants,
+            processed_tenants: job.processed_tenants,
+            errors: job
+                .errors
+                .map(|errors| errors.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            notes: job
+                .notes
+                .map(|notes| notes.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            owner_overview: job
+                .owner_overview
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            statement_ids: Vec::new(), // Populated separately
+            created_at: job.created_at,
+            finished_at: job.finished_at,
+        }
+    }
+}
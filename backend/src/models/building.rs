@@ -0,0 +1,115 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::models::deserialize_some;
+use crate::schema::buildings;
+
+// Database model for buildings, the billing unit whose costs are split across its property units
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = buildings)]
+pub struct Building {
+    pub id: Option<i32>,
+    pub name: String,
+    pub address: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub vacancy_persons: String, // none, one or previous_tenant
+}
+
+// Persons a vacant unit counts with for the PersonCount key. The landlord carries the
+// vacancy's share, so counting it with nobody shifts person-based costs onto the tenants.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VacancyPersons {
+    None,
+    #[default]
+    One,
+    PreviousTenant, // Persons of the last tenant before the vacancy, one if there was none
+}
+
+impl VacancyPersons {
+    // Explanation printed with person-based items
+    pub fn label(&self) -> &'static str {
+        match self {
+            VacancyPersons::None => "Leerstehende Wohnungen zählen mit 0 Personen",
+            VacancyPersons::One => "Leerstehende Wohnungen zählen mit 1 Person",
+            VacancyPersons::PreviousTenant => {
+                "Leerstehende Wohnungen zählen mit der Personenzahl des Vormieters, ohne Vormieter mit 1 Person"
+            }
+        }
+    }
+}
+
+impl fmt::Display for VacancyPersons {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VacancyPersons::None => write!(f, "none"),
+            VacancyPersons::One => write!(f, "one"),
+            VacancyPersons::PreviousTenant => write!(f, "previous_tenant"),
+        }
+    }
+}
+
+impl From<String> for VacancyPersons {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "none" => VacancyPersons::None,
+            "previous_tenant" => VacancyPersons::PreviousTenant,
+            _ => VacancyPersons::One, // Default to one person for unknown values
+        }
+    }
+}
+
+// Allowed values of the vacancy person setting
+pub const VACANCY_PERSONS_ERROR: &str = "Vacancy persons must be 'none', 'one' or 'previous_tenant'";
+
+pub fn valid_vacancy_persons(value: &str) -> bool {
+    matches!(value, "none" | "one" | "previous_tenant")
+}
+
+// New building data for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = buildings)]
+pub struct NewBuilding {
+    pub name: String,
+    pub address: Option<String>,
+    pub vacancy_persons: Option<String>, // Defaults to one
+}
+
+// Data transfer object for building updates
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = buildings)]
+pub struct BuildingUpdate {
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub address: Option<Option<String>>, // Double option for handling nulls
+    pub vacancy_persons: Option<String>,
+}
+
+// Data transfer object for responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildingDto {
+    pub id: i32,
+    pub name: String,
+    pub address: Option<String>,
+    pub vacancy_persons: VacancyPersons,
+}
+
+// Optional building filter for list endpoints, e.g. ?building_id=1
+#[derive(Debug, Deserialize)]
+pub struct BuildingFilter {
+    pub building_id: Option<i32>,
+}
+
+impl From<Building> for BuildingDto {
+    fn from(building: Building) -> Self {
+        BuildingDto {
+            id: building.id.unwrap_or(0),
+            name: building.name,
+            address: building.address,
+            vacancy_persons: VacancyPersons::from(building.vacancy_persons),
+        }
+    }
+}
@@ -1,8 +1,14 @@
 use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::{allocation_methods, cost_type_allocations, cost_types, fixed_costs, tariffs};
+use crate::models::decimal::{NullableSqlDecimal, SqlDecimal};
+use crate::models::deserialize_some;
+use crate::schema::{
+    allocation_methods, betrkv_categories, cost_transfers, cost_type_allocations, cost_types, fixed_costs,
+    tariffs,
+};
 
 // Database model for cost types
 #[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
@@ -15,6 +21,12 @@ pub struct CostType {
     pub unit: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub consumption_share_percent: Option<f32>, // Consumption part of a HeizkostenV split
+    pub uses_burner_hours: bool,                // Building consumption from the burner-hour meters
+    pub betrkv_category_id: Option<i32>,        // Allocable if not set
+    pub building_id: i32,
+    pub degree_day_weighting: bool, // Tenancies and meter estimates weighted by degree days
+    pub co2_emission_factor: Option<f32>, // kg CO2 per fuel unit; CO2 costs are split if set
 }
 
 // New cost type data for insertions
@@ -25,6 +37,14 @@ pub struct NewCostType {
     pub description: Option<String>,
     pub is_consumption_based: bool,
     pub unit: Option<String>,
+    pub consumption_share_percent: Option<f32>,
+    #[serde(default)]
+    pub uses_burner_hours: bool,
+    pub betrkv_category_id: Option<i32>,
+    pub building_id: Option<i32>, // Required when there is more than one building
+    #[serde(default)]
+    pub degree_day_weighting: bool,
+    pub co2_emission_factor: Option<f32>,
 }
 
 // Data transfer object for cost type updates
@@ -35,6 +55,14 @@ pub struct CostTypeUpdate {
     pub description: Option<String>,
     pub is_consumption_based: Option<bool>,
     pub unit: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub consumption_share_percent: Option<Option<f32>>, // Double option for handling nulls
+    pub uses_burner_hours: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub betrkv_category_id: Option<Option<i32>>, // Double option for handling nulls
+    pub degree_day_weighting: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub co2_emission_factor: Option<Option<f32>>, // Double option for handling nulls
 }
 
 // Data transfer object for cost type responses
@@ -45,9 +73,36 @@ pub struct CostTypeDto {
     pub description: Option<String>,
     pub is_consumption_based: bool,
     pub unit: Option<String>,
+    pub consumption_share_percent: Option<f32>,
+    pub uses_burner_hours: bool,
+    pub betrkv_category_id: Option<i32>,
+    pub building_id: i32,
+    pub degree_day_weighting: bool,
+    pub co2_emission_factor: Option<f32>,
     pub allocation_methods: Vec<AllocationMethodDto>,
 }
 
+// Database model for the BetrKV operating cost categories
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = betrkv_categories)]
+pub struct BetrkvCategory {
+    pub id: Option<i32>,
+    pub position: i32, // Order on statements; § 2 numbers first, non-allocable costs after
+    pub legal_reference: Option<String>,
+    pub name: String,
+    pub allocable: bool, // Umlagefähig, may be passed on to tenants
+}
+
+// Data transfer object for BetrKV category responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BetrkvCategoryDto {
+    pub id: i32,
+    pub position: i32,
+    pub legal_reference: Option<String>,
+    pub name: String,
+    pub allocable: bool,
+}
+
 // Database model for allocation methods
 #[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
 #[diesel(table_name = allocation_methods)]
@@ -91,7 +146,7 @@ pub struct NewCostTypeAllocation {
 pub struct Tariff {
     pub id: Option<i32>,
     pub cost_type_id: i32,
-    pub price_per_unit: f32,
+    pub price_per_unit: Decimal,
     pub valid_from: NaiveDate,
     pub valid_to: Option<NaiveDate>,
     pub created_at: NaiveDateTime,
@@ -103,7 +158,8 @@ pub struct Tariff {
 #[diesel(table_name = tariffs)]
 pub struct NewTariff {
     pub cost_type_id: i32,
-    pub price_per_unit: f32,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub price_per_unit: Decimal,
     pub valid_from: NaiveDate,
     pub valid_to: Option<NaiveDate>,
 }
@@ -112,7 +168,8 @@ pub struct NewTariff {
 #[derive(Debug, Deserialize, AsChangeset)]
 #[diesel(table_name = tariffs)]
 pub struct TariffUpdate {
-    pub price_per_unit: Option<f32>,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub price_per_unit: Option<Decimal>,
     pub valid_from: Option<NaiveDate>,
     pub valid_to: Option<Option<NaiveDate>>,
 }
@@ -122,7 +179,7 @@ pub struct TariffUpdate {
 pub struct TariffDto {
     pub id: i32,
     pub cost_type_id: i32,
-    pub price_per_unit: f32,
+    pub price_per_unit: Decimal,
     pub valid_from: NaiveDate,
     pub valid_to: Option<NaiveDate>,
 }
@@ -134,11 +191,14 @@ pub struct TariffDto {
 pub struct FixedCost {
     pub id: Option<i32>,
     pub cost_type_id: i32,
-    pub amount: f32,
+    pub amount: Decimal,
     pub billing_period_start: NaiveDate,
     pub billing_period_end: NaiveDate,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub fuel_quantity: Option<Decimal>,   // Fuel units on a fuel invoice
+    pub co2_emission_factor: Option<f32>, // kg CO2 per fuel unit stated on the invoice
+    pub co2_cost: Option<Decimal>,        // CO2 costs stated on the invoice
 }
 
 // New fixed cost data for insertions
@@ -146,18 +206,33 @@ pub struct FixedCost {
 #[diesel(table_name = fixed_costs)]
 pub struct NewFixedCost {
     pub cost_type_id: i32,
-    pub amount: f32,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub amount: Decimal,
     pub billing_period_start: NaiveDate,
     pub billing_period_end: NaiveDate,
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub fuel_quantity: Option<Decimal>,
+    pub co2_emission_factor: Option<f32>,
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub co2_cost: Option<Decimal>,
 }
 
 // Data transfer object for fixed cost updates
 #[derive(Debug, Deserialize, AsChangeset)]
 #[diesel(table_name = fixed_costs)]
 pub struct FixedCostUpdate {
-    pub amount: Option<f32>,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub amount: Option<Decimal>,
     pub billing_period_start: Option<NaiveDate>,
     pub billing_period_end: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub fuel_quantity: Option<Option<Decimal>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub co2_emission_factor: Option<Option<f32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub co2_cost: Option<Option<Decimal>>, // Double option for handling nulls
 }
 
 // Data transfer object for fixed cost responses
@@ -165,9 +240,98 @@ pub struct FixedCostUpdate {
 pub struct FixedCostDto {
     pub id: i32,
     pub cost_type_id: i32,
-    pub amount: f32,
+    pub amount: Decimal,
     pub billing_period_start: NaiveDate,
     pub billing_period_end: NaiveDate,
+    pub fuel_quantity: Option<Decimal>,
+    pub co2_emission_factor: Option<f32>,
+    pub co2_cost: Option<Decimal>,
+}
+
+// How the amount of a cost transfer is determined
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferMethod {
+    Percentage, // Share of the costs of a basis cost type, e.g. 5 % of the fuel costs
+    Meter,      // Consumption of a sub-meter times a price
+}
+
+impl TransferMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "percentage" => Some(TransferMethod::Percentage),
+            "meter" => Some(TransferMethod::Meter),
+            _ => None,
+        }
+    }
+}
+
+// Database model for costs moved from one cost type to another, e.g. Betriebsstrom
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = cost_transfers)]
+pub struct CostTransfer {
+    pub id: Option<i32>,
+    pub building_id: i32,
+    pub name: String,
+    pub source_cost_type_id: i32, // Costs are taken off this cost type
+    pub target_cost_type_id: i32, // and allocated with this one
+    pub method: String,           // percentage or meter
+    pub percent: Option<f32>,
+    pub basis_cost_type_id: Option<i32>,
+    pub meter_id: Option<i32>,
+    pub price_per_unit: Option<Decimal>, // Meter method; otherwise the source cost type's tariffs
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+// New cost transfer data for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = cost_transfers)]
+pub struct NewCostTransfer {
+    pub building_id: Option<i32>, // Required when there is more than one building
+    pub name: String,
+    pub source_cost_type_id: i32,
+    pub target_cost_type_id: i32,
+    pub method: String,
+    pub percent: Option<f32>,
+    pub basis_cost_type_id: Option<i32>,
+    pub meter_id: Option<i32>,
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub price_per_unit: Option<Decimal>,
+}
+
+// Data transfer object for cost transfer updates
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = cost_transfers)]
+pub struct CostTransferUpdate {
+    pub name: Option<String>,
+    pub source_cost_type_id: Option<i32>,
+    pub target_cost_type_id: Option<i32>,
+    pub method: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub percent: Option<Option<f32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub basis_cost_type_id: Option<Option<i32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub meter_id: Option<Option<i32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[diesel(serialize_as = NullableSqlDecimal)]
+    pub price_per_unit: Option<Option<Decimal>>, // Double option for handling nulls
+}
+
+// Data transfer object for cost transfer responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CostTransferDto {
+    pub id: i32,
+    pub building_id: i32,
+    pub name: String,
+    pub source_cost_type_id: i32,
+    pub target_cost_type_id: i32,
+    pub method: String,
+    pub percent: Option<f32>,
+    pub basis_cost_type_id: Option<i32>,
+    pub meter_id: Option<i32>,
+    pub price_per_unit: Option<Decimal>,
 }
 
 // Implementations for conversion between models and DTOs
@@ -179,11 +343,29 @@ impl From<CostType> for CostTypeDto {
             description: cost_type.description,
             is_consumption_based: cost_type.is_consumption_based,
             unit: cost_type.unit,
+            consumption_share_percent: cost_type.consumption_share_percent,
+            uses_burner_hours: cost_type.uses_burner_hours,
+            betrkv_category_id: cost_type.betrkv_category_id,
+            building_id: cost_type.building_id,
+            degree_day_weighting: cost_type.degree_day_weighting,
+            co2_emission_factor: cost_type.co2_emission_factor,
             allocation_methods: Vec::new(), // Populated separately
         }
     }
 }
 
+impl From<BetrkvCategory> for BetrkvCategoryDto {
+    fn from(category: BetrkvCategory) -> Self {
+        BetrkvCategoryDto {
+            id: category.id.unwrap_or(0),
+            position: category.position,
+            legal_reference: category.legal_reference,
+            name: category.name,
+            allocable: category.allocable,
+        }
+    }
+}
+
 impl From<AllocationMethod> for AllocationMethodDto {
     fn from(method: AllocationMethod) -> Self {
         AllocationMethodDto {
@@ -214,6 +396,26 @@ impl From<FixedCost> for FixedCostDto {
             amount: cost.amount,
             billing_period_start: cost.billing_period_start,
             billing_period_end: cost.billing_period_end,
+            fuel_quantity: cost.fuel_quantity,
+            co2_emission_factor: cost.co2_emission_factor,
+            co2_cost: cost.co2_cost,
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<CostTransfer> for CostTransferDto {
+    fn from(transfer: CostTransfer) -> Self {
+        CostTransferDto {
+            id: transfer.id.unwrap_or(0),
+            building_id: transfer.building_id,
+            name: transfer.name,
+            source_cost_type_id: transfer.source_cost_type_id,
+            target_cost_type_id: transfer.target_cost_type_id,
+            method: transfer.method,
+            percent: transfer.percent,
+            basis_cost_type_id: transfer.basis_cost_type_id,
+            meter_id: transfer.meter_id,
+            price_per_unit: transfer.price_per_unit,
+        }
+    }
+}
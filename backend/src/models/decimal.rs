@@ -0,0 +1,93 @@
+use diesel::deserialize::{self, FromSql, Queryable};
+use diesel::expression::AsExpression;
+use diesel::internal::derives::as_expression::Bound;
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::{Nullable, Text};
+use diesel::sqlite::{Sqlite, SqliteValue};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+use crate::schema::sql_types::ExactDecimal;
+
+// Decimal values are stored as their text representation to keep every digit.
+// Values written as REAL before the column was converted are read as well.
+impl FromSql<ExactDecimal, Sqlite> for Decimal {
+    fn from_sql(value: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+        let text = <String as FromSql<Text, Sqlite>>::from_sql(value)?;
+        Decimal::from_str(&text)
+            .or_else(|_| Decimal::from_scientific(&text))
+            .map_err(|e| format!("invalid decimal value '{}': {}", text, e).into())
+    }
+}
+
+impl ToSql<ExactDecimal, Sqlite> for Decimal {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        out.set_value(self.normalize().to_string());
+        Ok(IsNull::No)
+    }
+}
+
+impl Queryable<ExactDecimal, Sqlite> for Decimal {
+    type Row = Self;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        Ok(row)
+    }
+}
+
+// Decimal bound as a query parameter. Diesel can't accept rust_decimal's type directly,
+// so insertable and changeset fields use `#[diesel(serialize_as = SqlDecimal)]`.
+#[derive(Debug, Clone, Copy)]
+pub struct SqlDecimal(pub Decimal);
+
+impl From<Decimal> for SqlDecimal {
+    fn from(value: Decimal) -> Self {
+        SqlDecimal(value)
+    }
+}
+
+impl AsExpression<ExactDecimal> for SqlDecimal {
+    type Expression = Bound<ExactDecimal, Self>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
+
+impl ToSql<ExactDecimal, Sqlite> for SqlDecimal {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        <Decimal as ToSql<ExactDecimal, Sqlite>>::to_sql(&self.0, out)
+    }
+}
+
+// Same for nullable columns
+#[derive(Debug, Clone, Copy)]
+pub struct NullableSqlDecimal(pub Option<Decimal>);
+
+impl From<Option<Decimal>> for NullableSqlDecimal {
+    fn from(value: Option<Decimal>) -> Self {
+        NullableSqlDecimal(value)
+    }
+}
+
+impl AsExpression<Nullable<ExactDecimal>> for NullableSqlDecimal {
+    type Expression = Bound<Nullable<ExactDecimal>, Self>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
+
+impl ToSql<Nullable<ExactDecimal>, Sqlite> for NullableSqlDecimal {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        match &self.0 {
+            Some(value) => <Decimal as ToSql<ExactDecimal, Sqlite>>::to_sql(value, out),
+            None => Ok(IsNull::Yes),
+        }
+    }
+}
+
+// Exact decimal of a single precision value as it was entered, e.g. 72.3 m² instead of 72.300003
+pub fn from_f32(value: f32) -> Decimal {
+    Decimal::from_str(&value.to_string()).unwrap_or_default()
+}
@@ -0,0 +1,423 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::models::deserialize_some;
+use crate::schema::{burner_calibrations, degree_day_shares, heating_system_fuels, heating_systems};
+
+// Database model for a version of a burner stage's oil consumption rate
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = burner_calibrations)]
+pub struct BurnerCalibration {
+    pub id: Option<i32>,
+    pub stage: i32,            // Burner stage 1 or 2
+    pub litres_per_hour: f32,  // Oil burned per operating hour
+    pub valid_from: NaiveDate, // Applies until the next version of the same stage
+    pub notes: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub building_id: i32,      // Building whose burner the stage belongs to
+}
+
+// New calibration version for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = burner_calibrations)]
+pub struct NewBurnerCalibration {
+    pub stage: i32,
+    pub litres_per_hour: f32,
+    pub valid_from: NaiveDate,
+    pub notes: Option<String>,
+    pub building_id: Option<i32>, // Required when there is more than one building
+}
+
+// Data transfer object for calibration corrections
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = burner_calibrations)]
+pub struct BurnerCalibrationUpdate {
+    pub litres_per_hour: Option<f32>,
+    pub valid_from: Option<NaiveDate>,
+    pub notes: Option<Option<String>>, // Double option for handling nulls
+}
+
+// Data transfer object for calibration responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BurnerCalibrationDto {
+    pub id: i32,
+    pub stage: i32,
+    pub litres_per_hour: f32,
+    pub valid_from: NaiveDate,
+    pub valid_to: Option<NaiveDate>, // Day before the next version of the stage
+    pub notes: Option<String>,
+    pub building_id: i32,
+}
+
+// Database model for a central boiler producing space heat and hot water (HeizkostenV § 9)
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = heating_systems)]
+pub struct HeatingSystem {
+    pub id: Option<i32>,
+    pub building_id: i32,
+    pub name: String,
+    pub fuel_cost_type_id: i32,      // Fuel costs, split before allocation
+    pub heating_cost_type_id: i32,   // Receives the space heating part
+    pub hot_water_cost_type_id: i32, // Receives the hot water part; its bound meters give the volume
+    pub fuel_meter_id: Option<i32>,  // Fuel counter; otherwise burner hours or oil tanks
+    pub hot_water_heat_meter_id: Option<i32>, // Heat meter (kWh); otherwise the § 9 formula
+    pub hot_water_temperature: f32,  // tw in °C
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+// New heating system data for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = heating_systems)]
+pub struct NewHeatingSystem {
+    pub building_id: Option<i32>, // Required when there is more than one building
+    pub name: String,
+    pub fuel_cost_type_id: i32,
+    pub heating_cost_type_id: i32,
+    pub hot_water_cost_type_id: i32,
+    pub fuel_meter_id: Option<i32>,
+    pub hot_water_heat_meter_id: Option<i32>,
+    #[serde(default = "default_hot_water_temperature")]
+    pub hot_water_temperature: f32,
+}
+
+// Hot water temperature assumed by HeizkostenV § 9 (2) if not measured
+fn default_hot_water_temperature() -> f32 {
+    60.0
+}
+
+// Data transfer object for heating system updates
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = heating_systems)]
+pub struct HeatingSystemUpdate {
+    pub name: Option<String>,
+    pub fuel_cost_type_id: Option<i32>,
+    pub heating_cost_type_id: Option<i32>,
+    pub hot_water_cost_type_id: Option<i32>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub fuel_meter_id: Option<Option<i32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub hot_water_heat_meter_id: Option<Option<i32>>, // Double option for handling nulls
+    pub hot_water_temperature: Option<f32>,
+}
+
+// Data transfer object for heating system responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeatingSystemDto {
+    pub id: i32,
+    pub building_id: i32,
+    pub name: String,
+    pub fuel_cost_type_id: i32,
+    pub heating_cost_type_id: i32,
+    pub hot_water_cost_type_id: i32,
+    pub fuel_meter_id: Option<i32>,
+    pub hot_water_heat_meter_id: Option<i32>,
+    pub hot_water_temperature: f32,
+}
+
+// Fuel burned by a heating system
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FuelType {
+    HeatingOil,
+    NaturalGas,
+    LiquidGas,
+    WoodPellets,
+    Other, // No standard values, everything has to be entered
+}
+
+// Standard values of a fuel type, used where a fuel version leaves them out.
+// CO2 factors are those of the national emissions trading (BEHG) relative to Hi.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FuelDefaults {
+    pub fuel_type: FuelType,
+    pub label: &'static str,
+    pub fuel_unit: FuelUnit,
+    pub calorific_value: f32,     // Hi in kWh per fuel unit
+    pub density: Option<f32>,     // kg per fuel unit
+    pub co2_emission_factor: f32, // kg CO2 per kWh
+}
+
+impl FuelType {
+    pub const ALL: [FuelType; 5] = [
+        FuelType::HeatingOil,
+        FuelType::NaturalGas,
+        FuelType::LiquidGas,
+        FuelType::WoodPellets,
+        FuelType::Other,
+    ];
+
+    pub fn defaults(&self) -> Option<FuelDefaults> {
+        let (label, fuel_unit, calorific_value, density, co2_emission_factor) = match self {
+            FuelType::HeatingOil => ("Heizöl EL", FuelUnit::Litre, 10.0, Some(0.84), 0.266),
+            FuelType::NaturalGas => ("Erdgas H", FuelUnit::CubicMetre, 10.0, Some(0.78), 0.201),
+            FuelType::LiquidGas => ("Flüssiggas", FuelUnit::Litre, 6.57, Some(0.51), 0.239),
+            FuelType::WoodPellets => ("Holzpellets", FuelUnit::Kilogram, 4.9, None, 0.0),
+            FuelType::Other => return None,
+        };
+
+        Some(FuelDefaults {
+            fuel_type: *self,
+            label,
+            fuel_unit,
+            calorific_value,
+            density,
+            co2_emission_factor,
+        })
+    }
+}
+
+impl fmt::Display for FuelType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FuelType::HeatingOil => write!(f, "heating_oil"),
+            FuelType::NaturalGas => write!(f, "natural_gas"),
+            FuelType::LiquidGas => write!(f, "liquid_gas"),
+            FuelType::WoodPellets => write!(f, "wood_pellets"),
+            FuelType::Other => write!(f, "other"),
+        }
+    }
+}
+
+impl From<String> for FuelType {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "heating_oil" => FuelType::HeatingOil,
+            "natural_gas" => FuelType::NaturalGas,
+            "liquid_gas" => FuelType::LiquidGas,
+            "wood_pellets" => FuelType::WoodPellets,
+            _ => FuelType::Other, // Default to other for unknown values
+        }
+    }
+}
+
+// Unit fuel quantities are measured and invoiced in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FuelUnit {
+    #[serde(rename = "l")]
+    Litre,
+    #[serde(rename = "m3")]
+    CubicMetre,
+    #[serde(rename = "kg")]
+    Kilogram,
+    #[serde(rename = "kWh")]
+    KilowattHour,
+}
+
+impl FuelUnit {
+    // Unit of a meter or a quantity, None for units that are no fuel quantity
+    pub fn parse(unit: &str) -> Option<Self> {
+        match unit.trim().to_lowercase().replace('³', "3").as_str() {
+            "l" | "liter" | "litre" => Some(FuelUnit::Litre),
+            "m3" => Some(FuelUnit::CubicMetre),
+            "kg" => Some(FuelUnit::Kilogram),
+            "kwh" => Some(FuelUnit::KilowattHour),
+            _ => None,
+        }
+    }
+
+    // Unit as printed on the statement
+    pub fn label(&self) -> &'static str {
+        match self {
+            FuelUnit::Litre => "l",
+            FuelUnit::CubicMetre => "m³",
+            FuelUnit::Kilogram => "kg",
+            FuelUnit::KilowattHour => "kWh",
+        }
+    }
+}
+
+impl fmt::Display for FuelUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FuelUnit::Litre => write!(f, "l"),
+            FuelUnit::CubicMetre => write!(f, "m3"),
+            FuelUnit::Kilogram => write!(f, "kg"),
+            FuelUnit::KilowattHour => write!(f, "kWh"),
+        }
+    }
+}
+
+impl From<String> for FuelUnit {
+    fn from(s: String) -> Self {
+        FuelUnit::parse(&s).unwrap_or(FuelUnit::Litre) // Default to litres for unknown values
+    }
+}
+
+// Database model for a version of the fuel a heating system burns
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = heating_system_fuels)]
+pub struct HeatingSystemFuel {
+    pub id: Option<i32>,
+    pub heating_system_id: i32,
+    pub fuel_type: String,                // heating_oil, natural_gas, liquid_gas, wood_pellets or other
+    pub fuel_unit: String,                // l, m3, kg or kWh
+    pub calorific_value: f32,             // Hi in kWh per fuel unit
+    pub density: Option<f32>,             // kg per fuel unit
+    pub co2_emission_factor: Option<f32>, // kg CO2 per kWh (Hi)
+    pub efficiency: Option<f32>,          // Useful heat per kWh of fuel
+    pub valid_from: NaiveDate,            // Applies until the next version of the system
+    pub notes: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+// Fuel version as stored, also used to write corrections of all columns
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = heating_system_fuels, treat_none_as_null = true)]
+pub struct NewHeatingSystemFuel {
+    pub heating_system_id: i32,
+    pub fuel_type: String,
+    pub fuel_unit: String,
+    pub calorific_value: f32,
+    pub density: Option<f32>,
+    pub co2_emission_factor: Option<f32>,
+    pub efficiency: Option<f32>,
+    pub valid_from: NaiveDate,
+    pub notes: Option<String>,
+}
+
+// New fuel version; left out values are taken from the fuel type's standard values
+#[derive(Debug, Deserialize)]
+pub struct HeatingSystemFuelInput {
+    pub heating_system_id: i32,
+    pub fuel_type: FuelType,
+    pub fuel_unit: Option<FuelUnit>,
+    pub calorific_value: Option<f32>,
+    pub density: Option<f32>,
+    pub co2_emission_factor: Option<f32>,
+    pub efficiency: Option<f32>,
+    pub valid_from: NaiveDate,
+    pub notes: Option<String>,
+}
+
+// Data transfer object for fuel version corrections
+#[derive(Debug, Deserialize)]
+pub struct HeatingSystemFuelUpdate {
+    pub fuel_type: Option<FuelType>,
+    pub fuel_unit: Option<FuelUnit>,
+    pub calorific_value: Option<f32>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub density: Option<Option<f32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub co2_emission_factor: Option<Option<f32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub efficiency: Option<Option<f32>>, // Double option for handling nulls
+    pub valid_from: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub notes: Option<Option<String>>, // Double option for handling nulls
+}
+
+// Data transfer object for fuel version responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeatingSystemFuelDto {
+    pub id: i32,
+    pub heating_system_id: i32,
+    pub fuel_type: FuelType,
+    pub fuel_unit: FuelUnit,
+    pub calorific_value: f32,
+    pub density: Option<f32>,
+    pub co2_emission_factor: Option<f32>,
+    pub efficiency: Option<f32>,
+    pub valid_from: NaiveDate,
+    pub valid_to: Option<NaiveDate>, // Day before the next version of the system
+    pub notes: Option<String>,
+}
+
+// Filter for the fuel versions of one heating system
+#[derive(Debug, Deserialize)]
+pub struct HeatingSystemFuelFilter {
+    pub heating_system_id: Option<i32>,
+}
+
+// Date range for the fuel energy of a heating system
+#[derive(Debug, Deserialize)]
+pub struct FuelEnergyQuery {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+// Database model for a month of a building's own degree-day table
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = degree_day_shares)]
+pub struct DegreeDayShare {
+    pub id: Option<i32>,
+    pub building_id: i32,
+    pub month: i32,     // 1 (January) to 12 (December)
+    pub per_mille: f32, // Share of the annual heating demand
+}
+
+// New degree-day month for insertions
+#[derive(Debug, Insertable)]
+#[diesel(table_name = degree_day_shares)]
+pub struct NewDegreeDayShare {
+    pub building_id: i32,
+    pub month: i32,
+    pub per_mille: f32,
+}
+
+// Degree-day table replacing the standard one, January to December
+#[derive(Debug, Deserialize)]
+pub struct DegreeDayTableInput {
+    pub building_id: Option<i32>, // Required when there is more than one building
+    pub monthly_per_mille: Vec<f32>,
+}
+
+// Date range for oil consumption queries
+#[derive(Debug, Deserialize)]
+pub struct OilConsumptionQuery {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub building_id: Option<i32>, // Required when there is more than one building
+}
+
+impl From<BurnerCalibration> for BurnerCalibrationDto {
+    fn from(calibration: BurnerCalibration) -> Self {
+        BurnerCalibrationDto {
+            id: calibration.id.unwrap_or(0),
+            stage: calibration.stage,
+            litres_per_hour: calibration.litres_per_hour,
+            valid_from: calibration.valid_from,
+            valid_to: None, // Filled in from the following version
+            notes: calibration.notes,
+            building_id: calibration.building_id,
+        }
+    }
+}
+
+impl From<HeatingSystem> for HeatingSystemDto {
+    fn from(system: HeatingSystem) -> Self {
+        HeatingSystemDto {
+            id: system.id.unwrap_or(0),
+            building_id: system.building_id,
+            name: system.name,
+            fuel_cost_type_id: system.fuel_cost_type_id,
+            heating_cost_type_id: system.heating_cost_type_id,
+            hot_water_cost_type_id: system.hot_water_cost_type_id,
+            fuel_meter_id: system.fuel_meter_id,
+            hot_water_heat_meter_id: system.hot_water_heat_meter_id,
+            hot_water_temperature: system.hot_water_temperature,
+        }
+    }
+}
+
+impl From<HeatingSystemFuel> for HeatingSystemFuelDto {
+    fn from(fuel: HeatingSystemFuel) -> Self {
+        HeatingSystemFuelDto {
+            id: fuel.id.unwrap_or(0),
+            heating_system_id: fuel.heating_system_id,
+            fuel_type: FuelType::from(fuel.fuel_type),
+            fuel_unit: FuelUnit::from(fuel.fuel_unit),
+            calorific_value: fuel.calorific_value,
+            density: fuel.density,
+            co2_emission_factor: fuel.co2_emission_factor,
+            efficiency: fuel.efficiency,
+            valid_from: fuel.valid_from,
+            valid_to: None, // Filled in from the following version
+            notes: fuel.notes,
+        }
+    }
+}
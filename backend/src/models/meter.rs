@@ -1,8 +1,15 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-use crate::{db, schema::meters};
+use crate::models::decimal::SqlDecimal;
+use crate::models::deserialize_some;
+use crate::schema::{meter_cost_types, meters};
+
+// Meter type of the operating-hour counters of an oil burner stage
+pub const BURNER_HOURS_METER_TYPE: &str = "burner_hours";
 
 // Assignment type enum for meters
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -12,11 +19,11 @@ pub enum MeterAssignment {
     Common,
 }
 
-impl ToString for MeterAssignment {
-    fn to_string(&self) -> String {
+impl fmt::Display for MeterAssignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            MeterAssignment::Unit => "unit".to_string(),
-            MeterAssignment::Common => "common".to_string(),
+            MeterAssignment::Unit => write!(f, "unit"),
+            MeterAssignment::Common => write!(f, "common"),
         }
     }
 }
@@ -31,6 +38,33 @@ impl From<String> for MeterAssignment {
     }
 }
 
+// Weighting used when estimating a meter's value between two readings
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InterpolationMethod {
+    #[default]
+    Linear,   // Consumption spread evenly over the days
+    Seasonal, // Consumption spread by the monthly degree-day shares (heating)
+}
+
+impl fmt::Display for InterpolationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpolationMethod::Linear => write!(f, "linear"),
+            InterpolationMethod::Seasonal => write!(f, "seasonal"),
+        }
+    }
+}
+
+impl From<String> for InterpolationMethod {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "seasonal" => InterpolationMethod::Seasonal,
+            _ => InterpolationMethod::Linear, // Default to linear for unknown values
+        }
+    }
+}
+
 // Database model for meters
 #[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Associations, Selectable)]
 #[diesel(table_name = meters)]
@@ -44,6 +78,12 @@ pub struct Meter {
     pub property_unit_id: Option<i32>, // Nullable for common meters
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub interpolation_method: String,  // linear or seasonal
+    pub burner_stage: Option<i32>,     // Stage 1 or 2 for burner_hours meters
+    pub parent_meter_id: Option<i32>,  // Main meter this sub-meter is installed behind
+    pub residual_property_unit_id: Option<i32>, // Unit receiving main minus sub-meters (common meters)
+    pub residual_warning_percent: Option<f32>,  // Plausibility limit for the residual share
+    pub building_id: i32,                       // Unit meters belong to their unit's building
 }
 
 // New meter data for insertions
@@ -55,6 +95,12 @@ pub struct NewMeter {
     pub unit: String,
     pub assignment_type: String,
     pub property_unit_id: Option<i32>,
+    pub interpolation_method: String,
+    pub burner_stage: Option<i32>,
+    pub parent_meter_id: Option<i32>,
+    pub residual_property_unit_id: Option<i32>,
+    pub residual_warning_percent: Option<f32>,
+    pub building_id: Option<i32>,
 }
 
 // Data transfer object for meter updates
@@ -66,6 +112,16 @@ pub struct MeterUpdate {
     pub unit: Option<String>,
     pub assignment_type: Option<String>,
     pub property_unit_id: Option<Option<i32>>, // Double option for handling nulls
+    pub interpolation_method: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub burner_stage: Option<Option<i32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub parent_meter_id: Option<Option<i32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub residual_property_unit_id: Option<Option<i32>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub residual_warning_percent: Option<Option<f32>>, // Double option for handling nulls
+    pub building_id: Option<i32>, // Only for common meters; unit meters follow their unit
 }
 
 // Data transfer object for API responses
@@ -77,6 +133,12 @@ pub struct MeterDto {
     pub unit: String,
     pub assignment_type: MeterAssignment,
     pub property_unit_id: Option<i32>,
+    pub interpolation_method: InterpolationMethod,
+    pub burner_stage: Option<i32>,
+    pub parent_meter_id: Option<i32>,
+    pub residual_property_unit_id: Option<i32>,
+    pub residual_warning_percent: Option<f32>,
+    pub building_id: i32,
 }
 
 // DTO with additional validation for creating/updating
@@ -87,6 +149,118 @@ pub struct MeterInputDto {
     pub unit: String,
     pub assignment_type: MeterAssignment,
     pub property_unit_id: Option<i32>,
+    #[serde(default)]
+    pub interpolation_method: InterpolationMethod,
+    pub burner_stage: Option<i32>,
+    pub parent_meter_id: Option<i32>,
+    pub residual_property_unit_id: Option<i32>,
+    pub residual_warning_percent: Option<f32>,
+    pub building_id: Option<i32>, // Required for common meters when there is more than one building
+}
+
+// Junction table model binding a meter to a cost type its consumption is billed under
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Associations, Selectable)]
+#[diesel(table_name = meter_cost_types)]
+#[diesel(belongs_to(Meter))]
+pub struct MeterCostType {
+    pub id: Option<i32>,
+    pub meter_id: i32,
+    pub cost_type_id: i32,
+    pub conversion_factor: Decimal, // Cost type units per meter unit
+}
+
+// New meter to cost type binding for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = meter_cost_types)]
+pub struct NewMeterCostType {
+    pub meter_id: i32,
+    pub cost_type_id: i32,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub conversion_factor: Decimal,
+}
+
+// Request body when binding a meter to a cost type
+#[derive(Debug, Deserialize)]
+pub struct MeterCostTypeInput {
+    pub conversion_factor: Option<Decimal>, // Required when the units differ
+}
+
+// Data transfer object for the cost types a meter is bound to
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeterCostTypeDto {
+    pub cost_type_id: i32,
+    pub cost_type_name: String,
+    pub cost_type_unit: Option<String>,
+    pub conversion_factor: Decimal,
+}
+
+// Whether a meter unit and a cost type unit name the same unit, e.g. "m3" and "m³"
+pub fn units_match(meter_unit: &str, cost_type_unit: &str) -> bool {
+    let normalize = |unit: &str| {
+        unit.trim()
+            .to_lowercase()
+            .replace('³', "3")
+            .replace('²', "2")
+    };
+
+    normalize(meter_unit) == normalize(cost_type_unit)
+}
+
+// Why a meter's values can't be billed in a cost type's unit with the given conversion factor.
+// Values in another unit need a factor other than 1, values in the cost type's unit none.
+pub fn conversion_mismatch(
+    meter_name: &str,
+    meter_unit: &str,
+    cost_type_name: &str,
+    cost_type_unit: Option<&str>,
+    conversion_factor: Decimal,
+) -> Option<String> {
+    let cost_type_unit = cost_type_unit?;
+    match (units_match(meter_unit, cost_type_unit), conversion_factor == Decimal::ONE) {
+        (true, false) => Some(format!(
+            "Meter '{}' and cost type '{}' both count in '{}', conversion factor {} would change the consumption",
+            meter_name,
+            cost_type_name,
+            cost_type_unit,
+            conversion_factor.normalize()
+        )),
+        (false, true) => Some(format!(
+            "Meter unit '{}' of meter '{}' doesn't match the unit '{}' of cost type '{}'; a conversion factor is required",
+            meter_unit, meter_name, cost_type_unit, cost_type_name
+        )),
+        _ => None,
+    }
+}
+
+// A meter binding with the units on both sides, to check the conversion factor against them
+#[derive(Debug, Queryable)]
+pub struct BindingUnits {
+    pub meter_id: i32,
+    pub meter_name: String,
+    pub meter_unit: String,
+    pub cost_type_id: i32,
+    pub cost_type_name: String,
+    pub cost_type_unit: Option<String>,
+    pub conversion_factor: Decimal,
+}
+
+impl BindingUnits {
+    pub fn mismatch(&self) -> Option<String> {
+        conversion_mismatch(
+            &self.meter_name,
+            &self.meter_unit,
+            &self.cost_type_name,
+            self.cost_type_unit.as_deref(),
+            self.conversion_factor,
+        )
+    }
+}
+
+// Date range for residual consumption queries
+#[derive(Debug, Deserialize)]
+pub struct ResidualQuery {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
 }
 
 impl From<Meter> for MeterDto {
@@ -98,6 +272,12 @@ impl From<Meter> for MeterDto {
             unit: meter.unit,
             assignment_type: MeterAssignment::from(meter.assignment_type),
             property_unit_id: meter.property_unit_id,
+            interpolation_method: InterpolationMethod::from(meter.interpolation_method),
+            burner_stage: meter.burner_stage,
+            parent_meter_id: meter.parent_meter_id,
+            residual_property_unit_id: meter.residual_property_unit_id,
+            residual_warning_percent: meter.residual_warning_percent,
+            building_id: meter.building_id,
         }
     }
 }
@@ -106,9 +286,10 @@ impl From<MeterInputDto> for NewMeter {
     fn from(dto: MeterInputDto) -> Self {
         // Validate that unit meters have a property_unit_id
         let assignment_type = dto.assignment_type.to_string();
-        let property_unit_id = match dto.assignment_type {
-            MeterAssignment::Unit => dto.property_unit_id,
-            MeterAssignment::Common => None, // Common meters don't need a property_unit_id
+        let (property_unit_id, residual_property_unit_id) = match dto.assignment_type {
+            MeterAssignment::Unit => (dto.property_unit_id, None),
+            // Common meters don't need a property_unit_id, but may pass their residual to a unit
+            MeterAssignment::Common => (None, dto.residual_property_unit_id),
         };
 
         NewMeter {
@@ -117,6 +298,12 @@ impl From<MeterInputDto> for NewMeter {
             unit: dto.unit,
             assignment_type,
             property_unit_id,
+            interpolation_method: dto.interpolation_method.to_string(),
+            burner_stage: dto.burner_stage,
+            parent_meter_id: dto.parent_meter_id,
+            residual_property_unit_id,
+            residual_warning_percent: dto.residual_warning_percent,
+            building_id: dto.building_id,
         }
     }
 }
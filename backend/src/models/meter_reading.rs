@@ -1,7 +1,9 @@
 use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::models::decimal::SqlDecimal;
 use crate::models::meter::Meter;
 use crate::schema::meter_readings;
 
@@ -13,7 +15,7 @@ pub struct MeterReading {
     pub id: Option<i32>,
     pub meter_id: i32,
     pub reading_date: NaiveDateTime,
-    pub value: f32,
+    pub value: Decimal,
     pub notes: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
@@ -25,7 +27,8 @@ pub struct MeterReading {
 pub struct NewMeterReading {
     pub meter_id: i32,
     pub reading_date: NaiveDateTime,
-    pub value: f32,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub value: Decimal,
     pub notes: Option<String>,
 }
 
@@ -34,7 +37,8 @@ pub struct NewMeterReading {
 #[diesel(table_name = meter_readings)]
 pub struct MeterReadingUpdate {
     pub reading_date: Option<NaiveDateTime>,
-    pub value: Option<f32>,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub value: Option<Decimal>,
     pub notes: Option<Option<String>>, // Double option for handling nulls
 }
 
@@ -43,7 +47,7 @@ pub struct MeterReadingUpdate {
 pub struct MeterReadingInputDto {
     pub meter_id: i32,
     pub reading_date: NaiveDate, // Use just the date for simpler input
-    pub value: f32,
+    pub value: Decimal,
     pub notes: Option<String>,
 }
 
@@ -53,7 +57,7 @@ pub struct MeterReadingDto {
     pub id: i32,
     pub meter_id: i32,
     pub reading_date: NaiveDate, // Use just the date for simpler output
-    pub value: f32,
+    pub value: Decimal,
     pub notes: Option<String>,
 }
 
@@ -63,10 +67,18 @@ pub struct MeterReadingWithConsumption {
     pub id: i32,
     pub meter_id: i32,
     pub reading_date: NaiveDate,
-    pub value: f32,
+    pub value: Decimal,
     pub notes: Option<String>,
-    pub consumption: Option<f32>, // Consumption since last reading
+    pub consumption: Option<Decimal>, // Consumption since last reading
     pub days_since_last_reading: Option<i64>, // Days since last reading
+    pub estimated: bool, // Interpolated value at a range boundary, not an actual reading
+}
+
+// Optional date range for consumption queries
+#[derive(Debug, Deserialize)]
+pub struct ConsumptionQuery {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
 }
 
 impl From<MeterReading> for MeterReadingDto {
@@ -81,6 +93,21 @@ impl From<MeterReading> for MeterReadingDto {
     }
 }
 
+impl From<&MeterReading> for MeterReadingWithConsumption {
+    fn from(reading: &MeterReading) -> Self {
+        MeterReadingWithConsumption {
+            id: reading.id.unwrap_or(0),
+            meter_id: reading.meter_id,
+            reading_date: reading.reading_date.date(),
+            value: reading.value,
+            notes: reading.notes.clone(),
+            consumption: None,
+            days_since_last_reading: None,
+            estimated: false,
+        }
+    }
+}
+
 impl From<MeterReadingInputDto> for NewMeterReading {
     fn from(dto: MeterReadingInputDto) -> Self {
         // Convert date to datetime by setting the time to midnight
@@ -1,6 +1,23 @@
+use serde::{Deserialize, Deserializer};
+
+pub mod building;
 pub mod property_unit;
 pub mod tenant;
 pub mod meter;
 pub mod meter_reading;
 pub mod cost;
 pub mod billing;
+pub mod prepayment;
+pub mod heating;
+pub mod oil_tank;
+pub mod decimal;
+
+// Deserialize a present field (including an explicit null) as Some, so that
+// `Option<Option<T>>` update fields can tell "set to null" apart from "not sent"
+pub fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Deserialize::deserialize(deserializer).map(Some)
+}
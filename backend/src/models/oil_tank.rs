@@ -0,0 +1,204 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::models::decimal::SqlDecimal;
+use crate::models::deserialize_some;
+use crate::schema::{oil_deliveries, oil_tank_readings, oil_tanks};
+
+// Database model for heating oil tanks
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = oil_tanks)]
+pub struct OilTank {
+    pub id: Option<i32>,
+    pub name: String,
+    pub capacity_litres: Decimal,
+    pub cost_type_id: Option<i32>, // Cost type receiving the fuel cost
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+// New oil tank data for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = oil_tanks)]
+pub struct NewOilTank {
+    pub name: String,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub capacity_litres: Decimal,
+    pub cost_type_id: Option<i32>,
+}
+
+// Data transfer object for oil tank updates
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = oil_tanks)]
+pub struct OilTankUpdate {
+    pub name: Option<String>,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub capacity_litres: Option<Decimal>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub cost_type_id: Option<Option<i32>>, // Double option for handling nulls
+}
+
+// Data transfer object for oil tank responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OilTankDto {
+    pub id: i32,
+    pub name: String,
+    pub capacity_litres: Decimal,
+    pub cost_type_id: Option<i32>,
+}
+
+// Database model for oil deliveries
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = oil_deliveries)]
+pub struct OilDelivery {
+    pub id: Option<i32>,
+    pub tank_id: i32,
+    pub delivery_date: NaiveDate,
+    pub litres: Decimal,
+    pub total_price: Decimal, // Invoice amount of the delivery
+    pub invoice_reference: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl OilDelivery {
+    pub fn price_per_litre(&self) -> Decimal {
+        if self.litres > Decimal::ZERO {
+            self.total_price / self.litres
+        } else {
+            Decimal::ZERO
+        }
+    }
+}
+
+// New oil delivery for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = oil_deliveries)]
+pub struct NewOilDelivery {
+    pub tank_id: i32,
+    pub delivery_date: NaiveDate,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub litres: Decimal,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub total_price: Decimal,
+    pub invoice_reference: Option<String>,
+}
+
+// Data transfer object for oil delivery updates
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = oil_deliveries)]
+pub struct OilDeliveryUpdate {
+    pub delivery_date: Option<NaiveDate>,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub litres: Option<Decimal>,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub total_price: Option<Decimal>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub invoice_reference: Option<Option<String>>, // Double option for handling nulls
+}
+
+// Data transfer object for oil delivery responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OilDeliveryDto {
+    pub id: i32,
+    pub tank_id: i32,
+    pub delivery_date: NaiveDate,
+    pub litres: Decimal,
+    pub total_price: Decimal,
+    pub price_per_litre: Decimal,
+    pub invoice_reference: Option<String>,
+}
+
+// Database model for tank level readings
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = oil_tank_readings)]
+pub struct OilTankReading {
+    pub id: Option<i32>,
+    pub tank_id: i32,
+    pub reading_date: NaiveDate, // Taken before any delivery of the same day
+    pub litres: Decimal,
+    pub reading_type: String,    // dip or gauge
+    pub notes: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+// New tank level reading for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = oil_tank_readings)]
+pub struct NewOilTankReading {
+    pub tank_id: i32,
+    pub reading_date: NaiveDate,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub litres: Decimal,
+    #[serde(default = "default_reading_type")]
+    pub reading_type: String,
+    pub notes: Option<String>,
+}
+
+// Data transfer object for tank level reading updates
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = oil_tank_readings)]
+pub struct OilTankReadingUpdate {
+    pub reading_date: Option<NaiveDate>,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub litres: Option<Decimal>,
+    pub reading_type: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub notes: Option<Option<String>>, // Double option for handling nulls
+}
+
+// Data transfer object for tank level reading responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OilTankReadingDto {
+    pub id: i32,
+    pub tank_id: i32,
+    pub reading_date: NaiveDate,
+    pub litres: Decimal,
+    pub reading_type: String,
+    pub notes: Option<String>,
+}
+
+fn default_reading_type() -> String {
+    "dip".to_string()
+}
+
+impl From<OilTank> for OilTankDto {
+    fn from(tank: OilTank) -> Self {
+        OilTankDto {
+            id: tank.id.unwrap_or(0),
+            name: tank.name,
+            capacity_litres: tank.capacity_litres,
+            cost_type_id: tank.cost_type_id,
+        }
+    }
+}
+
+impl From<OilDelivery> for OilDeliveryDto {
+    fn from(delivery: OilDelivery) -> Self {
+        OilDeliveryDto {
+            id: delivery.id.unwrap_or(0),
+            tank_id: delivery.tank_id,
+            delivery_date: delivery.delivery_date,
+            litres: delivery.litres,
+            total_price: delivery.total_price,
+            price_per_litre: delivery.price_per_litre(),
+            invoice_reference: delivery.invoice_reference,
+        }
+    }
+}
+
+impl From<OilTankReading> for OilTankReadingDto {
+    fn from(reading: OilTankReading) -> Self {
+        OilTankReadingDto {
+            id: reading.id.unwrap_or(0),
+            tank_id: reading.tank_id,
+            reading_date: reading.reading_date,
+            litres: reading.litres,
+            reading_type: reading.reading_type,
+            notes: reading.notes,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::models::decimal::SqlDecimal;
+use crate::schema::{prepayment_schedules, prepayments};
+
+// Database model for a tenant's monthly prepayment schedule
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = prepayment_schedules)]
+pub struct PrepaymentSchedule {
+    pub id: Option<i32>,
+    pub tenant_id: i32,
+    pub monthly_amount: Decimal,
+    pub valid_from: NaiveDate,
+    pub valid_to: Option<NaiveDate>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+// New prepayment schedule data for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = prepayment_schedules)]
+pub struct NewPrepaymentSchedule {
+    pub tenant_id: i32,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub monthly_amount: Decimal,
+    pub valid_from: NaiveDate,
+    pub valid_to: Option<NaiveDate>,
+}
+
+// Data transfer object for prepayment schedule updates
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = prepayment_schedules)]
+pub struct PrepaymentScheduleUpdate {
+    #[diesel(serialize_as = SqlDecimal)]
+    pub monthly_amount: Option<Decimal>,
+    pub valid_from: Option<NaiveDate>,
+    pub valid_to: Option<Option<NaiveDate>>,
+}
+
+// Data transfer object for prepayment schedule responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrepaymentScheduleDto {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub monthly_amount: Decimal,
+    pub valid_from: NaiveDate,
+    pub valid_to: Option<NaiveDate>,
+}
+
+// Database model for a prepayment received within a billing period
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = prepayments)]
+pub struct Prepayment {
+    pub id: Option<i32>,
+    pub tenant_id: i32,
+    pub billing_period_id: i32,
+    pub amount: Decimal,
+    pub received_date: NaiveDate,
+    pub notes: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+// New received prepayment for insertions
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = prepayments)]
+pub struct NewPrepayment {
+    pub tenant_id: i32,
+    pub billing_period_id: i32,
+    #[diesel(serialize_as = SqlDecimal)]
+    pub amount: Decimal,
+    pub received_date: NaiveDate,
+    pub notes: Option<String>,
+}
+
+// Data transfer object for received prepayment updates
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = prepayments)]
+pub struct PrepaymentUpdate {
+    #[diesel(serialize_as = SqlDecimal)]
+    pub amount: Option<Decimal>,
+    pub received_date: Option<NaiveDate>,
+    pub notes: Option<Option<String>>, // Double option for handling nulls
+}
+
+// Data transfer object for received prepayment responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrepaymentDto {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub billing_period_id: i32,
+    pub amount: Decimal,
+    pub received_date: NaiveDate,
+    pub notes: Option<String>,
+}
+
+impl From<PrepaymentSchedule> for PrepaymentScheduleDto {
+    fn from(schedule: PrepaymentSchedule) -> Self {
+        PrepaymentScheduleDto {
+            id: schedule.id.unwrap_or(0),
+            tenant_id: schedule.tenant_id,
+            monthly_amount: schedule.monthly_amount,
+            valid_from: schedule.valid_from,
+            valid_to: schedule.valid_to,
+        }
+    }
+}
+
+impl From<Prepayment> for PrepaymentDto {
+    fn from(prepayment: Prepayment) -> Self {
+        PrepaymentDto {
+            id: prepayment.id.unwrap_or(0),
+            tenant_id: prepayment.tenant_id,
+            billing_period_id: prepayment.billing_period_id,
+            amount: prepayment.amount,
+            received_date: prepayment.received_date,
+            notes: prepayment.notes,
+        }
+    }
+}
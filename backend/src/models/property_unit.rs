@@ -2,6 +2,7 @@ use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::models::deserialize_some;
 use crate::schema::property_units;
 
 // Database model for property units
@@ -13,6 +14,22 @@ pub struct PropertyUnit {
     pub living_area_m2: f32,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub heated_area_m2: Option<f32>, // Falls back to the living area if not set
+    pub owner_occupied: bool,        // Days without tenancy are the owner's own use
+    pub owner_persons: Option<i32>,  // Persons during the owner's own use
+    pub building_id: i32,
+}
+
+impl PropertyUnit {
+    // Area used for the base part of heating costs
+    pub fn heated_area(&self) -> f32 {
+        self.heated_area_m2.unwrap_or(self.living_area_m2)
+    }
+
+    // Persons counted for the owner's own use, one if not set
+    pub fn owner_persons(&self) -> i32 {
+        self.owner_persons.unwrap_or(1)
+    }
 }
 
 // New property unit data for insertions
@@ -21,6 +38,11 @@ pub struct PropertyUnit {
 pub struct NewPropertyUnit {
     pub name: String,
     pub living_area_m2: f32,
+    pub heated_area_m2: Option<f32>,
+    #[serde(default)]
+    pub owner_occupied: bool,
+    pub owner_persons: Option<i32>,
+    pub building_id: Option<i32>, // Required when there is more than one building
 }
 
 // Data transfer object for property unit updates
@@ -29,6 +51,12 @@ pub struct NewPropertyUnit {
 pub struct PropertyUnitUpdate {
     pub name: Option<String>,
     pub living_area_m2: Option<f32>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub heated_area_m2: Option<Option<f32>>, // Double option for handling nulls
+    pub owner_occupied: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub owner_persons: Option<Option<i32>>, // Double option for handling nulls
+    pub building_id: Option<i32>,
 }
 
 // Data transfer object for responses
@@ -37,6 +65,10 @@ pub struct PropertyUnitDto {
     pub id: i32,
     pub name: String,
     pub living_area_m2: f32,
+    pub heated_area_m2: Option<f32>,
+    pub owner_occupied: bool,
+    pub owner_persons: Option<i32>,
+    pub building_id: i32,
 }
 
 impl From<PropertyUnit> for PropertyUnitDto {
@@ -45,6 +77,10 @@ impl From<PropertyUnit> for PropertyUnitDto {
             id: unit.id.unwrap_or(0),
             name: unit.name,
             living_area_m2: unit.living_area_m2,
+            heated_area_m2: unit.heated_area_m2,
+            owner_occupied: unit.owner_occupied,
+            owner_persons: unit.owner_persons,
+            building_id: unit.building_id,
         }
     }
 }
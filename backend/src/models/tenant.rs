@@ -1,7 +1,8 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::models::deserialize_some;
 use crate::schema::tenants;
 
 // Database model for tenants
@@ -15,6 +16,16 @@ pub struct Tenant {
     pub property_unit_id: i32,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub move_in_date: Option<NaiveDate>,  // None: tenancy started before any billing period
+    pub move_out_date: Option<NaiveDate>, // None: tenancy is ongoing
+}
+
+impl Tenant {
+    // Whether the tenancy covers the given day
+    pub fn occupies(&self, date: NaiveDate) -> bool {
+        self.move_in_date.is_none_or(|move_in| move_in <= date)
+            && self.move_out_date.is_none_or(|move_out| move_out >= date)
+    }
 }
 
 // New tenant data for insertions
@@ -24,6 +35,8 @@ pub struct NewTenant {
     pub name: String,
     pub number_of_persons: i32,
     pub property_unit_id: i32,
+    pub move_in_date: Option<NaiveDate>,
+    pub move_out_date: Option<NaiveDate>,
 }
 
 // Data transfer object for tenant updates
@@ -33,6 +46,10 @@ pub struct TenantUpdate {
     pub name: Option<String>,
     pub number_of_persons: Option<i32>,
     pub property_unit_id: Option<i32>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub move_in_date: Option<Option<NaiveDate>>, // Double option for handling nulls
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub move_out_date: Option<Option<NaiveDate>>,
 }
 
 // Data transfer object for responses
@@ -42,6 +59,8 @@ pub struct TenantDto {
     pub name: String,
     pub number_of_persons: i32,
     pub property_unit_id: i32,
+    pub move_in_date: Option<NaiveDate>,
+    pub move_out_date: Option<NaiveDate>,
 }
 
 impl From<Tenant> for TenantDto {
@@ -51,6 +70,8 @@ impl From<Tenant> for TenantDto {
             name: tenant.name,
             number_of_persons: tenant.number_of_persons,
             property_unit_id: tenant.property_unit_id,
+            move_in_date: tenant.move_in_date,
+            move_out_date: tenant.move_out_date,
         }
     }
 }
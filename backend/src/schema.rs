@@ -1,5 +1,12 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+    // Exact decimal number, stored as text since SQLite has no decimal type
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(sqlite_type(name = "Text"))]
+    pub struct ExactDecimal;
+}
+
 diesel::table! {
     allocation_methods (id) {
         id -> Nullable<Integer>,
@@ -8,10 +15,35 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    betrkv_categories (id) {
+        id -> Nullable<Integer>,
+        position -> Integer,
+        legal_reference -> Nullable<Text>,
+        name -> Text,
+        allocable -> Bool,
+    }
+}
+
+diesel::table! {
+    billing_jobs (id) {
+        id -> Nullable<Integer>,
+        billing_period_id -> Integer,
+        status -> Text,
+        total_tenants -> Integer,
+        processed_tenants -> Integer,
+        errors -> Nullable<Text>,
+        owner_overview -> Nullable<Text>,
+        created_at -> Text,
+        finished_at -> Nullable<Text>,
+        notes -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     billing_periods (id) {
         id -> Nullable<Integer>,
-        property_unit_id -> Integer,
+        building_id -> Integer,
         start_date -> Text,
         end_date -> Text,
         name -> Text,
@@ -21,13 +53,104 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
+    billing_statement_items (id) {
+        id -> Nullable<Integer>,
+        billing_statement_id -> Integer,
+        cost_type_id -> Integer,
+        cost_type_name -> Text,
+        allocation_key -> Text,
+        total_amount -> ExactDecimal,
+        total_quantity -> ExactDecimal,
+        tenant_quantity -> ExactDecimal,
+        share_amount -> ExactDecimal,
+        position -> Integer,
+        tariff_id -> Nullable<Integer>,
+        price_per_unit -> Nullable<ExactDecimal>,
+        segment_start -> Nullable<Date>,
+        segment_end -> Nullable<Date>,
+        consumption_estimated -> Bool,
+        cost_part -> Nullable<Text>,
+        cost_part_percent -> Nullable<Float>,
+        common_consumption -> Bool,
+        fixed_cost_id -> Nullable<Integer>,
+        invoice_amount -> Nullable<ExactDecimal>,
+        invoice_start -> Nullable<Date>,
+        invoice_end -> Nullable<Date>,
+        invoice_days -> Nullable<Integer>,
+        apportioned_days -> Nullable<Integer>,
+        source_cost_type_id -> Nullable<Integer>,
+        source_cost_type_name -> Nullable<Text>,
+        source_share_percent -> Nullable<ExactDecimal>,
+        co2_cost -> Nullable<ExactDecimal>,
+        co2_landlord_amount -> Nullable<ExactDecimal>,
+        transfer_name -> Nullable<Text>,
+        transfer_cost_type_name -> Nullable<Text>,
+        transfer_amount -> Nullable<ExactDecimal>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
     billing_statements (id) {
         id -> Nullable<Integer>,
         billing_period_id -> Integer,
         tenant_id -> Integer,
-        total_amount -> Float,
+        total_amount -> ExactDecimal,
         generated_at -> Text,
         html_content -> Nullable<Text>,
+        prepayments_amount -> ExactDecimal,
+        balance_amount -> ExactDecimal,
+        calculation_trace -> Nullable<Text>,
+        billing_job_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    buildings (id) {
+        id -> Nullable<Integer>,
+        name -> Text,
+        address -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        vacancy_persons -> Text,
+    }
+}
+
+diesel::table! {
+    burner_calibrations (id) {
+        id -> Nullable<Integer>,
+        stage -> Integer,
+        litres_per_hour -> Float,
+        valid_from -> Date,
+        notes -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        building_id -> Integer,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
+    cost_transfers (id) {
+        id -> Nullable<Integer>,
+        building_id -> Integer,
+        name -> Text,
+        source_cost_type_id -> Integer,
+        target_cost_type_id -> Integer,
+        method -> Text,
+        percent -> Nullable<Float>,
+        basis_cost_type_id -> Nullable<Integer>,
+        meter_id -> Nullable<Integer>,
+        price_per_unit -> Nullable<ExactDecimal>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -48,27 +171,96 @@ diesel::table! {
         unit -> Nullable<Text>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        consumption_share_percent -> Nullable<Float>,
+        uses_burner_hours -> Bool,
+        betrkv_category_id -> Nullable<Integer>,
+        building_id -> Integer,
+        degree_day_weighting -> Bool,
+        co2_emission_factor -> Nullable<Float>,
+    }
+}
+
+diesel::table! {
+    degree_day_shares (id) {
+        id -> Nullable<Integer>,
+        building_id -> Integer,
+        month -> Integer,
+        per_mille -> Float,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
     fixed_costs (id) {
         id -> Nullable<Integer>,
         cost_type_id -> Integer,
-        amount -> Float,
+        amount -> ExactDecimal,
         billing_period_start -> Date,
         billing_period_end -> Date,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        fuel_quantity -> Nullable<ExactDecimal>,
+        co2_emission_factor -> Nullable<Float>,
+        co2_cost -> Nullable<ExactDecimal>,
+    }
+}
+
+diesel::table! {
+    heating_system_fuels (id) {
+        id -> Nullable<Integer>,
+        heating_system_id -> Integer,
+        fuel_type -> Text,
+        fuel_unit -> Text,
+        calorific_value -> Float,
+        density -> Nullable<Float>,
+        co2_emission_factor -> Nullable<Float>,
+        efficiency -> Nullable<Float>,
+        valid_from -> Date,
+        notes -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
 diesel::table! {
+    heating_systems (id) {
+        id -> Nullable<Integer>,
+        building_id -> Integer,
+        name -> Text,
+        fuel_cost_type_id -> Integer,
+        heating_cost_type_id -> Integer,
+        hot_water_cost_type_id -> Integer,
+        fuel_meter_id -> Nullable<Integer>,
+        hot_water_heat_meter_id -> Nullable<Integer>,
+        hot_water_temperature -> Float,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
+    meter_cost_types (id) {
+        id -> Nullable<Integer>,
+        meter_id -> Integer,
+        cost_type_id -> Integer,
+        conversion_factor -> ExactDecimal,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
     meter_readings (id) {
         id -> Nullable<Integer>,
         meter_id -> Integer,
         reading_date -> Timestamp,
-        value -> Float,
+        value -> ExactDecimal,
         notes -> Nullable<Text>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
@@ -85,6 +277,89 @@ diesel::table! {
         property_unit_id -> Nullable<Integer>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        interpolation_method -> Text,
+        burner_stage -> Nullable<Integer>,
+        parent_meter_id -> Nullable<Integer>,
+        residual_property_unit_id -> Nullable<Integer>,
+        residual_warning_percent -> Nullable<Float>,
+        building_id -> Integer,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
+    oil_deliveries (id) {
+        id -> Nullable<Integer>,
+        tank_id -> Integer,
+        delivery_date -> Date,
+        litres -> ExactDecimal,
+        total_price -> ExactDecimal,
+        invoice_reference -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
+    oil_tank_readings (id) {
+        id -> Nullable<Integer>,
+        tank_id -> Integer,
+        reading_date -> Date,
+        litres -> ExactDecimal,
+        reading_type -> Text,
+        notes -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
+    oil_tanks (id) {
+        id -> Nullable<Integer>,
+        name -> Text,
+        capacity_litres -> ExactDecimal,
+        cost_type_id -> Nullable<Integer>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
+    prepayment_schedules (id) {
+        id -> Nullable<Integer>,
+        tenant_id -> Integer,
+        monthly_amount -> ExactDecimal,
+        valid_from -> Date,
+        valid_to -> Nullable<Date>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
+    prepayments (id) {
+        id -> Nullable<Integer>,
+        tenant_id -> Integer,
+        billing_period_id -> Integer,
+        amount -> ExactDecimal,
+        received_date -> Date,
+        notes -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -95,14 +370,21 @@ diesel::table! {
         living_area_m2 -> Float,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        heated_area_m2 -> Nullable<Float>,
+        owner_occupied -> Bool,
+        owner_persons -> Nullable<Integer>,
+        building_id -> Integer,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExactDecimal;
+
     tariffs (id) {
         id -> Nullable<Integer>,
         cost_type_id -> Integer,
-        price_per_unit -> Float,
+        price_per_unit -> ExactDecimal,
         valid_from -> Date,
         valid_to -> Nullable<Date>,
         created_at -> Timestamp,
@@ -118,29 +400,67 @@ diesel::table! {
         property_unit_id -> Integer,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        move_in_date -> Nullable<Date>,
+        move_out_date -> Nullable<Date>,
     }
 }
 
-diesel::joinable!(billing_periods -> property_units (property_unit_id));
+diesel::joinable!(billing_jobs -> billing_periods (billing_period_id));
+diesel::joinable!(billing_periods -> buildings (building_id));
+diesel::joinable!(billing_statement_items -> billing_statements (billing_statement_id));
+diesel::joinable!(billing_statement_items -> cost_types (cost_type_id));
+diesel::joinable!(billing_statement_items -> tariffs (tariff_id));
 diesel::joinable!(billing_statements -> billing_periods (billing_period_id));
 diesel::joinable!(billing_statements -> tenants (tenant_id));
+diesel::joinable!(burner_calibrations -> buildings (building_id));
+diesel::joinable!(cost_transfers -> buildings (building_id));
 diesel::joinable!(cost_type_allocations -> allocation_methods (allocation_method_id));
 diesel::joinable!(cost_type_allocations -> cost_types (cost_type_id));
+diesel::joinable!(cost_types -> betrkv_categories (betrkv_category_id));
+diesel::joinable!(cost_types -> buildings (building_id));
+diesel::joinable!(degree_day_shares -> buildings (building_id));
 diesel::joinable!(fixed_costs -> cost_types (cost_type_id));
+diesel::joinable!(heating_system_fuels -> heating_systems (heating_system_id));
+diesel::joinable!(heating_systems -> buildings (building_id));
+diesel::joinable!(meter_cost_types -> cost_types (cost_type_id));
+diesel::joinable!(meter_cost_types -> meters (meter_id));
 diesel::joinable!(meter_readings -> meters (meter_id));
+diesel::joinable!(meters -> buildings (building_id));
 diesel::joinable!(meters -> property_units (property_unit_id));
+diesel::joinable!(oil_deliveries -> oil_tanks (tank_id));
+diesel::joinable!(oil_tank_readings -> oil_tanks (tank_id));
+diesel::joinable!(oil_tanks -> cost_types (cost_type_id));
+diesel::joinable!(prepayment_schedules -> tenants (tenant_id));
+diesel::joinable!(prepayments -> billing_periods (billing_period_id));
+diesel::joinable!(prepayments -> tenants (tenant_id));
+diesel::joinable!(property_units -> buildings (building_id));
 diesel::joinable!(tariffs -> cost_types (cost_type_id));
 diesel::joinable!(tenants -> property_units (property_unit_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     allocation_methods,
+    betrkv_categories,
+    billing_jobs,
     billing_periods,
+    billing_statement_items,
     billing_statements,
+    buildings,
+    burner_calibrations,
+    cost_transfers,
     cost_type_allocations,
     cost_types,
+    degree_day_shares,
     fixed_costs,
+    heating_system_fuels,
+    heating_systems,
+    meter_cost_types,
     meter_readings,
     meters,
+    oil_deliveries,
+    oil_tank_readings,
+    oil_tanks,
+    prepayment_schedules,
+    prepayments,
     property_units,
     tariffs,
     tenants,
@@ -0,0 +1,2516 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use log::{info, warn};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+use crate::models::billing::BillingPeriod;
+use crate::models::building::VacancyPersons;
+use crate::models::decimal;
+use crate::models::cost::{AllocationMethod, BetrkvCategory, CostTransfer, CostType, FixedCost, Tariff, TransferMethod};
+use crate::models::heating::HeatingSystem;
+use crate::models::meter::{
+    units_match, BindingUnits, Meter, MeterCostType, BURNER_HOURS_METER_TYPE,
+};
+use crate::models::property_unit::PropertyUnit;
+use crate::models::tenant::Tenant;
+use crate::schema::{
+    allocation_methods, betrkv_categories, buildings, cost_transfers, cost_type_allocations, cost_types,
+    fixed_costs, heating_systems, meter_cost_types, meters, property_units, tariffs, tenants,
+};
+use crate::services::co2::{self, Co2Content, Co2Split};
+use crate::services::fuel::{self, FuelError};
+use crate::services::heating::OilConsumption;
+use crate::services::hot_water::{self, HotWaterError, HotWaterSplit};
+use crate::services::interpolation::{self, DayWeighting, DegreeDays};
+use crate::services::meter_hierarchy::{MeterTree, ResidualConsumption};
+use crate::services::rounding::{distribute_cents, round_cents};
+use crate::services::{heating, oil_tank};
+
+// Errors that can occur while allocating the costs of a billing period
+#[derive(Debug, Error)]
+pub enum AllocationError {
+    #[error("database error: {0}")]
+    Database(#[from] diesel::result::Error),
+    #[error("invalid billing period: {0}")]
+    InvalidPeriod(String),
+}
+
+// Allocation keys, matching the names seeded into the allocation_methods table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AllocationKey {
+    LivingArea,
+    PersonCount,
+    Consumption,
+    EqualShare,
+    HeatedArea,
+}
+
+impl AllocationKey {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "LivingArea" => Some(AllocationKey::LivingArea),
+            "PersonCount" => Some(AllocationKey::PersonCount),
+            "Consumption" => Some(AllocationKey::Consumption),
+            "EqualShare" => Some(AllocationKey::EqualShare),
+            "HeatedArea" => Some(AllocationKey::HeatedArea),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AllocationKey::LivingArea => "LivingArea",
+            AllocationKey::PersonCount => "PersonCount",
+            AllocationKey::Consumption => "Consumption",
+            AllocationKey::EqualShare => "EqualShare",
+            AllocationKey::HeatedArea => "HeatedArea",
+        }
+    }
+
+    // Label used on the printed statement
+    pub fn label(&self) -> &'static str {
+        match self {
+            AllocationKey::LivingArea => "Wohnfläche (m²)",
+            AllocationKey::PersonCount => "Personen",
+            AllocationKey::Consumption => "Verbrauch",
+            AllocationKey::EqualShare => "Wohneinheiten",
+            AllocationKey::HeatedArea => "Beheizte Fläche (m²)",
+        }
+    }
+}
+
+// Part of a cost type split into consumption costs and base costs (HeizkostenV §7/§8)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CostPart {
+    Consumption, // Allocated by metered consumption
+    Base,        // Allocated by heated area
+}
+
+impl CostPart {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "consumption" => Some(CostPart::Consumption),
+            "base" => Some(CostPart::Base),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostPart::Consumption => "consumption",
+            CostPart::Base => "base",
+        }
+    }
+
+    // Label used on the printed statement
+    pub fn label(&self) -> &'static str {
+        match self {
+            CostPart::Consumption => "Verbrauchskosten",
+            CostPart::Base => "Grundkosten",
+        }
+    }
+}
+
+// Who carries a participant's share
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParticipantKind {
+    Tenant,
+    Owner,   // Days of an owner-occupied unit without tenancy (Eigenanteil)
+    Vacancy, // Days of a unit without tenancy, carried by the landlord
+}
+
+// A tenancy (or owner use, or vacancy) of a unit within the billing period, with its time-weighted key quantities
+#[derive(Debug, Clone, Serialize)]
+pub struct Participant {
+    pub kind: ParticipantKind,
+    pub tenant_id: Option<i32>,
+    pub property_unit_id: i32,
+    pub name: String,
+    pub number_of_persons: i32,
+    pub occupied_days: i64,  // Days of the period covered by the tenancy (or vacancy)
+    pub living_area_m2: Decimal, // Unit area weighted by the share of the unit-period
+    pub heated_area_m2: Decimal, // Heated unit area, weighted the same way
+    pub unit_share: Decimal,     // Share of the unit-period, split among concurrent tenants
+    pub person_share: Decimal,   // Persons weighted by occupied days
+}
+
+// One participant's part of a cost line
+#[derive(Debug, Clone, Serialize)]
+pub struct ParticipantShare {
+    pub kind: ParticipantKind,
+    pub tenant_id: Option<i32>,
+    pub property_unit_id: i32,
+    pub quantity: Decimal,
+    pub amount: Decimal,       // Rounded to cents
+    pub exact_amount: Decimal, // Before rounding
+    pub estimated: bool,       // Quantity based on estimated meter values
+}
+
+// Window of the billing period in which a single tariff applies
+#[derive(Debug, Clone, Serialize)]
+pub struct TariffSegment {
+    pub tariff_id: Option<i32>,
+    pub price_per_unit: Decimal,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+// Invoice of a cost type outside BetrKV § 2 within the billing period, carried by the landlord alone
+#[derive(Debug, Clone, Serialize)]
+pub struct LandlordCost {
+    pub cost_type_id: i32,
+    pub cost_type_name: String,
+    pub category_name: String,
+    pub apportionment: Apportionment,
+    pub amount: Decimal, // Apportioned amount rounded to cents
+}
+
+// Part of a fixed cost invoice falling into the billing period (Abgrenzung).
+// The invoice amount is apportioned by the days of its period inside the billing period.
+#[derive(Debug, Clone, Serialize)]
+pub struct Apportionment {
+    pub fixed_cost_id: Option<i32>,
+    pub invoice_amount: Decimal,
+    pub invoice_start: NaiveDate,
+    pub invoice_end: NaiveDate,
+    pub invoice_days: i64,
+    pub start_date: NaiveDate, // Overlap of the invoice period with the billing period
+    pub end_date: NaiveDate,
+    pub apportioned_days: i64,
+}
+
+impl Apportionment {
+    // Overlap of a fixed cost with the billing period, None if it lies outside
+    pub fn of(fixed_cost: &FixedCost, start_date: NaiveDate, end_date: NaiveDate) -> Option<Self> {
+        let overlap_start = fixed_cost.billing_period_start.max(start_date);
+        let overlap_end = fixed_cost.billing_period_end.min(end_date);
+        if overlap_end < overlap_start {
+            return None;
+        }
+
+        Some(Apportionment {
+            fixed_cost_id: fixed_cost.id,
+            invoice_amount: fixed_cost.amount,
+            invoice_start: fixed_cost.billing_period_start,
+            invoice_end: fixed_cost.billing_period_end,
+            invoice_days: (fixed_cost.billing_period_end - fixed_cost.billing_period_start).num_days() + 1,
+            start_date: overlap_start,
+            end_date: overlap_end,
+            apportioned_days: (overlap_end - overlap_start).num_days() + 1,
+        })
+    }
+
+    // Exact amount of the invoice for the apportioned days
+    pub fn amount(&self) -> Decimal {
+        self.invoice_amount * Decimal::from(self.apportioned_days) / Decimal::from(self.invoice_days)
+    }
+
+    pub fn is_partial(&self) -> bool {
+        self.apportioned_days < self.invoice_days
+    }
+}
+
+// Consumption of a meter (with its sub-meters) that went into a cost line
+#[derive(Debug, Clone, Serialize)]
+pub struct MeterUsage {
+    pub property_unit_id: Option<i32>, // Unit the consumption counts for, None for common meters
+    pub conversion_factor: Decimal,    // Cost type units per meter unit
+    pub consumption: ResidualConsumption,
+    pub counted: Decimal, // Residual in cost type units, negative residuals count as zero
+}
+
+// Costs moved from one cost type to another in a billing period
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedTransfer {
+    pub transfer_id: i32,
+    pub name: String,
+    pub source_cost_type_id: i32,
+    pub source_cost_type_name: String,
+    pub target_cost_type_id: i32,
+    pub target_cost_type_name: String,
+    pub method: TransferMethod,
+    pub percent: Option<Decimal>,
+    pub basis_cost_type_name: Option<String>,
+    pub basis_amount: Option<Decimal>, // Costs of the basis cost type in the period
+    pub meter_name: Option<String>,
+    pub meter_consumption: Option<Decimal>,
+    pub price_per_unit: Option<Decimal>, // Average over the tariff windows if taken from tariffs
+    pub amount: Decimal,                 // Exact amount moved
+}
+
+// Part of a cost line moved to another cost type by a transfer
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferPart {
+    pub transfer_id: i32,
+    pub name: String,
+    pub target_cost_type_name: String,
+    pub amount: Decimal, // Exact
+}
+
+// A building-level amount distributed across all participants with one allocation key
+#[derive(Debug, Clone, Serialize)]
+pub struct CostLine {
+    pub cost_type_id: i32,
+    pub cost_type_name: String,
+    pub total_amount: Decimal,       // Rounded to cents
+    pub exact_total_amount: Decimal, // Before rounding
+    pub allocation_key: AllocationKey,
+    pub fallback_from: Option<AllocationKey>, // Key without any quantity, replaced by EqualShare
+    pub total_quantity: Decimal,
+    pub tariff_segment: Option<TariffSegment>, // Set on consumption-based lines, one line per segment
+    pub cost_part: Option<CostPart>,           // Set on both lines of a split cost type
+    pub cost_part_percent: Option<Decimal>,
+    pub common_consumption: bool, // Cost of common meters, allocated with the cost type's key
+    pub apportionment: Option<Apportionment>, // Set on fixed cost lines, one line per invoice
+    pub priced_consumption: Option<Decimal>,  // Building consumption multiplied by the tariff
+    pub oil_consumption: Option<OilConsumption>, // Burner-hour derivation of the priced consumption
+    pub hot_water_split: Option<HotWaterSplit>,  // Set on lines split off the fuel costs of a heating system
+    pub degree_day_weighting: bool, // Tenancies and meter estimates weighted by degree days
+    pub co2_cost: Option<Decimal>,  // CO2 costs contained in the line before the landlord's part is taken off
+    pub co2_landlord_amount: Option<Decimal>, // Landlord's part of the CO2 costs (CO2KostAufG)
+    pub co2_split: Option<Co2Split>,
+    pub transfer_in: Option<AppliedTransfer>, // Set on lines allocating costs moved in from another cost type
+    pub transfers_out: Vec<TransferPart>,     // Parts of the line moved to other cost types
+    pub common_meters: Vec<MeterUsage>,       // Meters making up the total of a common consumption line
+    pub key_meters: Vec<MeterUsage>,          // Meters the consumption key quantities come from
+    pub shares: Vec<ParticipantShare>,
+}
+
+impl CostLine {
+    // A tenant's share of the line, None if the tenant has neither a quantity nor an amount in it,
+    // e.g. for a tariff window after moving out
+    pub fn share_for(&self, tenant_id: i32) -> Option<&ParticipantShare> {
+        self.shares
+            .iter()
+            .find(|share| share.tenant_id == Some(tenant_id))
+            .filter(|share| !(share.quantity.is_zero() && share.amount.is_zero()))
+    }
+
+    // A participant's part of the line's CO2 costs and of the landlord's part taken off them
+    pub fn co2_for(&self, share: &ParticipantShare) -> Option<(Decimal, Decimal)> {
+        let co2_cost = self.co2_cost?;
+        if self.exact_total_amount.is_zero() {
+            return Some((Decimal::ZERO, Decimal::ZERO));
+        }
+        let part = share.exact_amount / self.exact_total_amount;
+
+        Some((co2_cost * part, self.co2_landlord_amount.unwrap_or_default() * part))
+    }
+}
+
+// Result of allocating all cost types of a billing period
+#[derive(Debug, Clone, Serialize)]
+pub struct BillingAllocation {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub period_days: i64,
+    pub degree_days: DegreeDays, // Table used for degree-day weighted cost types
+    pub participants: Vec<Participant>,
+    pub lines: Vec<CostLine>,
+    pub co2_splits: Vec<Co2Split>, // Landlord's part of the CO2 costs per fuel cost type
+    pub transfers: Vec<AppliedTransfer>,
+    pub landlord_costs: Vec<LandlordCost>, // Non-allocable invoices, not part of any cost line
+    pub vacancy_persons: VacancyPersons, // Persons a vacant unit counts with for the PersonCount key
+    pub warnings: Vec<String>, // Inconsistent input giving wrong statements, e.g. missing readings or tariffs
+    pub notes: Vec<String>,    // Estimates and fallbacks the statements are based on, e.g. extrapolated meter values
+}
+
+// Messages collected while allocating a billing period
+#[derive(Debug, Default)]
+struct Findings {
+    warnings: Vec<String>,
+    notes: Vec<String>,
+}
+
+impl Findings {
+    fn warn(&mut self, message: String) {
+        warn!("{}", message);
+        self.warnings.push(message);
+    }
+
+    fn note(&mut self, message: String) {
+        info!("{}", message);
+        self.notes.push(message);
+    }
+}
+
+// Allocate every cost type of the billing period's building across its property units and tenants.
+// Time-based keys are prorated by occupied days, weighted by degree days for heating cost types;
+// days without a tenancy go to vacancy.
+// Meter values at the period and tariff boundaries are interpolated between readings.
+// Amounts are rounded to cents at the end; the shares of each cost line always add up to the
+// line's building total, and the lines allocating one invoice to the invoiced amount.
+pub fn allocate_billing_period(
+    conn: &mut SqliteConnection,
+    billing_period: &BillingPeriod,
+) -> Result<BillingAllocation, AllocationError> {
+    let (start_date, end_date) = parse_period(billing_period)?;
+
+    // Everything is computed for the building the period belongs to
+    let building_id = billing_period.building_id;
+    let units = property_units::table
+        .filter(property_units::building_id.eq(building_id))
+        .load::<PropertyUnit>(conn)?;
+    let unit_ids: Vec<i32> = units.iter().filter_map(|unit| unit.id).collect();
+    let all_tenants = tenants::table
+        .filter(tenants::property_unit_id.eq_any(&unit_ids))
+        .select(Tenant::as_select())
+        .load::<Tenant>(conn)?;
+    let vacancy_persons = VacancyPersons::from(
+        buildings::table
+            .filter(buildings::id.eq(building_id))
+            .select(buildings::vacancy_persons)
+            .first::<String>(conn)?,
+    );
+    let period_days = (end_date - start_date).num_days() + 1;
+    let participants = build_participants(
+        &units,
+        &all_tenants,
+        start_date,
+        end_date,
+        &DayWeighting::Calendar,
+        vacancy_persons,
+    );
+    let degree_days = interpolation::load_degree_days(conn, building_id)?;
+
+    // Readings of all meters counting for a unit, used to determine consumption at the
+    // period boundaries. Main meters count with their residual after the sub-meters.
+    let mut unit_meters = Vec::new();
+    for meter in meters::table
+        .filter(meters::building_id.eq(building_id))
+        .filter(
+            meters::property_unit_id
+                .is_not_null()
+                .or(meters::residual_property_unit_id.is_not_null()),
+        )
+        .filter(meters::meter_type.ne(BURNER_HOURS_METER_TYPE))
+        .select(Meter::as_select())
+        .load::<Meter>(conn)?
+    {
+        let Some(unit_id) = meter.property_unit_id.or(meter.residual_property_unit_id) else {
+            continue;
+        };
+        unit_meters.push(UnitMeter {
+            property_unit_id: unit_id,
+            tree: MeterTree::load(conn, &meter, &degree_days)?,
+        });
+    }
+
+    // Common meters whose consumption is not passed to a unit, e.g. Allgemeinstrom
+    let mut common_meters = Vec::new();
+    for meter in meters::table
+        .filter(meters::building_id.eq(building_id))
+        .filter(meters::assignment_type.eq("common"))
+        .filter(meters::residual_property_unit_id.is_null())
+        .filter(meters::meter_type.ne(BURNER_HOURS_METER_TYPE))
+        .select(Meter::as_select())
+        .load::<Meter>(conn)?
+    {
+        common_meters.push(MeterTree::load(conn, &meter, &degree_days)?);
+    }
+
+    let mut lines = Vec::new();
+    let mut co2_splits = Vec::new();
+    let mut landlord_costs = Vec::new();
+    let mut findings = Findings::default();
+
+    // Bindings made before a meter or cost type changed its unit bill the wrong quantities
+    for binding in load_binding_units(conn, building_id)? {
+        if let Some(message) = binding.mismatch() {
+            findings.warn(message);
+        }
+    }
+    let all_cost_types = cost_types::table
+        .filter(cost_types::building_id.eq(building_id))
+        .load::<CostType>(conn)?;
+    let categories: HashMap<i32, BetrkvCategory> = betrkv_categories::table
+        .load::<BetrkvCategory>(conn)?
+        .into_iter()
+        .filter_map(|category| category.id.map(|category_id| (category_id, category)))
+        .collect();
+    let category_of = |cost_type: &CostType| {
+        cost_type
+            .betrkv_category_id
+            .and_then(|category_id| categories.get(&category_id))
+    };
+
+    // Fuel costs of combined heating systems are split into heating and hot water first
+    let systems = heating_systems::table
+        .filter(heating_systems::building_id.eq(building_id))
+        .load::<HeatingSystem>(conn)?;
+    let context = BuildingContext {
+        units: &units,
+        all_tenants: &all_tenants,
+        participants: &participants,
+        unit_meters: &unit_meters,
+        common_meters: &common_meters,
+        degree_days,
+        start_date,
+        end_date,
+        vacancy_persons,
+    };
+
+    for cost_type in &all_cost_types {
+        let Some(cost_type_id) = cost_type.id else {
+            continue;
+        };
+
+        // Repairs, management and other costs outside BetrKV § 2 stay with the landlord
+        if let Some(category) = category_of(cost_type).filter(|category| !category.allocable) {
+            info!(
+                "Cost type '{}' is not allocable ({}), excluded from tenant statements",
+                cost_type.name, category.name
+            );
+            for fixed_cost in fixed_costs::table
+                .filter(fixed_costs::cost_type_id.eq(cost_type_id))
+                .order_by(fixed_costs::billing_period_start.asc())
+                .load::<FixedCost>(conn)?
+            {
+                let Some(apportionment) = Apportionment::of(&fixed_cost, start_date, end_date) else {
+                    continue;
+                };
+                landlord_costs.push(LandlordCost {
+                    cost_type_id,
+                    cost_type_name: cost_type.name.clone(),
+                    category_name: category.name.clone(),
+                    amount: round_cents(apportionment.amount()),
+                    apportionment,
+                });
+            }
+            continue;
+        }
+
+        // Amounts are allocated under the cost type itself unless it is split
+        let own_target = allocation_target(conn, cost_type, Decimal::ONE, None)?;
+        let bindings = own_target.bindings.clone();
+        let targets = match systems
+            .iter()
+            .find(|system| system.fuel_cost_type_id == cost_type_id)
+        {
+            Some(system) => context.hot_water_targets(conn, system, cost_type, &all_cost_types, &mut findings)?,
+            None => None,
+        }
+        .unwrap_or_else(|| vec![own_target]);
+
+        // CO2 contained in the fuel costs, collected from every amount allocated below. The cost
+        // type's own factor takes precedence over the fuel of the heating system burning it.
+        let system_co2_factor = match systems
+            .iter()
+            .find(|system| system.fuel_cost_type_id == cost_type_id)
+        {
+            Some(system) => match fuel::fuel_for_period(conn, system, start_date, end_date) {
+                Ok(period_fuel) => {
+                    findings.notes.extend(period_fuel.warnings.iter().cloned());
+                    period_fuel.co2_per_fuel_unit()
+                }
+                Err(FuelError::Database(e)) => return Err(e.into()),
+                Err(FuelError::Undetermined(_)) => None, // Reported by the hot water split
+            },
+            None => None,
+        };
+        let co2_factor = cost_type
+            .co2_emission_factor
+            .map(decimal::from_f32)
+            .or(system_co2_factor)
+            .filter(|factor| *factor > Decimal::ZERO && co2::split_applies(start_date));
+        let mut co2_content = Co2Content::default();
+        let first_line = lines.len();
+
+        if cost_type.is_consumption_based {
+            // Consumption of all units within each tariff window multiplied by its price
+            let cost_tariffs = tariffs::table
+                .filter(tariffs::cost_type_id.eq(cost_type_id))
+                .load::<Tariff>(conn)?;
+            let segments = tariff_segments(&cost_tariffs, start_date, end_date);
+
+            let covered_days: i64 = segments
+                .iter()
+                .map(|segment| (segment.end_date - segment.start_date).num_days() + 1)
+                .sum();
+            if covered_days < period_days {
+                let message = format!(
+                    "Tariffs of cost type '{}' cover {} of {} days in billing period {} - {}",
+                    cost_type.name, covered_days, period_days, start_date, end_date
+                );
+                findings.warn(message);
+            }
+            if bindings.is_empty() && !cost_type.uses_burner_hours && !segments.is_empty() {
+                let message = format!(
+                    "Cost type '{}' has tariffs but no meters bound to it, its consumption costs are 0",
+                    cost_type.name
+                );
+                findings.warn(message);
+            }
+
+            for segment in segments {
+                let segment_consumption = consumption_by_unit(
+                    &unit_meters,
+                    &bindings,
+                    segment.start_date,
+                    segment.end_date,
+                    cost_type.degree_day_weighting,
+                    &mut findings,
+                );
+
+                // Oil burned according to the burner-hour meters, or the sum of all units
+                let oil_consumption = if cost_type.uses_burner_hours {
+                    let oil = heating::oil_consumption(conn, building_id, segment.start_date, segment.end_date)?;
+                    findings.warnings.extend(oil.warnings.iter().cloned());
+                    Some(oil)
+                } else {
+                    None
+                };
+                let building_consumption = match &oil_consumption {
+                    Some(oil) => oil.litres,
+                    None => segment_consumption.values.values().sum::<Decimal>(),
+                };
+                let total_amount = building_consumption * segment.price_per_unit;
+
+                // Common meters become a building cost allocated with the cost type's key
+                let (common_total, common_usages) = if cost_type.uses_burner_hours {
+                    (Decimal::ZERO, Vec::new())
+                } else {
+                    common_consumption(
+                        &common_meters,
+                        &bindings,
+                        segment.start_date,
+                        segment.end_date,
+                        cost_type.degree_day_weighting,
+                        &mut findings,
+                    )
+                };
+                let common_amount = common_total * segment.price_per_unit;
+
+                if !total_amount.is_zero() {
+                    let content = co2_factor.map(|factor| {
+                        co2::fuel_co2(building_consumption, factor, segment.start_date, segment.end_date)
+                    });
+                    let amount_start = lines.len();
+                    lines.extend(
+                        context
+                            .target_lines(
+                                &targets,
+                                total_amount,
+                                Some(segment.clone()),
+                                (segment.start_date, segment.end_date),
+                                &mut findings,
+                            )
+                            .into_iter()
+                            .map(|line| CostLine {
+                                priced_consumption: Some(building_consumption),
+                                oil_consumption: oil_consumption.clone(),
+                                ..line
+                            }),
+                    );
+                    mark_co2(&mut lines[amount_start..], total_amount, content, &mut co2_content);
+                }
+                if !common_amount.is_zero() {
+                    let content = co2_factor
+                        .map(|factor| co2::fuel_co2(common_total, factor, segment.start_date, segment.end_date));
+                    let amount_start = lines.len();
+                    lines.extend(
+                        context
+                            .target_lines(
+                                &targets,
+                                common_amount,
+                                Some(segment.clone()),
+                                (segment.start_date, segment.end_date),
+                                &mut findings,
+                            )
+                            .into_iter()
+                            .map(|line| CostLine {
+                                common_consumption: true,
+                                priced_consumption: Some(common_total),
+                                common_meters: common_usages.clone(),
+                                ..line
+                            }),
+                    );
+                    mark_co2(&mut lines[amount_start..], common_amount, content, &mut co2_content);
+                }
+            }
+        } else {
+            // Each invoice counts with the days of its period inside the billing period and is
+            // allocated among the participants of those days
+            let cost_fixed_costs = fixed_costs::table
+                .filter(fixed_costs::cost_type_id.eq(cost_type_id))
+                .order_by(fixed_costs::billing_period_start.asc())
+                .load::<FixedCost>(conn)?;
+
+            let mut invoiced = false;
+            for fixed_cost in &cost_fixed_costs {
+                let Some(apportionment) = Apportionment::of(fixed_cost, start_date, end_date) else {
+                    continue;
+                };
+                if apportionment.is_partial() {
+                    info!(
+                        "Invoice {} - {} of cost type '{}' apportioned with {} of {} days",
+                        apportionment.invoice_start,
+                        apportionment.invoice_end,
+                        cost_type.name,
+                        apportionment.apportioned_days,
+                        apportionment.invoice_days
+                    );
+                }
+                let total_amount = apportionment.amount();
+                if total_amount.is_zero() {
+                    continue;
+                }
+                invoiced = true;
+
+                let content = match co2_factor {
+                    Some(factor) => {
+                        let content = co2::invoice_co2(fixed_cost, factor, &apportionment);
+                        if content.is_none() {
+                            let message = format!(
+                                "Invoice {} - {} of cost type '{}' has no fuel quantity, its CO2 costs are not split",
+                                apportionment.invoice_start, apportionment.invoice_end, cost_type.name
+                            );
+                            findings.warn(message);
+                        }
+                        content
+                    }
+                    None => None,
+                };
+                let amount_start = lines.len();
+                lines.extend(
+                    context
+                        .target_lines(
+                            &targets,
+                            total_amount,
+                            None,
+                            (apportionment.start_date, apportionment.end_date),
+                            &mut findings,
+                        )
+                        .into_iter()
+                        .map(|line| CostLine {
+                            apportionment: Some(apportionment.clone()),
+                            ..line
+                        }),
+                );
+                mark_co2(&mut lines[amount_start..], total_amount, content, &mut co2_content);
+            }
+
+            // Fuel consumed from the oil tanks assigned to the cost type. Invoices of the same cost
+            // type already bill the oil, so the tanks are only valued without any.
+            let fuel_costs =
+                oil_tank::fuel_costs_for_cost_type(conn, cost_type_id, start_date, end_date, &mut findings.warnings)?;
+            let fuel_amount: Decimal = fuel_costs.iter().map(|fuel_cost| fuel_cost.amount).sum();
+            if invoiced && !fuel_amount.is_zero() {
+                let message = format!(
+                    "Cost type '{}' has invoices and oil tanks in the period, the oil of the tanks ({:.2}) is left out to not bill it twice",
+                    cost_type.name,
+                    fuel_amount.round_dp(2)
+                );
+                findings.warn(message);
+            } else if !fuel_amount.is_zero() {
+                let content = co2_factor.map(|factor| {
+                    let mut content = Co2Content::default();
+                    for fuel_cost in &fuel_costs {
+                        content.add(co2::oil_co2(fuel_cost, factor));
+                    }
+                    content
+                });
+                let amount_start = lines.len();
+                lines.extend(context.target_lines(
+                    &targets,
+                    fuel_amount,
+                    None,
+                    (start_date, end_date),
+                    &mut findings,
+                ));
+                mark_co2(&mut lines[amount_start..], fuel_amount, content, &mut co2_content);
+            }
+        }
+
+        // The landlord carries a part of the CO2 costs depending on the building's emissions
+        if co2_content.co2_cost > Decimal::ZERO {
+            let heated_area: Decimal = units.iter().map(|unit| decimal::from_f32(unit.heated_area())).sum();
+            if heated_area > Decimal::ZERO {
+                let split = Co2Split::of(cost_type_id, &cost_type.name, co2_content, heated_area, (start_date, end_date));
+                info!(
+                    "CO2 costs of '{}': {:.1} kg CO2/m²/a, tier {}, landlord carries {} %",
+                    cost_type.name, split.specific_emissions, split.tier, split.landlord_percent
+                );
+                apply_co2_split(&mut lines[first_line..], &split);
+                co2_splits.push(split);
+            } else {
+                let message = format!(
+                    "Building has no heated area, CO2 costs of cost type '{}' are not split",
+                    cost_type.name
+                );
+                findings.warn(message);
+            }
+        }
+    }
+
+    // Costs moved between cost types, e.g. the operating electricity of the heating
+    let mut transfers = Vec::new();
+    for transfer in cost_transfers::table
+        .filter(cost_transfers::building_id.eq(building_id))
+        .order_by(cost_transfers::id.asc())
+        .select(CostTransfer::as_select())
+        .load::<CostTransfer>(conn)?
+    {
+        if let Some(applied) = context.apply_transfer(
+            conn,
+            &transfer,
+            &all_cost_types,
+            &mut lines,
+            &mut co2_splits,
+            &mut findings,
+        )? {
+            info!(
+                "Cost transfer '{}': {:.2} moved from '{}' to '{}'",
+                applied.name, applied.amount, applied.source_cost_type_name, applied.target_cost_type_name
+            );
+            transfers.push(applied);
+        }
+    }
+
+    round_to_cents(&mut lines);
+
+    // Keys without any quantity have been replaced by EqualShare in build_cost_line
+    for line in &lines {
+        if let Some(fallback_from) = line.fallback_from {
+            findings.notes.push(format!(
+                "Allocation key {} has no quantity for cost type '{}', allocated by EqualShare",
+                fallback_from.as_str(),
+                line.cost_type_name
+            ));
+        }
+    }
+    let Findings { mut warnings, mut notes } = findings;
+    let mut seen = HashSet::new();
+    warnings.retain(|message| seen.insert(message.clone()));
+    notes.retain(|message| seen.insert(message.clone()));
+
+    // Statement order follows BetrKV § 2; cost types without a category come last
+    let position_of: HashMap<i32, i32> = all_cost_types
+        .iter()
+        .filter_map(|cost_type| {
+            let category = category_of(cost_type)?;
+            Some((cost_type.id?, category.position))
+        })
+        .collect();
+    lines.sort_by_key(|line| {
+        (
+            position_of.get(&line.cost_type_id).copied().unwrap_or(i32::MAX),
+            line.cost_type_name.clone(),
+            line.cost_type_id,
+        )
+    });
+
+    Ok(BillingAllocation {
+        start_date,
+        end_date,
+        period_days,
+        degree_days,
+        participants,
+        lines,
+        co2_splits,
+        transfers,
+        landlord_costs,
+        vacancy_persons,
+        warnings,
+        notes,
+    })
+}
+
+// Record the CO2 costs contained in an amount on the lines it was allocated to,
+// in proportion to each line's part of the amount
+fn mark_co2(lines: &mut [CostLine], amount: Decimal, content: Option<Co2Content>, total: &mut Co2Content) {
+    let Some(content) = content else {
+        return;
+    };
+    total.add(content);
+    if amount.is_zero() {
+        return;
+    }
+
+    for line in lines {
+        let line_co2 = content.co2_cost * line.total_amount / amount;
+        line.co2_cost = Some(line.co2_cost.unwrap_or_default() + line_co2);
+    }
+}
+
+// Take the landlord's part of the CO2 costs off the lines containing them. All shares of a line
+// are reduced in proportion, so every participant keeps the tenant part of its CO2 costs.
+fn apply_co2_split(lines: &mut [CostLine], split: &Co2Split) {
+    for line in lines {
+        let Some(co2_cost) = line.co2_cost else {
+            continue;
+        };
+        if line.total_amount.is_zero() {
+            continue;
+        }
+
+        let landlord_amount = co2_cost * split.landlord_percent / Decimal::ONE_HUNDRED;
+        let remaining = (line.total_amount - landlord_amount) / line.total_amount;
+        line.total_amount -= landlord_amount;
+        line.exact_total_amount = line.total_amount;
+        for share in &mut line.shares {
+            share.amount *= remaining;
+            share.exact_amount = share.amount;
+        }
+        line.co2_landlord_amount = Some(landlord_amount);
+        line.co2_split = Some(split.clone());
+    }
+}
+
+// Distribute a total proportionally to the given quantities.
+// The last share absorbs the residue of inexact divisions so the shares sum up to the total.
+pub fn distribute(total: Decimal, quantities: &[Decimal]) -> Vec<Decimal> {
+    let total_quantity: Decimal = quantities.iter().sum();
+    if quantities.is_empty() || total_quantity <= Decimal::ZERO {
+        return vec![Decimal::ZERO; quantities.len()];
+    }
+
+    let mut shares: Vec<Decimal> = quantities
+        .iter()
+        .map(|quantity| total * quantity / total_quantity)
+        .collect();
+
+    // Assign the residue to the last participant with a non-zero quantity
+    if let Some(last) = quantities.iter().rposition(|quantity| *quantity > Decimal::ZERO) {
+        let others: Decimal = shares
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != last)
+            .map(|(_, share)| share)
+            .sum();
+        shares[last] = total - others;
+    }
+
+    shares
+}
+
+// Round the exact amounts of all lines to cents. The lines allocating the same amount, e.g. the
+// heating and hot water lines split off one fuel invoice, are rounded so they add up to that
+// amount, then each line's shares to the line total.
+fn round_to_cents(lines: &mut [CostLine]) {
+    // Lines of the same invoice (or of the invoice-less costs) of the same source cost type
+    let source_of = |line: &CostLine| {
+        (
+            line.hot_water_split.as_ref().map_or(line.cost_type_id, |split| split.fuel_cost_type_id),
+            line.apportionment.as_ref().and_then(|apportionment| apportionment.fixed_cost_id),
+        )
+    };
+    let mut sources: Vec<(i32, Option<i32>)> = lines.iter().map(source_of).collect();
+    sources.sort_unstable();
+    sources.dedup();
+
+    for source in sources {
+        let indices: Vec<usize> = (0..lines.len())
+            .filter(|index| source_of(&lines[*index]) == source)
+            .collect();
+        let exact_totals: Vec<Decimal> = indices.iter().map(|index| lines[*index].total_amount).collect();
+        let rounded_totals = distribute_cents(exact_totals.iter().sum(), &exact_totals);
+
+        for (index, total) in indices.into_iter().zip(rounded_totals) {
+            let line = &mut lines[index];
+            let exact_shares: Vec<Decimal> = line.shares.iter().map(|share| share.amount).collect();
+            for (share, amount) in line.shares.iter_mut().zip(distribute_cents(total, &exact_shares)) {
+                share.amount = amount;
+            }
+            line.total_amount = total;
+        }
+    }
+}
+
+pub fn parse_period(billing_period: &BillingPeriod) -> Result<(NaiveDate, NaiveDate), AllocationError> {
+    let start = NaiveDate::parse_from_str(&billing_period.start_date, "%Y-%m-%d")
+        .map_err(|_| AllocationError::InvalidPeriod(format!("invalid start date {}", billing_period.start_date)))?;
+    let end = NaiveDate::parse_from_str(&billing_period.end_date, "%Y-%m-%d")
+        .map_err(|_| AllocationError::InvalidPeriod(format!("invalid end date {}", billing_period.end_date)))?;
+
+    if end < start {
+        return Err(AllocationError::InvalidPeriod(format!(
+            "end date {} is before start date {}",
+            end, start
+        )));
+    }
+
+    Ok((start, end))
+}
+
+// Walk every day of the period per unit. A day is split evenly among the tenants
+// occupying the unit on that day; days without any tenancy are assigned to vacancy,
+// counting with the building's vacancy persons.
+// Days count with their weight, e.g. their degree-day share for heating costs.
+fn build_participants(
+    units: &[PropertyUnit],
+    all_tenants: &[Tenant],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    weighting: &DayWeighting,
+    vacancy_persons: VacancyPersons,
+) -> Vec<Participant> {
+    let period_end = end_date.succ_opt().unwrap_or(end_date);
+    // A window without any heating demand in a custom table is split by calendar days
+    let weighting = if weighting.weight_between(start_date, period_end) > Decimal::ZERO {
+        weighting
+    } else {
+        &DayWeighting::Calendar
+    };
+    let period_weight = weighting.weight_between(start_date, period_end);
+    let mut participants = Vec::new();
+
+    for unit in units {
+        let Some(unit_id) = unit.id else {
+            continue;
+        };
+        let unit_tenants: Vec<&Tenant> = all_tenants
+            .iter()
+            .filter(|tenant| tenant.property_unit_id == unit_id && tenant.id.is_some())
+            .collect();
+
+        let mut weighted_days = vec![Decimal::ZERO; unit_tenants.len()];
+        let mut occupied_weight = vec![Decimal::ZERO; unit_tenants.len()];
+        let mut occupied_days = vec![0i64; unit_tenants.len()];
+        let mut vacant_weight = Decimal::ZERO;
+        let mut vacant_person_weight = Decimal::ZERO;
+        let mut vacant_persons = 1;
+        let mut vacant_days = 0i64;
+
+        for date in start_date.iter_days().take_while(|date| *date <= end_date) {
+            let active: Vec<usize> = unit_tenants
+                .iter()
+                .enumerate()
+                .filter(|(_, tenant)| tenant.occupies(date))
+                .map(|(index, _)| index)
+                .collect();
+
+            let day_weight = weighting.day_weight(date);
+            if active.is_empty() {
+                vacant_persons = match vacancy_persons {
+                    VacancyPersons::None => 0,
+                    VacancyPersons::One => 1,
+                    VacancyPersons::PreviousTenant => unit_tenants
+                        .iter()
+                        .filter(|tenant| tenant.move_out_date.is_some_and(|move_out| move_out < date))
+                        .max_by_key(|tenant| tenant.move_out_date)
+                        .map_or(1, |tenant| tenant.number_of_persons),
+                };
+                vacant_weight += day_weight;
+                vacant_person_weight += Decimal::from(vacant_persons) * day_weight;
+                vacant_days += 1;
+            }
+            for index in &active {
+                weighted_days[*index] += day_weight / Decimal::from(active.len());
+                occupied_weight[*index] += day_weight;
+                occupied_days[*index] += 1;
+            }
+        }
+
+        for (index, tenant) in unit_tenants.iter().enumerate() {
+            if occupied_days[index] == 0 {
+                continue;
+            }
+            let unit_share = weighted_days[index] / period_weight;
+            participants.push(Participant {
+                kind: ParticipantKind::Tenant,
+                tenant_id: tenant.id,
+                property_unit_id: unit_id,
+                name: tenant.name.clone(),
+                number_of_persons: tenant.number_of_persons,
+                occupied_days: occupied_days[index],
+                living_area_m2: decimal::from_f32(unit.living_area_m2) * unit_share,
+                heated_area_m2: decimal::from_f32(unit.heated_area()) * unit_share,
+                unit_share,
+                person_share: Decimal::from(tenant.number_of_persons) * occupied_weight[index] / period_weight,
+            });
+        }
+
+        // Days without tenancy are the owner's own use in owner-occupied units, vacancy otherwise.
+        // Vacancy shows the persons of its last day; its person share follows them day by day.
+        if vacant_days > 0 {
+            let unit_share = vacant_weight / period_weight;
+            let (kind, name, persons, person_weight) = if unit.owner_occupied {
+                let persons = unit.owner_persons();
+                (
+                    ParticipantKind::Owner,
+                    format!("Eigennutzung {}", unit.name),
+                    persons,
+                    Decimal::from(persons) * vacant_weight,
+                )
+            } else {
+                (ParticipantKind::Vacancy, format!("Leerstand {}", unit.name), vacant_persons, vacant_person_weight)
+            };
+            participants.push(Participant {
+                kind,
+                tenant_id: None,
+                property_unit_id: unit_id,
+                name,
+                number_of_persons: persons,
+                occupied_days: vacant_days,
+                living_area_m2: decimal::from_f32(unit.living_area_m2) * unit_share,
+                heated_area_m2: decimal::from_f32(unit.heated_area()) * unit_share,
+                unit_share,
+                person_share: person_weight / period_weight,
+            });
+        }
+    }
+
+    participants
+}
+
+// Participants of a part of the billing period, aligned with the participants of the whole period.
+// Participants without any day in the part are kept with zero quantities.
+fn participants_within(
+    units: &[PropertyUnit],
+    all_tenants: &[Tenant],
+    participants: &[Participant],
+    from: NaiveDate,
+    to: NaiveDate,
+    weighting: &DayWeighting,
+    vacancy_persons: VacancyPersons,
+) -> Vec<Participant> {
+    let within = build_participants(units, all_tenants, from, to, weighting, vacancy_persons);
+
+    participants
+        .iter()
+        .map(|participant| {
+            within
+                .iter()
+                .find(|p| {
+                    p.kind == participant.kind
+                        && p.tenant_id == participant.tenant_id
+                        && p.property_unit_id == participant.property_unit_id
+                })
+                .cloned()
+                .unwrap_or_else(|| Participant {
+                    occupied_days: 0,
+                    living_area_m2: Decimal::ZERO,
+                    heated_area_m2: Decimal::ZERO,
+                    unit_share: Decimal::ZERO,
+                    person_share: Decimal::ZERO,
+                    ..participant.clone()
+                })
+        })
+        .collect()
+}
+
+// Split the billing period into windows with a single applicable tariff.
+// Where tariffs overlap the most recent one applies; days without any tariff are left out.
+fn tariff_segments(cost_tariffs: &[Tariff], start_date: NaiveDate, end_date: NaiveDate) -> Vec<TariffSegment> {
+    let mut segments: Vec<TariffSegment> = Vec::new();
+
+    for date in start_date.iter_days().take_while(|date| *date <= end_date) {
+        let Some(tariff) = cost_tariffs
+            .iter()
+            .filter(|tariff| {
+                tariff.valid_from <= date && tariff.valid_to.is_none_or(|valid_to| valid_to >= date)
+            })
+            .max_by_key(|tariff| tariff.valid_from)
+        else {
+            continue;
+        };
+
+        match segments.last_mut() {
+            Some(segment)
+                if segment.tariff_id == tariff.id && segment.end_date.succ_opt() == Some(date) =>
+            {
+                segment.end_date = date;
+            }
+            _ => segments.push(TariffSegment {
+                tariff_id: tariff.id,
+                price_per_unit: tariff.price_per_unit,
+                start_date: date,
+                end_date: date,
+            }),
+        }
+    }
+
+    segments
+}
+
+// Resolve the allocation key assigned to a cost type.
+// Without an assignment, consumption-based costs follow consumption and all others living area.
+fn allocation_key_for_cost_type(
+    conn: &mut SqliteConnection,
+    cost_type: &CostType,
+) -> Result<AllocationKey, AllocationError> {
+    let methods = cost_type_allocations::table
+        .filter(cost_type_allocations::cost_type_id.eq(cost_type.id.unwrap_or(0)))
+        .inner_join(allocation_methods::table)
+        .order_by(cost_type_allocations::id.asc())
+        .select(allocation_methods::all_columns)
+        .load::<AllocationMethod>(conn)?;
+
+    if methods.len() > 1 {
+        warn!(
+            "Cost type '{}' has {} allocation methods assigned, using '{}'",
+            cost_type.name,
+            methods.len(),
+            methods[0].name
+        );
+    }
+
+    let assigned = methods
+        .iter()
+        .find_map(|method| AllocationKey::from_name(&method.name));
+
+    Ok(assigned.unwrap_or(if cost_type.is_consumption_based {
+        AllocationKey::Consumption
+    } else {
+        AllocationKey::LivingArea
+    }))
+}
+
+// Cost lines of one building total. Cost types with a consumption share are split into a
+// consumption part and a base part allocated by heated area (HeizkostenV §7/§8).
+fn cost_lines(
+    cost_type: &CostType,
+    total_amount: Decimal,
+    key: AllocationKey,
+    tariff_segment: Option<TariffSegment>,
+    participants: &[Participant],
+    consumption: &ParticipantConsumption,
+) -> Vec<CostLine> {
+    let quantities = |key: AllocationKey| -> Vec<Decimal> {
+        participants
+            .iter()
+            .zip(&consumption.values)
+            .map(|(p, value)| key_quantity(key, p, *value))
+            .collect()
+    };
+
+    let Some(consumption_percent) = cost_type.consumption_share_percent else {
+        return vec![build_cost_line(
+            cost_type,
+            total_amount,
+            key,
+            tariff_segment,
+            participants,
+            quantities(key),
+            consumption,
+        )];
+    };
+
+    let consumption_percent = decimal::from_f32(consumption_percent);
+    let consumption_amount = total_amount * consumption_percent / Decimal::ONE_HUNDRED;
+    [
+        (CostPart::Consumption, AllocationKey::Consumption, consumption_amount, consumption_percent),
+        (CostPart::Base, AllocationKey::HeatedArea, total_amount - consumption_amount, Decimal::ONE_HUNDRED - consumption_percent),
+    ]
+    .into_iter()
+    .filter(|(_, _, amount, _)| !amount.is_zero())
+    .map(|(part, part_key, amount, percent)| {
+        let mut line = build_cost_line(
+            cost_type,
+            amount,
+            part_key,
+            tariff_segment.clone(),
+            participants,
+            quantities(part_key),
+            consumption,
+        );
+        line.cost_part = Some(part);
+        line.cost_part_percent = Some(percent);
+        line
+    })
+    .collect()
+}
+
+fn key_quantity(key: AllocationKey, participant: &Participant, consumption: Decimal) -> Decimal {
+    match key {
+        AllocationKey::LivingArea => participant.living_area_m2,
+        AllocationKey::PersonCount => participant.person_share,
+        AllocationKey::Consumption => consumption,
+        AllocationKey::EqualShare => participant.unit_share,
+        AllocationKey::HeatedArea => participant.heated_area_m2,
+    }
+}
+
+fn build_cost_line(
+    cost_type: &CostType,
+    total_amount: Decimal,
+    key: AllocationKey,
+    tariff_segment: Option<TariffSegment>,
+    participants: &[Participant],
+    quantities: Vec<Decimal>,
+    consumption: &ParticipantConsumption,
+) -> CostLine {
+    let mut key = key;
+    let mut fallback_from = None;
+    let mut quantities = quantities;
+    let mut total_quantity: Decimal = quantities.iter().sum();
+
+    // A key without any quantity (e.g. no readings) would leave the cost unallocated
+    if total_quantity <= Decimal::ZERO && !participants.is_empty() {
+        warn!(
+            "Allocation key {} has no quantity for cost type '{}', falling back to EqualShare",
+            key.as_str(),
+            cost_type.name
+        );
+        fallback_from = Some(key);
+        key = AllocationKey::EqualShare;
+        quantities = participants.iter().map(|p| p.unit_share).collect();
+        total_quantity = quantities.iter().sum();
+    }
+
+    let amounts = distribute(total_amount, &quantities);
+    let shares = participants
+        .iter()
+        .zip(quantities.iter().zip(amounts))
+        .zip(&consumption.estimated)
+        .map(|((participant, (quantity, amount)), estimated)| ParticipantShare {
+            kind: participant.kind,
+            tenant_id: participant.tenant_id,
+            property_unit_id: participant.property_unit_id,
+            quantity: *quantity,
+            amount,
+            exact_amount: amount,
+            estimated: key == AllocationKey::Consumption && *estimated,
+        })
+        .collect();
+
+    CostLine {
+        cost_type_id: cost_type.id.unwrap_or(0),
+        cost_type_name: cost_type.name.clone(),
+        total_amount,
+        exact_total_amount: total_amount,
+        allocation_key: key,
+        fallback_from,
+        total_quantity,
+        tariff_segment,
+        cost_part: None,
+        cost_part_percent: None,
+        common_consumption: false,
+        apportionment: None,
+        priced_consumption: None,
+        oil_consumption: None,
+        hot_water_split: None,
+        degree_day_weighting: false,
+        co2_cost: None,
+        co2_landlord_amount: None,
+        co2_split: None,
+        transfer_in: None,
+        transfers_out: Vec::new(),
+        common_meters: Vec::new(),
+        key_meters: if key == AllocationKey::Consumption {
+            consumption.meters.clone()
+        } else {
+            Vec::new()
+        },
+        shares,
+    }
+}
+
+// Conversion factor of each meter bound to a cost type, by meter ID
+type MeterBindings = HashMap<i32, Decimal>;
+
+// Cost type a building amount is allocated under, with its share of the amount
+struct AllocationTarget<'a> {
+    cost_type: &'a CostType,
+    key: AllocationKey,
+    bindings: MeterBindings, // Meters giving the consumption key quantities
+    share: Decimal,
+    hot_water_split: Option<HotWaterSplit>,
+}
+
+fn allocation_target<'a>(
+    conn: &mut SqliteConnection,
+    cost_type: &'a CostType,
+    share: Decimal,
+    hot_water_split: Option<HotWaterSplit>,
+) -> Result<AllocationTarget<'a>, AllocationError> {
+    // Only meters bound to the cost type count, converted to its unit
+    let bindings: MeterBindings = meter_cost_types::table
+        .filter(meter_cost_types::cost_type_id.eq(cost_type.id.unwrap_or(0)))
+        .load::<MeterCostType>(conn)?
+        .into_iter()
+        .map(|binding| (binding.meter_id, binding.conversion_factor))
+        .collect();
+
+    Ok(AllocationTarget {
+        cost_type,
+        key: allocation_key_for_cost_type(conn, cost_type)?,
+        bindings,
+        share,
+        hot_water_split,
+    })
+}
+
+// Meter bindings of a building's cost types with the units of both sides
+pub fn load_binding_units(
+    conn: &mut SqliteConnection,
+    building_id: i32,
+) -> Result<Vec<BindingUnits>, diesel::result::Error> {
+    meter_cost_types::table
+        .inner_join(meters::table)
+        .inner_join(cost_types::table)
+        .filter(cost_types::building_id.eq(building_id))
+        .order_by(meter_cost_types::id.asc())
+        .select((
+            meter_cost_types::meter_id,
+            meters::name,
+            meters::unit,
+            meter_cost_types::cost_type_id,
+            cost_types::name,
+            cost_types::unit,
+            meter_cost_types::conversion_factor,
+        ))
+        .load::<BindingUnits>(conn)
+}
+
+// Units, tenants and meters of the building a billing period is allocated for
+struct BuildingContext<'a> {
+    units: &'a [PropertyUnit],
+    all_tenants: &'a [Tenant],
+    participants: &'a [Participant],
+    unit_meters: &'a [UnitMeter],
+    common_meters: &'a [MeterTree],
+    degree_days: DegreeDays,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    vacancy_persons: VacancyPersons,
+}
+
+impl BuildingContext<'_> {
+    // Heating cost types weight days by their degree-day share, all others count calendar days
+    fn weighting_for(&self, cost_type: &CostType) -> DayWeighting {
+        if cost_type.degree_day_weighting {
+            DayWeighting::DegreeDays(self.degree_days)
+        } else {
+            DayWeighting::Calendar
+        }
+    }
+
+    // Cost lines of an amount spent from the beginning of `from` to the end of `to`, allocated
+    // under each target among the participants of those days
+    fn target_lines(
+        &self,
+        targets: &[AllocationTarget],
+        amount: Decimal,
+        tariff_segment: Option<TariffSegment>,
+        (from, to): (NaiveDate, NaiveDate),
+        findings: &mut Findings,
+    ) -> Vec<CostLine> {
+        let mut lines = Vec::new();
+        for target in targets {
+            let target_amount = amount * target.share;
+            if target_amount.is_zero() {
+                continue;
+            }
+            let weighted = target.cost_type.degree_day_weighting;
+            let window_participants = participants_within(
+                self.units,
+                self.all_tenants,
+                self.participants,
+                from,
+                to,
+                &self.weighting_for(target.cost_type),
+                self.vacancy_persons,
+            );
+            let unit_consumption =
+                consumption_by_unit(self.unit_meters, &target.bindings, from, to, weighted, findings);
+            let consumption = self.participant_consumption(
+                &window_participants,
+                &target.bindings,
+                &unit_consumption,
+                (from, to),
+                weighted,
+                findings,
+            );
+            lines.extend(
+                cost_lines(
+                    target.cost_type,
+                    target_amount,
+                    target.key,
+                    tariff_segment.clone(),
+                    &window_participants,
+                    &consumption,
+                )
+                .into_iter()
+                .map(|line| CostLine {
+                    hot_water_split: target.hot_water_split.clone(),
+                    degree_day_weighting: weighted,
+                    ..line
+                }),
+            );
+        }
+
+        lines
+    }
+
+    // Consumption of each participant from the meter values at the start and end of its own days.
+    // The unit's meters are read at every change of occupants, so an outgoing and an incoming tenant
+    // each get what was consumed during their tenancy; concurrent tenants split the consumption of
+    // their common days. Where a meter can't be read at a change, the unit's consumption is split by
+    // each participant's share of the days instead.
+    fn participant_consumption(
+        &self,
+        participants: &[Participant],
+        bindings: &MeterBindings,
+        unit_consumption: &UnitConsumption,
+        (from, to): (NaiveDate, NaiveDate),
+        degree_day_weighting: bool,
+        findings: &mut Findings,
+    ) -> ParticipantConsumption {
+        let mut result = ParticipantConsumption {
+            values: vec![Decimal::ZERO; participants.len()],
+            estimated: vec![false; participants.len()],
+            meters: unit_consumption.meters.clone(),
+        };
+
+        let mut unit_ids: Vec<i32> = participants.iter().map(|p| p.property_unit_id).collect();
+        unit_ids.sort_unstable();
+        unit_ids.dedup();
+        for unit_id in unit_ids {
+            let Some(unit_value) = unit_consumption.values.get(&unit_id).copied() else {
+                continue;
+            };
+            let members: Vec<usize> = (0..participants.len())
+                .filter(|index| participants[*index].property_unit_id == unit_id)
+                .collect();
+
+            // Stretches of days with the same occupants; days without a tenant go to vacancy or owner
+            let mut stretches: Vec<(NaiveDate, NaiveDate, Vec<usize>)> = Vec::new();
+            for date in from.iter_days().take_while(|date| *date <= to) {
+                let tenants: Vec<usize> = members
+                    .iter()
+                    .copied()
+                    .filter(|index| {
+                        participants[*index].tenant_id.is_some_and(|tenant_id| {
+                            self.all_tenants
+                                .iter()
+                                .any(|tenant| tenant.id == Some(tenant_id) && tenant.occupies(date))
+                        })
+                    })
+                    .collect();
+                let active = if tenants.is_empty() {
+                    members
+                        .iter()
+                        .copied()
+                        .filter(|index| participants[*index].kind != ParticipantKind::Tenant)
+                        .collect()
+                } else {
+                    tenants
+                };
+                match stretches.last_mut() {
+                    Some((_, end, occupants)) if *occupants == active => *end = date,
+                    _ => stretches.push((date, date, active)),
+                }
+            }
+
+            // A single stretch gets the unit's consumption of the whole window
+            if stretches.len() == 1 {
+                let (_, _, occupants) = &stretches[0];
+                for index in occupants {
+                    result.values[*index] = unit_value / Decimal::from(occupants.len());
+                    result.estimated[*index] = unit_consumption.estimated.contains(&unit_id);
+                }
+                continue;
+            }
+
+            let meters: Vec<(&UnitMeter, Decimal)> = self
+                .unit_meters
+                .iter()
+                .filter(|meter| meter.property_unit_id == unit_id)
+                .filter_map(|meter| bindings.get(&meter.tree.main_meter.meter_id).map(|factor| (meter, *factor)))
+                .collect();
+            let mut values = vec![Decimal::ZERO; participants.len()];
+            let mut estimated = vec![false; participants.len()];
+            let mut readable = true;
+            'stretches: for (start, end, occupants) in &stretches {
+                let mut stretch_value = Decimal::ZERO;
+                let mut stretch_estimated = false;
+                for (meter, factor) in &meters {
+                    let Some(consumption) = meter.tree.residual(*start, *end, degree_day_weighting) else {
+                        let message = format!(
+                            "Meter '{}' can't be read from {} to {}, consumption split between the occupants by days",
+                            meter.tree.main_meter.name, start, end
+                        );
+                        findings.note(message);
+                        readable = false;
+                        break 'stretches;
+                    };
+                    stretch_value += consumption.residual.max(Decimal::ZERO) * factor;
+                    stretch_estimated |= consumption.estimated;
+                }
+                for index in occupants {
+                    values[*index] += stretch_value / Decimal::from(occupants.len());
+                    estimated[*index] |= stretch_estimated;
+                }
+            }
+
+            for index in members {
+                if readable {
+                    result.values[index] = values[index];
+                    result.estimated[index] = estimated[index];
+                } else {
+                    result.values[index] = unit_value * participants[index].unit_share;
+                    result.estimated[index] = unit_consumption.estimated.contains(&unit_id);
+                }
+            }
+        }
+
+        result
+    }
+
+    // Take the amount of a cost transfer off the lines of its source cost type in proportion and
+    // allocate it with the target cost type. None, with a warning, if it can't be determined.
+    fn apply_transfer(
+        &self,
+        conn: &mut SqliteConnection,
+        transfer: &CostTransfer,
+        all_cost_types: &[CostType],
+        lines: &mut Vec<CostLine>,
+        co2_splits: &mut [Co2Split],
+        findings: &mut Findings,
+    ) -> Result<Option<AppliedTransfer>, AllocationError> {
+        let mut skip = |message: String| findings.warn(message);
+        let find = |cost_type_id: i32| all_cost_types.iter().find(|cost_type| cost_type.id == Some(cost_type_id));
+        let (Some(source), Some(target)) = (find(transfer.source_cost_type_id), find(transfer.target_cost_type_id))
+        else {
+            skip(format!(
+                "Cost types of cost transfer '{}' don't belong to its building, not applied",
+                transfer.name
+            ));
+            return Ok(None);
+        };
+
+        let source_total: Decimal = lines
+            .iter()
+            .filter(|line| line.cost_type_id == transfer.source_cost_type_id)
+            .map(|line| line.total_amount)
+            .sum();
+        if source_total <= Decimal::ZERO {
+            skip(format!(
+                "Cost type '{}' has no costs in the period, cost transfer '{}' not applied",
+                source.name, transfer.name
+            ));
+            return Ok(None);
+        }
+
+        let mut applied = AppliedTransfer {
+            transfer_id: transfer.id.unwrap_or(0),
+            name: transfer.name.clone(),
+            source_cost_type_id: transfer.source_cost_type_id,
+            source_cost_type_name: source.name.clone(),
+            target_cost_type_id: transfer.target_cost_type_id,
+            target_cost_type_name: target.name.clone(),
+            method: TransferMethod::Percentage,
+            percent: None,
+            basis_cost_type_name: None,
+            basis_amount: None,
+            meter_name: None,
+            meter_consumption: None,
+            price_per_unit: None,
+            amount: Decimal::ZERO,
+        };
+
+        match TransferMethod::from_name(&transfer.method) {
+            Some(TransferMethod::Percentage) => {
+                let (Some(percent), Some(basis)) = (
+                    transfer.percent.map(decimal::from_f32),
+                    transfer.basis_cost_type_id.and_then(find),
+                ) else {
+                    skip(format!(
+                        "Cost transfer '{}' has no percentage or basis cost type, not applied",
+                        transfer.name
+                    ));
+                    return Ok(None);
+                };
+                // Costs of the basis before any transfer into it, including parts split off it
+                // and the landlord's part of its CO2 costs
+                let basis_id = basis.id.unwrap_or(0);
+                let basis_amount: Decimal = lines
+                    .iter()
+                    .filter(|line| {
+                        line.transfer_in.is_none()
+                            && (line.cost_type_id == basis_id
+                                || line
+                                    .hot_water_split
+                                    .as_ref()
+                                    .is_some_and(|split| split.fuel_cost_type_id == basis_id))
+                    })
+                    .map(|line| {
+                        line.total_amount
+                            + line.co2_landlord_amount.unwrap_or_default()
+                            + line.transfers_out.iter().map(|part| part.amount).sum::<Decimal>()
+                    })
+                    .sum();
+                if basis_amount <= Decimal::ZERO {
+                    skip(format!(
+                        "Basis cost type '{}' has no costs of its own in the period, cost transfer '{}' not applied",
+                        basis.name, transfer.name
+                    ));
+                    return Ok(None);
+                }
+
+                applied.percent = Some(percent);
+                applied.basis_cost_type_name = Some(basis.name.clone());
+                applied.basis_amount = Some(basis_amount);
+                applied.amount = basis_amount * percent / Decimal::ONE_HUNDRED;
+            }
+            Some(TransferMethod::Meter) => {
+                let meter = match transfer.meter_id {
+                    Some(meter_id) => meters::table
+                        .filter(meters::id.eq(meter_id))
+                        .select(Meter::as_select())
+                        .first::<Meter>(conn)
+                        .optional()?,
+                    None => None,
+                };
+                let Some(meter) = meter else {
+                    skip(format!("Cost transfer '{}' has no meter, not applied", transfer.name));
+                    return Ok(None);
+                };
+
+                // Consumption in each window of a price: the transfer's own or the source's tariffs
+                let windows = match transfer.price_per_unit {
+                    Some(price) => vec![(self.start_date, self.end_date, price)],
+                    None => {
+                        let source_tariffs = tariffs::table
+                            .filter(tariffs::cost_type_id.eq(transfer.source_cost_type_id))
+                            .load::<Tariff>(conn)?;
+                        tariff_segments(&source_tariffs, self.start_date, self.end_date)
+                            .into_iter()
+                            .map(|segment| (segment.start_date, segment.end_date, segment.price_per_unit))
+                            .collect()
+                    }
+                };
+                if windows.is_empty() {
+                    skip(format!(
+                        "Cost transfer '{}' needs a price per unit or tariffs of cost type '{}', not applied",
+                        transfer.name, source.name
+                    ));
+                    return Ok(None);
+                }
+
+                let mut consumption = Decimal::ZERO;
+                for (from, to, price) in windows {
+                    let Some(window_consumption) = hot_water::meter_consumption(conn, &meter, from, to)? else {
+                        skip(format!(
+                            "Not enough readings of meter '{}' from {} to {}, cost transfer '{}' not applied",
+                            meter.name, from, to, transfer.name
+                        ));
+                        return Ok(None);
+                    };
+                    consumption += window_consumption;
+                    applied.amount += window_consumption * price;
+                }
+
+                applied.method = TransferMethod::Meter;
+                applied.meter_name = Some(meter.name.clone());
+                applied.meter_consumption = Some(consumption);
+                applied.price_per_unit = if consumption.is_zero() {
+                    transfer.price_per_unit
+                } else {
+                    Some(applied.amount / consumption)
+                };
+            }
+            None => {
+                skip(format!(
+                    "Cost transfer '{}' has unknown method '{}', not applied",
+                    transfer.name, transfer.method
+                ));
+                return Ok(None);
+            }
+        }
+
+        if applied.amount <= Decimal::ZERO {
+            return Ok(None);
+        }
+        if applied.amount > source_total {
+            skip(format!(
+                "Cost transfer '{}' of {:.2} exceeds the costs of cost type '{}' ({:.2}), not applied",
+                transfer.name,
+                applied.amount.round_dp(2),
+                source.name,
+                source_total.round_dp(2)
+            ));
+            return Ok(None);
+        }
+
+        // All shares of the source lines are reduced in proportion, and so are the CO2 costs they
+        // contain. The CO2 costs moved along are no longer part of the fuel's CO2 split.
+        let remaining = (source_total - applied.amount) / source_total;
+        for line in lines
+            .iter_mut()
+            .filter(|line| line.cost_type_id == transfer.source_cost_type_id)
+        {
+            line.transfers_out.push(TransferPart {
+                transfer_id: applied.transfer_id,
+                name: applied.name.clone(),
+                target_cost_type_name: applied.target_cost_type_name.clone(),
+                amount: line.total_amount * applied.amount / source_total,
+            });
+            line.total_amount *= remaining;
+            line.exact_total_amount = line.total_amount;
+            for share in &mut line.shares {
+                share.amount *= remaining;
+                share.exact_amount = share.amount;
+            }
+
+            let Some(split_cost_type_id) = line.co2_split.as_ref().map(|split| split.cost_type_id) else {
+                continue;
+            };
+            let co2_cost = line.co2_cost.unwrap_or_default();
+            let landlord_amount = line.co2_landlord_amount.unwrap_or_default();
+            line.co2_cost = Some(co2_cost * remaining);
+            line.co2_landlord_amount = Some(landlord_amount * remaining);
+            if let Some(split) = co2_splits
+                .iter_mut()
+                .find(|split| split.cost_type_id == split_cost_type_id)
+            {
+                split.co2_cost -= co2_cost - co2_cost * remaining;
+                split.landlord_amount -= landlord_amount - landlord_amount * remaining;
+            }
+        }
+        for line in lines.iter_mut() {
+            if let Some(split) = line.co2_split.as_mut() {
+                if let Some(updated) = co2_splits.iter().find(|updated| updated.cost_type_id == split.cost_type_id) {
+                    *split = updated.clone();
+                }
+            }
+        }
+
+        // The moved amount is allocated like the target's own costs of the whole period
+        let target_allocation = allocation_target(conn, target, Decimal::ONE, None)?;
+        lines.extend(
+            self.target_lines(
+                &[target_allocation],
+                applied.amount,
+                None,
+                (self.start_date, self.end_date),
+                findings,
+            )
+            .into_iter()
+            .map(|line| CostLine {
+                transfer_in: Some(applied.clone()),
+                ..line
+            }),
+        );
+
+        Ok(Some(applied))
+    }
+
+    // Heating and hot water targets of a heating system's fuel costs (HeizkostenV § 9).
+    // None if the cost types are missing; without enough data to split, all goes to heating.
+    fn hot_water_targets<'a>(
+        &self,
+        conn: &mut SqliteConnection,
+        system: &HeatingSystem,
+        fuel_cost_type: &CostType,
+        all_cost_types: &'a [CostType],
+        findings: &mut Findings,
+    ) -> Result<Option<Vec<AllocationTarget<'a>>>, AllocationError> {
+        let find = |cost_type_id: i32| all_cost_types.iter().find(|cost_type| cost_type.id == Some(cost_type_id));
+        let (Some(heating_cost_type), Some(hot_water_cost_type)) =
+            (find(system.heating_cost_type_id), find(system.hot_water_cost_type_id))
+        else {
+            let message = format!(
+                "Cost types of heating system '{}' don't belong to its building, fuel costs not split",
+                system.name
+            );
+            findings.warn(message);
+            return Ok(None);
+        };
+
+        // Hot water volume of the meters bound to the hot water cost type, for the formula
+        let hot_water = allocation_target(conn, hot_water_cost_type, Decimal::ONE, None)?;
+        let volume_in_m3 = hot_water_cost_type
+            .unit
+            .as_deref()
+            .is_some_and(|unit| units_match(unit, "m3"));
+        let hot_water_volume = if hot_water.bindings.is_empty() || !volume_in_m3 {
+            None
+        } else {
+            let weighted = hot_water_cost_type.degree_day_weighting;
+            let units: Decimal = consumption_by_unit(
+                self.unit_meters,
+                &hot_water.bindings,
+                self.start_date,
+                self.end_date,
+                weighted,
+                findings,
+            )
+            .values
+            .values()
+            .sum();
+            let (common, _) = common_consumption(
+                self.common_meters,
+                &hot_water.bindings,
+                self.start_date,
+                self.end_date,
+                weighted,
+                findings,
+            );
+            Some(units + common)
+        };
+
+        match hot_water::hot_water_split(
+            conn,
+            system,
+            fuel_cost_type,
+            hot_water_volume,
+            self.start_date,
+            self.end_date,
+        ) {
+            Ok(split) => {
+                info!(
+                    "Heating system '{}': {:.2} % of the fuel costs for hot water",
+                    system.name, split.hot_water_percent
+                );
+                let share = split.hot_water_share();
+                Ok(Some(vec![
+                    allocation_target(conn, heating_cost_type, Decimal::ONE - share, Some(split.clone()))?,
+                    AllocationTarget {
+                        share,
+                        hot_water_split: Some(split),
+                        ..hot_water
+                    },
+                ]))
+            }
+            Err(HotWaterError::Undetermined(message)) => {
+                let message = format!("{}, fuel costs allocated as heating costs", message);
+                findings.warn(message);
+                Ok(Some(vec![allocation_target(conn, heating_cost_type, Decimal::ONE, None)?]))
+            }
+            Err(HotWaterError::Database(e)) => Err(AllocationError::Database(e)),
+        }
+    }
+}
+
+// A meter counting for a unit, with its sub-meters
+struct UnitMeter {
+    property_unit_id: i32,
+    tree: MeterTree,
+}
+
+// Consumption per property unit within a date range, summed over the unit's meters
+struct UnitConsumption {
+    values: HashMap<i32, Decimal>,
+    estimated: HashSet<i32>, // Units with at least one estimated meter value
+    meters: Vec<MeterUsage>,
+}
+
+// Consumption of each participant, aligned with the participants of a window
+struct ParticipantConsumption {
+    values: Vec<Decimal>,
+    estimated: Vec<bool>, // Quantity based on at least one estimated meter value
+    meters: Vec<MeterUsage>, // Unit meters over the whole window
+}
+
+// Consumption from the beginning of `from` to the end of `to`. Meter values at the boundaries
+// are interpolated where no reading was taken on the day. A main meter contributes its
+// consumption minus its sub-meters; a negative residual is counted as zero.
+// Only bound meters are included, converted with the binding's factor. With degree-day weighting
+// boundary values are estimated by degree days regardless of the meter's interpolation method.
+fn consumption_by_unit(
+    unit_meters: &[UnitMeter],
+    bindings: &MeterBindings,
+    from: NaiveDate,
+    to: NaiveDate,
+    degree_day_weighting: bool,
+    findings: &mut Findings,
+) -> UnitConsumption {
+    let mut result = UnitConsumption {
+        values: HashMap::new(),
+        estimated: HashSet::new(),
+        meters: Vec::new(),
+    };
+
+    for meter in unit_meters {
+        let Some(factor) = bindings.get(&meter.tree.main_meter.meter_id) else {
+            continue;
+        };
+        match meter.tree.residual(from, to, degree_day_weighting) {
+            Some(consumption) => {
+                report_residual(&consumption, findings);
+                let counted = consumption.residual.max(Decimal::ZERO) * factor;
+                *result.values.entry(meter.property_unit_id).or_default() += counted;
+                if consumption.estimated {
+                    result.estimated.insert(meter.property_unit_id);
+                }
+                result.meters.push(MeterUsage {
+                    property_unit_id: Some(meter.property_unit_id),
+                    conversion_factor: *factor,
+                    consumption,
+                    counted,
+                });
+            }
+            None => {
+                let message = format!(
+                    "Not enough readings to determine the consumption of meter '{}' from {} to {}",
+                    meter.tree.main_meter.name, from, to
+                );
+                findings.warn(message);
+            }
+        }
+    }
+
+    result
+}
+
+// Sub-meters missing or exceeding their main meter make the residual wrong; a residual above
+// the meter's limit and values estimated beyond the readings are noted on the statements
+fn report_residual(consumption: &ResidualConsumption, findings: &mut Findings) {
+    for message in &consumption.warnings {
+        findings.warn(message.clone());
+    }
+    for message in consumption.notes.iter().cloned().chain(consumption.extrapolation_warnings()) {
+        findings.note(message);
+    }
+}
+
+// Consumption of the common meters bound to a cost type from the beginning of `from`
+// to the end of `to`, with the meters it is made of. Main meters count with their residual
+// after the sub-meters.
+fn common_consumption(
+    common_meters: &[MeterTree],
+    bindings: &MeterBindings,
+    from: NaiveDate,
+    to: NaiveDate,
+    degree_day_weighting: bool,
+    findings: &mut Findings,
+) -> (Decimal, Vec<MeterUsage>) {
+    let usages: Vec<MeterUsage> = common_meters
+        .iter()
+        .filter_map(|tree| {
+            let factor = bindings.get(&tree.main_meter.meter_id)?;
+            match tree.residual(from, to, degree_day_weighting) {
+                Some(consumption) => {
+                    report_residual(&consumption, findings);
+                    Some(MeterUsage {
+                        property_unit_id: None,
+                        conversion_factor: *factor,
+                        counted: consumption.residual.max(Decimal::ZERO) * factor,
+                        consumption,
+                    })
+                }
+                None => {
+                    let message = format!(
+                        "Not enough readings to determine the consumption of common meter '{}' from {} to {}",
+                        tree.main_meter.name, from, to
+                    );
+                    findings.warn(message);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    (usages.iter().map(|usage| usage.counted).sum(), usages)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+    use rust_decimal_macros::dec;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn timestamp() -> NaiveDateTime {
+        date(2024, 1, 1).and_hms_opt(0, 0, 0).unwrap()
+    }
+
+    fn unit(id: i32, living_area_m2: f32, owner_occupied: bool) -> PropertyUnit {
+        PropertyUnit {
+            id: Some(id),
+            name: format!("Einheit {}", id),
+            living_area_m2,
+            created_at: timestamp(),
+            updated_at: timestamp(),
+            heated_area_m2: None,
+            owner_occupied,
+            owner_persons: Some(2),
+            building_id: 1,
+        }
+    }
+
+    fn tenant(
+        id: i32,
+        property_unit_id: i32,
+        number_of_persons: i32,
+        move_in_date: Option<NaiveDate>,
+        move_out_date: Option<NaiveDate>,
+    ) -> Tenant {
+        Tenant {
+            id: Some(id),
+            name: format!("Mieter {}", id),
+            number_of_persons,
+            property_unit_id,
+            created_at: timestamp(),
+            updated_at: timestamp(),
+            move_in_date,
+            move_out_date,
+        }
+    }
+
+    fn tariff(id: i32, price_per_unit: Decimal, valid_from: NaiveDate, valid_to: Option<NaiveDate>) -> Tariff {
+        Tariff {
+            id: Some(id),
+            cost_type_id: 1,
+            price_per_unit,
+            valid_from,
+            valid_to,
+            created_at: timestamp(),
+            updated_at: timestamp(),
+        }
+    }
+
+    fn fixed_cost(amount: Decimal, start_date: NaiveDate, end_date: NaiveDate) -> FixedCost {
+        FixedCost {
+            id: Some(1),
+            cost_type_id: 1,
+            amount,
+            billing_period_start: start_date,
+            billing_period_end: end_date,
+            created_at: timestamp(),
+            updated_at: timestamp(),
+            fuel_quantity: None,
+            co2_emission_factor: None,
+            co2_cost: None,
+        }
+    }
+
+    fn cost_type(id: i32, name: &str) -> CostType {
+        CostType {
+            id: Some(id),
+            name: name.to_string(),
+            description: None,
+            is_consumption_based: false,
+            unit: None,
+            created_at: timestamp(),
+            updated_at: timestamp(),
+            consumption_share_percent: None,
+            uses_burner_hours: false,
+            betrkv_category_id: None,
+            building_id: 1,
+            degree_day_weighting: false,
+            co2_emission_factor: None,
+        }
+    }
+
+    fn hot_water_split(hot_water_percent: Decimal) -> HotWaterSplit {
+        HotWaterSplit {
+            heating_system_id: 1,
+            fuel_cost_type_id: 3,
+            fuel_cost_type_name: "Gas".to_string(),
+            heating_cost_type_id: 4,
+            hot_water_cost_type_id: 5,
+            method: hot_water::HotWaterMethod::HeatMeter,
+            hot_water_volume: None,
+            hot_water_temperature: None,
+            heat_quantity: hot_water_percent * dec!(100),
+            fuel_unit: crate::models::heating::FuelUnit::CubicMetre,
+            calorific_value: dec!(10),
+            hot_water_fuel: hot_water_percent * dec!(10),
+            fuel_source: fuel::FuelSource::FuelMeter,
+            fuel_quantity: dec!(1000),
+            hot_water_percent,
+        }
+    }
+
+    // Building 1 with two units, a tenant in each and the billing period 2024
+    const HOUSE_2024: &str = "
+        INSERT INTO property_units (id, name, living_area_m2) VALUES (1, 'EG', 100), (2, 'OG', 50);
+        INSERT INTO tenants (id, name, number_of_persons, property_unit_id)
+            VALUES (1, 'Mieter EG', 1, 1), (2, 'Mieter OG', 3, 2);
+        INSERT INTO billing_periods (id, building_id, start_date, end_date, name)
+            VALUES (1, 1, '2024-01-01', '2024-12-31', '2024');
+    ";
+
+    // Allocate the billing period 2024 of a test database filled with `seed`
+    fn allocate_2024(seed: &str) -> BillingAllocation {
+        use diesel::connection::SimpleConnection;
+
+        let conn = &mut crate::db::test_connection();
+        conn.batch_execute(HOUSE_2024).unwrap();
+        conn.batch_execute(seed).unwrap();
+        let billing_period = crate::schema::billing_periods::table
+            .first::<BillingPeriod>(conn)
+            .unwrap();
+        allocate_billing_period(conn, &billing_period).unwrap()
+    }
+
+    fn tenant_amounts(line: &CostLine) -> Vec<Decimal> {
+        line.shares
+            .iter()
+            .filter(|share| share.kind == ParticipantKind::Tenant)
+            .map(|share| share.amount)
+            .collect()
+    }
+
+    fn participants_2024(units: &[PropertyUnit], tenants: &[Tenant]) -> Vec<Participant> {
+        build_participants(
+            units,
+            tenants,
+            date(2024, 1, 1),
+            date(2024, 12, 31),
+            &DayWeighting::Calendar,
+            VacancyPersons::One,
+        )
+    }
+
+    #[test]
+    fn costs_are_split_with_the_allocation_method_of_the_cost_type() {
+        let allocation = allocate_2024(
+            "
+            INSERT INTO cost_types (id, name) VALUES (1, 'Müllabfuhr'), (2, 'Grundsteuer');
+            INSERT INTO cost_type_allocations (cost_type_id, allocation_method_id)
+                SELECT 1, id FROM allocation_methods WHERE name = 'PersonCount';
+            INSERT INTO fixed_costs (cost_type_id, amount, billing_period_start, billing_period_end)
+                VALUES (1, '400', '2024-01-01', '2024-12-31'), (2, '300', '2024-01-01', '2024-12-31');
+            ",
+        );
+
+        let line_of = |cost_type_id| allocation.lines.iter().find(|line| line.cost_type_id == cost_type_id).unwrap();
+        assert_eq!(line_of(1).allocation_key, AllocationKey::PersonCount);
+        assert_eq!(tenant_amounts(line_of(1)), vec![dec!(100), dec!(300)]);
+        // Cost types without an assigned method are split by living area
+        assert_eq!(line_of(2).allocation_key, AllocationKey::LivingArea);
+        assert_eq!(tenant_amounts(line_of(2)), vec![dec!(200), dec!(100)]);
+    }
+
+    #[test]
+    fn lines_split_off_one_invoice_add_up_to_the_invoice() {
+        let participants = participants_2024(&[unit(1, 50.0, false), unit(2, 50.0, false)], &[]);
+        let consumption = ParticipantConsumption {
+            values: vec![Decimal::ZERO; 2],
+            estimated: vec![false; 2],
+            meters: Vec::new(),
+        };
+        // Half of 100.01 each; rounded per cost type both halves would round up to 50.01
+        let mut lines: Vec<CostLine> = [(4, "Heizung"), (5, "Warmwasser")]
+            .iter()
+            .map(|(id, name)| CostLine {
+                hot_water_split: Some(hot_water_split(dec!(50))),
+                ..build_cost_line(
+                    &cost_type(*id, name),
+                    dec!(50.005),
+                    AllocationKey::EqualShare,
+                    None,
+                    &participants,
+                    vec![Decimal::ONE; 2],
+                    &consumption,
+                )
+            })
+            .collect();
+        round_to_cents(&mut lines);
+
+        assert_eq!(lines[0].total_amount + lines[1].total_amount, dec!(100.01));
+        for line in &lines {
+            assert_eq!(line.shares.iter().map(|share| share.amount).sum::<Decimal>(), line.total_amount);
+        }
+    }
+
+    #[test]
+    fn days_without_tenancy_are_vacancy() {
+        let tenants = [tenant(1, 1, 2, Some(date(2024, 4, 1)), None)];
+        let participants = participants_2024(&[unit(1, 100.0, false)], &tenants);
+
+        assert_eq!(participants.len(), 2);
+        let vacancy = &participants[1];
+        assert_eq!(vacancy.kind, ParticipantKind::Vacancy);
+        assert_eq!(vacancy.tenant_id, None);
+        assert_eq!(vacancy.occupied_days, 91);
+        assert_eq!(vacancy.number_of_persons, 1);
+        assert_eq!(vacancy.unit_share, dec!(91) / dec!(366));
+        assert_eq!((participants[0].unit_share + vacancy.unit_share).round_dp(20), Decimal::ONE);
+    }
+
+    #[test]
+    fn vacancy_counts_with_the_building_setting() {
+        let tenants = [
+            tenant(1, 1, 3, None, Some(date(2024, 3, 31))),
+            tenant(2, 1, 2, Some(date(2024, 7, 1)), None),
+        ];
+        let vacancy = |vacancy_persons| {
+            build_participants(
+                &[unit(1, 100.0, false)],
+                &tenants,
+                date(2024, 1, 1),
+                date(2024, 12, 31),
+                &DayWeighting::Calendar,
+                vacancy_persons,
+            )
+            .into_iter()
+            .find(|participant| participant.kind == ParticipantKind::Vacancy)
+            .unwrap()
+        };
+
+        let none = vacancy(VacancyPersons::None);
+        assert_eq!(none.number_of_persons, 0);
+        assert_eq!(none.person_share, Decimal::ZERO);
+        assert_eq!(none.unit_share, dec!(91) / dec!(366));
+
+        let previous = vacancy(VacancyPersons::PreviousTenant);
+        assert_eq!(previous.number_of_persons, 3);
+        assert_eq!(previous.person_share, dec!(3) * dec!(91) / dec!(366));
+    }
+
+    #[test]
+    fn vacancy_without_previous_tenant_counts_as_one_person() {
+        let tenants = [tenant(1, 1, 4, Some(date(2024, 4, 1)), None)];
+        let participants = build_participants(
+            &[unit(1, 100.0, false)],
+            &tenants,
+            date(2024, 1, 1),
+            date(2024, 12, 31),
+            &DayWeighting::Calendar,
+            VacancyPersons::PreviousTenant,
+        );
+
+        assert_eq!(participants[1].kind, ParticipantKind::Vacancy);
+        assert_eq!(participants[1].person_share, dec!(91) / dec!(366));
+    }
+
+    #[test]
+    fn tenant_change_splits_the_unit_by_days() {
+        let tenants = [
+            tenant(1, 1, 2, None, Some(date(2024, 6, 30))),
+            tenant(2, 1, 3, Some(date(2024, 7, 1)), None),
+        ];
+        let participants = participants_2024(&[unit(1, 100.0, false)], &tenants);
+
+        assert_eq!(participants.len(), 2);
+        let (outgoing, incoming) = (&participants[0], &participants[1]);
+        assert_eq!(outgoing.kind, ParticipantKind::Tenant);
+        assert_eq!(outgoing.occupied_days, 182);
+        assert_eq!(incoming.occupied_days, 184);
+        assert_eq!(outgoing.unit_share, dec!(182) / dec!(366));
+        assert_eq!(outgoing.person_share, dec!(2) * dec!(182) / dec!(366));
+        assert_eq!(incoming.unit_share, dec!(184) / dec!(366));
+        assert_eq!(incoming.living_area_m2, dec!(100) * incoming.unit_share);
+        assert_eq!((outgoing.unit_share + incoming.unit_share).round_dp(20), Decimal::ONE);
+    }
+
+    #[test]
+    fn concurrent_tenants_share_the_unit() {
+        let tenants = [tenant(1, 1, 1, None, None), tenant(2, 1, 1, None, None)];
+        let participants = participants_2024(&[unit(1, 80.0, false)], &tenants);
+
+        assert_eq!(participants.len(), 2);
+        assert!(participants.iter().all(|participant| participant.unit_share == dec!(0.5)));
+        assert!(participants.iter().all(|participant| participant.occupied_days == 366));
+        assert!(participants.iter().all(|participant| participant.person_share == Decimal::ONE));
+    }
+
+    #[test]
+    fn tenancies_outside_the_period_are_left_out() {
+        let tenants = [
+            tenant(1, 1, 1, None, Some(date(2023, 12, 31))),
+            tenant(2, 1, 1, Some(date(2024, 1, 1)), None),
+        ];
+        let participants = participants_2024(&[unit(1, 100.0, false)], &tenants);
+
+        assert_eq!(participants.len(), 1);
+        assert_eq!(participants[0].tenant_id, Some(2));
+        assert_eq!(participants[0].unit_share, Decimal::ONE);
+    }
+
+    #[test]
+    fn tenants_without_quantity_and_amount_have_no_share() {
+        let tenants = [
+            tenant(1, 1, 2, None, Some(date(2024, 6, 30))),
+            tenant(2, 1, 3, Some(date(2024, 7, 1)), None),
+        ];
+        let participants = participants_2024(&[unit(1, 100.0, false)], &tenants);
+        let consumption = ParticipantConsumption {
+            values: vec![Decimal::ZERO; 2],
+            estimated: vec![false; 2],
+            meters: Vec::new(),
+        };
+        // Tariff window after the first tenant moved out
+        let line = build_cost_line(
+            &cost_type(1, "Wasser"),
+            dec!(120),
+            AllocationKey::Consumption,
+            None,
+            &participants,
+            vec![Decimal::ZERO, dec!(40)],
+            &consumption,
+        );
+
+        assert!(line.share_for(1).is_none());
+        assert_eq!(line.share_for(2).unwrap().amount, dec!(120));
+    }
+
+    #[test]
+    fn later_tariff_takes_over_from_its_start() {
+        let tariffs = [
+            tariff(1, dec!(2.0), date(2023, 1, 1), None),
+            tariff(2, dec!(2.5), date(2024, 7, 1), None),
+        ];
+        let segments = tariff_segments(&tariffs, date(2024, 1, 1), date(2024, 12, 31));
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].tariff_id, Some(1));
+        assert_eq!((segments[0].start_date, segments[0].end_date), (date(2024, 1, 1), date(2024, 6, 30)));
+        assert_eq!(segments[1].tariff_id, Some(2));
+        assert_eq!(segments[1].price_per_unit, dec!(2.5));
+        assert_eq!((segments[1].start_date, segments[1].end_date), (date(2024, 7, 1), date(2024, 12, 31)));
+    }
+
+    #[test]
+    fn days_without_tariff_are_left_out() {
+        let tariffs = [
+            tariff(1, dec!(2.0), date(2024, 1, 1), Some(date(2024, 3, 31))),
+            tariff(2, dec!(2.5), date(2024, 5, 1), None),
+        ];
+        let segments = tariff_segments(&tariffs, date(2024, 1, 1), date(2024, 12, 31));
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].end_date, date(2024, 3, 31));
+        assert_eq!(segments[1].start_date, date(2024, 5, 1));
+        assert!(tariff_segments(&[], date(2024, 1, 1), date(2024, 12, 31)).is_empty());
+    }
+
+    #[test]
+    fn sub_meter_problems_are_allocation_warnings() {
+        let allocation = allocate_2024(
+            "
+            INSERT INTO cost_types (id, name, is_consumption_based, unit) VALUES (1, 'Wasser', 1, 'm3');
+            INSERT INTO tariffs (cost_type_id, price_per_unit, valid_from) VALUES (1, '2', '2024-01-01');
+            INSERT INTO meters (id, name, meter_type, unit, assignment_type, residual_property_unit_id)
+                VALUES (1, 'Haus', 'water', 'm3', 'common', 1);
+            INSERT INTO meters (id, name, meter_type, unit, assignment_type, property_unit_id, parent_meter_id)
+                VALUES (2, 'OG', 'water', 'm3', 'unit', 2, 1);
+            INSERT INTO meter_cost_types (meter_id, cost_type_id) VALUES (1, 1), (2, 1);
+            INSERT INTO meter_readings (meter_id, reading_date, value) VALUES
+                (1, '2024-01-01 00:00:00', '1000'), (1, '2025-01-01 00:00:00', '1100'),
+                (2, '2024-01-01 00:00:00', '0'), (2, '2025-01-01 00:00:00', '120');
+            ",
+        );
+
+        assert!(allocation
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("Sub-meters of 'Haus' add up to 120.00")));
+    }
+
+    #[test]
+    fn owner_occupied_unit_without_tenancy_is_own_use() {
+        let participants = participants_2024(&[unit(1, 100.0, true)], &[]);
+
+        assert_eq!(participants.len(), 1);
+        let owner = &participants[0];
+        assert_eq!(owner.kind, ParticipantKind::Owner);
+        assert_eq!(owner.name, "Eigennutzung Einheit 1");
+        assert_eq!(owner.occupied_days, 366);
+        assert_eq!(owner.unit_share, Decimal::ONE);
+        assert_eq!(owner.person_share, dec!(2));
+    }
+
+    #[test]
+    fn invoice_across_periods_is_apportioned_by_days() {
+        let cost = fixed_cost(dec!(732), date(2023, 7, 1), date(2024, 6, 30));
+        let apportionment = Apportionment::of(&cost, date(2024, 1, 1), date(2024, 12, 31)).unwrap();
+
+        assert_eq!(apportionment.invoice_days, 366);
+        assert_eq!(apportionment.apportioned_days, 182);
+        assert_eq!((apportionment.start_date, apportionment.end_date), (date(2024, 1, 1), date(2024, 6, 30)));
+        assert_eq!(apportionment.amount(), dec!(364));
+        assert!(apportionment.is_partial());
+    }
+
+    #[test]
+    fn invoice_of_the_whole_period_is_taken_in_full() {
+        let cost = fixed_cost(dec!(1000), date(2024, 1, 1), date(2024, 12, 31));
+        let apportionment = Apportionment::of(&cost, date(2024, 1, 1), date(2024, 12, 31)).unwrap();
+
+        assert_eq!(apportionment.amount(), dec!(1000));
+        assert!(!apportionment.is_partial());
+    }
+
+    #[test]
+    fn invoice_outside_the_period_is_not_apportioned() {
+        let cost = fixed_cost(dec!(1000), date(2023, 1, 1), date(2023, 12, 31));
+        assert!(Apportionment::of(&cost, date(2024, 1, 1), date(2024, 12, 31)).is_none());
+    }
+
+    #[test]
+    fn transferred_costs_are_allocated_with_the_target_cost_type() {
+        let allocation = allocate_2024(
+            "
+            INSERT INTO cost_types (id, name) VALUES (1, 'Allgemeinstrom'), (2, 'Heizung');
+            INSERT INTO cost_type_allocations (cost_type_id, allocation_method_id)
+                SELECT 2, id FROM allocation_methods WHERE name = 'PersonCount';
+            INSERT INTO fixed_costs (cost_type_id, amount, billing_period_start, billing_period_end)
+                VALUES (1, '600', '2024-01-01', '2024-12-31'), (2, '2000', '2024-01-01', '2024-12-31');
+            INSERT INTO cost_transfers (building_id, name, source_cost_type_id, target_cost_type_id, method, percent, basis_cost_type_id)
+                VALUES (1, 'Betriebsstrom', 1, 2, 'percentage', 6, 2);
+            ",
+        );
+
+        // 6 % of the heating costs are taken off Allgemeinstrom and split by persons
+        let applied = &allocation.transfers[0];
+        assert_eq!(applied.basis_amount, Some(dec!(2000)));
+        assert_eq!(applied.amount, dec!(120));
+        let source = allocation.lines.iter().find(|line| line.cost_type_id == 1).unwrap();
+        assert_eq!(source.total_amount, dec!(480));
+        assert_eq!(tenant_amounts(source), vec![dec!(320), dec!(160)]);
+        let moved = allocation.lines.iter().find(|line| line.transfer_in.is_some()).unwrap();
+        assert_eq!(moved.cost_type_id, 2);
+        assert_eq!(tenant_amounts(moved), vec![dec!(30), dec!(90)]);
+    }
+
+    #[test]
+    fn transfer_beyond_the_source_costs_is_not_applied() {
+        let allocation = allocate_2024(
+            "
+            INSERT INTO cost_types (id, name) VALUES (1, 'Allgemeinstrom'), (2, 'Heizung');
+            INSERT INTO fixed_costs (cost_type_id, amount, billing_period_start, billing_period_end)
+                VALUES (1, '100', '2024-01-01', '2024-12-31'), (2, '2000', '2024-01-01', '2024-12-31');
+            INSERT INTO cost_transfers (building_id, name, source_cost_type_id, target_cost_type_id, method, percent, basis_cost_type_id)
+                VALUES (1, 'Betriebsstrom', 1, 2, 'percentage', 10, 2);
+            ",
+        );
+
+        assert!(allocation.transfers.is_empty());
+        assert!(allocation.lines.iter().all(|line| line.transfer_in.is_none()));
+        assert!(allocation
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("Cost transfer 'Betriebsstrom' of 200.00 exceeds")));
+    }
+
+    #[test]
+    fn fuel_costs_are_split_into_heating_and_hot_water() {
+        let allocation = allocate_2024(
+            "
+            INSERT INTO cost_types (id, name, is_consumption_based, unit)
+                VALUES (1, 'Gas', 0, NULL), (2, 'Heizung', 0, NULL), (3, 'Warmwasser', 1, 'm3');
+            INSERT INTO fixed_costs (cost_type_id, amount, billing_period_start, billing_period_end)
+                VALUES (1, '2000', '2024-01-01', '2024-12-31');
+            INSERT INTO meters (id, name, meter_type, unit, assignment_type, property_unit_id)
+                VALUES (1, 'WW EG', 'water', 'm3', 'unit', 1), (2, 'WW OG', 'water', 'm3', 'unit', 2),
+                    (3, 'Gas', 'gas', 'm3', 'common', NULL);
+            INSERT INTO meter_cost_types (meter_id, cost_type_id) VALUES (1, 3), (2, 3);
+            INSERT INTO meter_readings (meter_id, reading_date, value) VALUES
+                (1, '2024-01-01 00:00:00', '0'), (1, '2025-01-01 00:00:00', '30'),
+                (2, '2024-01-01 00:00:00', '0'), (2, '2025-01-01 00:00:00', '10'),
+                (3, '2024-01-01 00:00:00', '1000'), (3, '2025-01-01 00:00:00', '3000');
+            INSERT INTO heating_systems (id, building_id, name, fuel_cost_type_id, heating_cost_type_id,
+                    hot_water_cost_type_id, fuel_meter_id)
+                VALUES (1, 1, 'Kessel', 1, 2, 3, 3);
+            INSERT INTO heating_system_fuels (heating_system_id, fuel_type, fuel_unit, calorific_value, valid_from)
+                VALUES (1, 'natural_gas', 'm3', 10, '2020-01-01');
+            ",
+        );
+
+        // Q = 2.5 * 40 m3 * (60 - 10) = 5000 kWh of 2000 m3 * 10 kWh of gas burned
+        let line_of = |cost_type_id| allocation.lines.iter().find(|line| line.cost_type_id == cost_type_id).unwrap();
+        let split = line_of(3).hot_water_split.as_ref().unwrap();
+        assert_eq!(split.hot_water_percent, dec!(25));
+        assert_eq!(line_of(2).total_amount, dec!(1500));
+        assert_eq!(tenant_amounts(line_of(2)), vec![dec!(1000), dec!(500)]);
+        assert_eq!(line_of(3).total_amount, dec!(500));
+        assert_eq!(tenant_amounts(line_of(3)), vec![dec!(375), dec!(125)]);
+        assert!(allocation.lines.iter().all(|line| line.cost_type_id != 1));
+    }
+}
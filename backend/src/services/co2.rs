@@ -0,0 +1,257 @@
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+
+use crate::models::cost::FixedCost;
+use crate::models::decimal;
+use crate::services::allocation::Apportionment;
+use crate::services::oil_tank::FuelCost;
+
+// CO2 price of the national emissions trading (BEHG § 10) in € per tonne, by calendar year.
+// Later years use the last known price unless the invoice states the CO2 costs.
+const CO2_PRICES: [(i32, Decimal); 6] = [
+    (2021, dec!(25)),
+    (2022, dec!(30)),
+    (2023, dec!(30)),
+    (2024, dec!(45)),
+    (2025, dec!(55)),
+    (2026, dec!(55)),
+];
+
+// Tiers of the CO2KostAufG annex for residential buildings: lower limit of the specific emissions
+// in kg CO2 per m² and year, and the landlord's part of the CO2 costs in percent
+const TIERS: [(Decimal, Decimal); 10] = [
+    (dec!(0), dec!(0)),
+    (dec!(12), dec!(10)),
+    (dec!(17), dec!(20)),
+    (dec!(22), dec!(30)),
+    (dec!(27), dec!(40)),
+    (dec!(32), dec!(50)),
+    (dec!(37), dec!(60)),
+    (dec!(42), dec!(70)),
+    (dec!(47), dec!(80)),
+    (dec!(52), dec!(95)),
+];
+
+// The split applies to billing periods beginning on or after this date (CO2KostAufG § 13)
+pub fn split_applies(start_date: NaiveDate) -> bool {
+    start_date >= NaiveDate::from_ymd_opt(2023, 1, 1).unwrap()
+}
+
+// Fuel burned and the CO2 emitted and paid for with it
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Co2Content {
+    pub fuel_quantity: Decimal,
+    pub emissions_kg: Decimal,
+    pub co2_cost: Decimal,
+}
+
+impl Co2Content {
+    pub fn add(&mut self, other: Co2Content) {
+        self.fuel_quantity += other.fuel_quantity;
+        self.emissions_kg += other.emissions_kg;
+        self.co2_cost += other.co2_cost;
+    }
+}
+
+// Landlord's part of the CO2 costs of a fuel cost type in a billing period
+#[derive(Debug, Clone, Serialize)]
+pub struct Co2Split {
+    pub cost_type_id: i32,
+    pub cost_type_name: String,
+    pub fuel_quantity: Decimal,
+    pub emissions_kg: Decimal,
+    pub co2_cost: Decimal,
+    pub heated_area_m2: Decimal,
+    pub specific_emissions: Decimal, // kg CO2 per m² heated area and year
+    pub tier: i32,                   // 1 to 10
+    pub landlord_percent: Decimal,
+    pub tenant_percent: Decimal,
+    pub landlord_amount: Decimal, // Exact; taken off the cost lines of the fuel
+}
+
+impl Co2Split {
+    // Tier of the specific emissions of a building for the CO2 content of a whole billing period
+    pub fn of(
+        cost_type_id: i32,
+        cost_type_name: &str,
+        content: Co2Content,
+        heated_area_m2: Decimal,
+        (start_date, end_date): (NaiveDate, NaiveDate),
+    ) -> Self {
+        let specific_emissions = content.emissions_kg / heated_area_m2 / period_years(start_date, end_date);
+        let tier = TIERS
+            .iter()
+            .rposition(|(lower_limit, _)| specific_emissions >= *lower_limit)
+            .unwrap_or(0);
+        let landlord_percent = TIERS[tier].1;
+
+        Co2Split {
+            cost_type_id,
+            cost_type_name: cost_type_name.to_string(),
+            fuel_quantity: content.fuel_quantity,
+            emissions_kg: content.emissions_kg,
+            co2_cost: content.co2_cost,
+            heated_area_m2,
+            specific_emissions,
+            tier: tier as i32 + 1,
+            landlord_percent,
+            tenant_percent: Decimal::ONE_HUNDRED - landlord_percent,
+            landlord_amount: content.co2_cost * landlord_percent / Decimal::ONE_HUNDRED,
+        }
+    }
+}
+
+// BEHG CO2 price in € per tonne on the given day
+pub fn co2_price(date: NaiveDate) -> Decimal {
+    CO2_PRICES
+        .iter()
+        .rev()
+        .find(|(year, _)| *year <= date.year())
+        .map(|(_, price)| *price)
+        .unwrap_or_default()
+}
+
+// Average CO2 price of the days from `from` to `to`, both inclusive
+fn average_co2_price(from: NaiveDate, to: NaiveDate) -> Decimal {
+    let days = (to - from).num_days() + 1;
+    if days <= 0 {
+        return co2_price(from);
+    }
+    let total: Decimal = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(co2_price)
+        .sum();
+
+    total / Decimal::from(days)
+}
+
+// CO2 content of fuel burned from the beginning of `from` to the end of `to`,
+// priced with the CO2 price of those days
+pub fn fuel_co2(fuel_quantity: Decimal, emission_factor: Decimal, from: NaiveDate, to: NaiveDate) -> Co2Content {
+    let emissions_kg = fuel_quantity * emission_factor;
+
+    Co2Content {
+        fuel_quantity,
+        emissions_kg,
+        co2_cost: emissions_kg / dec!(1000) * average_co2_price(from, to),
+    }
+}
+
+// CO2 content of the apportioned part of a fuel invoice. The invoice's own factor and CO2 costs
+// take precedence over the cost type's factor and the BEHG price. None without a fuel quantity.
+pub fn invoice_co2(
+    fixed_cost: &FixedCost,
+    emission_factor: Decimal,
+    apportionment: &Apportionment,
+) -> Option<Co2Content> {
+    let fuel_quantity = fixed_cost.fuel_quantity?;
+    let emission_factor = fixed_cost
+        .co2_emission_factor
+        .map(decimal::from_f32)
+        .unwrap_or(emission_factor);
+    let invoice = fuel_co2(
+        fuel_quantity,
+        emission_factor,
+        apportionment.invoice_start,
+        apportionment.invoice_end,
+    );
+    let share = Decimal::from(apportionment.apportioned_days) / Decimal::from(apportionment.invoice_days);
+
+    Some(Co2Content {
+        fuel_quantity: invoice.fuel_quantity * share,
+        emissions_kg: invoice.emissions_kg * share,
+        co2_cost: fixed_cost.co2_cost.unwrap_or(invoice.co2_cost) * share,
+    })
+}
+
+// CO2 content of the oil taken from a tank, each lot priced with the CO2 price of its delivery
+pub fn oil_co2(fuel_cost: &FuelCost, emission_factor: Decimal) -> Co2Content {
+    let mut content = Co2Content::default();
+    for lot in &fuel_cost.lots {
+        let delivery_date = lot.delivery_date.unwrap_or(fuel_cost.start_date);
+        content.add(fuel_co2(lot.litres, emission_factor, delivery_date, delivery_date));
+    }
+
+    content
+}
+
+// Length of a billing period in years; a period of twelve calendar months counts as one year
+fn period_years(start_date: NaiveDate, end_date: NaiveDate) -> Decimal {
+    let full_year = start_date
+        .checked_add_months(chrono::Months::new(12))
+        .is_some_and(|next_start| end_date.succ_opt() == Some(next_start));
+    if full_year {
+        Decimal::ONE
+    } else {
+        Decimal::from((end_date - start_date).num_days() + 1) / dec!(365)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    // Split of 100 m² heated area over 2024 for the given emissions and CO2 costs of 1000 €
+    fn split_2024(emissions_kg: Decimal) -> Co2Split {
+        let content = Co2Content {
+            fuel_quantity: Decimal::ZERO,
+            emissions_kg,
+            co2_cost: dec!(1000),
+        };
+        Co2Split::of(1, "Gas", content, dec!(100), (date(2024, 1, 1), date(2024, 12, 31)))
+    }
+
+    #[test]
+    fn tiers_start_at_their_lower_limit() {
+        let cases = [
+            (dec!(1199), 1, dec!(0)),
+            (dec!(1200), 2, dec!(10)),
+            (dec!(2699), 4, dec!(30)),
+            (dec!(2700), 5, dec!(40)),
+            (dec!(5199), 9, dec!(80)),
+            (dec!(5200), 10, dec!(95)),
+            (dec!(9000), 10, dec!(95)),
+        ];
+        for (emissions_kg, tier, landlord_percent) in cases {
+            let split = split_2024(emissions_kg);
+            assert_eq!(split.tier, tier, "{} kg", emissions_kg);
+            assert_eq!(split.landlord_percent, landlord_percent, "{} kg", emissions_kg);
+        }
+    }
+
+    #[test]
+    fn split_divides_the_co2_costs() {
+        let split = split_2024(dec!(3500));
+
+        assert_eq!(split.specific_emissions, dec!(35));
+        assert_eq!(split.tier, 6);
+        assert_eq!(split.tenant_percent, dec!(50));
+        assert_eq!(split.landlord_amount, dec!(500));
+    }
+
+    #[test]
+    fn partial_periods_are_scaled_to_a_year() {
+        let content = Co2Content {
+            fuel_quantity: Decimal::ZERO,
+            emissions_kg: dec!(600),
+            co2_cost: dec!(100),
+        };
+        // 600 kg in 182 days are 12.03 kg per m² and year
+        let split = Co2Split::of(1, "Gas", content, dec!(100), (date(2024, 1, 1), date(2024, 6, 30)));
+        assert_eq!(split.tier, 2);
+    }
+
+    #[test]
+    fn co2_price_follows_the_calendar_year() {
+        assert_eq!(co2_price(date(2020, 6, 1)), Decimal::ZERO);
+        assert_eq!(co2_price(date(2024, 6, 1)), dec!(45));
+        assert_eq!(co2_price(date(2030, 6, 1)), dec!(55));
+    }
+}
@@ -0,0 +1,306 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use log::warn;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::cost::CostType;
+use crate::models::decimal;
+use crate::models::heating::{FuelType, FuelUnit, HeatingSystem, HeatingSystemFuel};
+use crate::models::meter::Meter;
+use crate::schema::{cost_types, heating_system_fuels, meters};
+use crate::services::{heating, hot_water, oil_tank};
+
+// Where the fuel quantity of the period comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FuelSource {
+    FuelMeter,
+    BurnerHours,
+    OilTanks,
+}
+
+// Errors that can occur while determining the fuel of a heating system
+#[derive(Debug, Error)]
+pub enum FuelError {
+    #[error("database error: {0}")]
+    Database(#[from] diesel::result::Error),
+    #[error("{0}")]
+    Undetermined(String), // Fuel version or quantity missing, or not convertible
+}
+
+// Fuel a heating system burned in a period. If the fuel versions change within the period,
+// their values are averaged weighted by the days each version applies. Versions lacking an
+// optional value are left out of its average and reported in `warnings`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodFuel {
+    pub fuel_type: FuelType, // Of the last version in the period
+    pub fuel_unit: FuelUnit,
+    pub calorific_value: Decimal,             // Hi in kWh per fuel unit
+    pub density: Option<Decimal>,             // kg per fuel unit
+    pub co2_emission_factor: Option<Decimal>, // kg CO2 per kWh (Hi)
+    pub efficiency: Option<Decimal>,          // Useful heat per kWh of fuel
+    pub version_ids: Vec<i32>,                // Fuel versions in effect, oldest first
+    pub warnings: Vec<String>,
+}
+
+impl PeriodFuel {
+    // A quantity measured in `unit` in fuel units, None if it can't be converted
+    pub fn fuel_units(&self, quantity: Decimal, unit: FuelUnit) -> Option<Decimal> {
+        use FuelUnit::*;
+
+        match (unit, self.fuel_unit) {
+            (from, to) if from == to => Some(quantity),
+            (KilowattHour, _) if self.calorific_value > Decimal::ZERO => Some(quantity / self.calorific_value),
+            (Litre, CubicMetre) => Some(quantity / dec!(1000)),
+            (CubicMetre, Litre) => Some(quantity * dec!(1000)),
+            (Kilogram, Litre | CubicMetre) => self
+                .density
+                .filter(|density| *density > Decimal::ZERO)
+                .map(|density| quantity / density),
+            _ => None,
+        }
+    }
+
+    // Energy content of a fuel quantity in kWh (Hi)
+    pub fn energy_kwh(&self, fuel_quantity: Decimal) -> Decimal {
+        fuel_quantity * self.calorific_value
+    }
+
+    // kg CO2 emitted per fuel unit
+    pub fn co2_per_fuel_unit(&self) -> Option<Decimal> {
+        self.co2_emission_factor
+            .map(|factor| factor * self.calorific_value)
+    }
+}
+
+// Energy content of the fuel a heating system burned in a period, e.g. for consumption information
+#[derive(Debug, Clone, Serialize)]
+pub struct FuelEnergy {
+    pub heating_system_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub fuel: PeriodFuel,
+    pub fuel_source: FuelSource,
+    pub fuel_quantity: Decimal,           // In the fuel unit
+    pub energy_kwh: Decimal,              // Fuel quantity × Hi
+    pub useful_heat_kwh: Option<Decimal>, // Energy × efficiency
+    pub emissions_kg: Option<Decimal>,    // Energy × CO2 factor
+}
+
+// Fuel versions of a heating system, oldest first
+pub fn load_fuels(
+    conn: &mut SqliteConnection,
+    heating_system_id: i32,
+) -> Result<Vec<HeatingSystemFuel>, diesel::result::Error> {
+    heating_system_fuels::table
+        .filter(heating_system_fuels::heating_system_id.eq(heating_system_id))
+        .order_by(heating_system_fuels::valid_from.asc())
+        .select(HeatingSystemFuel::as_select())
+        .load::<HeatingSystemFuel>(conn)
+}
+
+// Day before the next fuel version of the same heating system, if any
+pub fn valid_to(fuels: &[HeatingSystemFuel], fuel: &HeatingSystemFuel) -> Option<NaiveDate> {
+    fuels
+        .iter()
+        .filter(|other| other.heating_system_id == fuel.heating_system_id && other.valid_from > fuel.valid_from)
+        .map(|other| other.valid_from)
+        .min()
+        .and_then(|next_valid_from| next_valid_from.pred_opt())
+}
+
+// Fuel in effect from the beginning of `start_date` to the end of `end_date`
+pub fn period_fuel(
+    system: &HeatingSystem,
+    fuels: &[HeatingSystemFuel],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<PeriodFuel, FuelError> {
+    if fuels.iter().all(|fuel| fuel.valid_from > start_date) {
+        return Err(FuelError::Undetermined(format!(
+            "Heating system '{}' has no fuel version valid on {}",
+            system.name, start_date
+        )));
+    }
+
+    // Days of the period each version applies
+    let windows: Vec<(Decimal, &HeatingSystemFuel)> = fuels
+        .iter()
+        .filter_map(|fuel| {
+            let from = fuel.valid_from.max(start_date);
+            let to = valid_to(fuels, fuel).map_or(end_date, |valid_to| valid_to.min(end_date));
+            (from <= to).then(|| (Decimal::from((to - from).num_days() + 1), fuel))
+        })
+        .collect();
+    let total_days: Decimal = windows.iter().map(|(days, _)| *days).sum();
+    let (_, last) = windows[windows.len() - 1];
+
+    let fuel_unit = FuelUnit::from(last.fuel_unit.clone());
+    if windows
+        .iter()
+        .any(|(_, fuel)| FuelUnit::from(fuel.fuel_unit.clone()) != fuel_unit)
+    {
+        return Err(FuelError::Undetermined(format!(
+            "The fuel unit of heating system '{}' changes between {} and {}",
+            system.name, start_date, end_date
+        )));
+    }
+
+    // Average over the versions having the value, None if none has it
+    let mut warnings = Vec::new();
+    let mut average = |label: &str, value: fn(&HeatingSystemFuel) -> Option<f32>| -> Option<Decimal> {
+        let mut sum = Decimal::ZERO;
+        let mut days_with_value = Decimal::ZERO;
+        let mut days_without_value = Decimal::ZERO;
+        for (days, fuel) in &windows {
+            match value(fuel) {
+                Some(value) => {
+                    sum += *days * decimal::from_f32(value);
+                    days_with_value += *days;
+                }
+                None => days_without_value += *days,
+            }
+        }
+        if days_with_value.is_zero() {
+            return None;
+        }
+        if !days_without_value.is_zero() {
+            let message = format!(
+                "Fuel of heating system '{}' has no {} on {} of {} days from {} to {}, the {} of the other days is used",
+                system.name, label, days_without_value, total_days, start_date, end_date, label
+            );
+            warn!("{}", message);
+            warnings.push(message);
+        }
+        Some(sum / days_with_value)
+    };
+
+    let calorific_value = average("calorific value", |fuel| Some(fuel.calorific_value)).unwrap_or_default();
+    let density = average("density", |fuel| fuel.density);
+    let co2_emission_factor = average("CO2 emission factor", |fuel| fuel.co2_emission_factor);
+    let efficiency = average("efficiency", |fuel| fuel.efficiency);
+
+    Ok(PeriodFuel {
+        fuel_type: FuelType::from(last.fuel_type.clone()),
+        fuel_unit,
+        calorific_value,
+        density,
+        co2_emission_factor,
+        efficiency,
+        version_ids: windows.iter().filter_map(|(_, fuel)| fuel.id).collect(),
+        warnings,
+    })
+}
+
+// Fuel of a heating system in effect during a period, loading its versions
+pub fn fuel_for_period(
+    conn: &mut SqliteConnection,
+    system: &HeatingSystem,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<PeriodFuel, FuelError> {
+    let fuels = load_fuels(conn, system.id.unwrap_or(0))?;
+    period_fuel(system, &fuels, start_date, end_date)
+}
+
+// Fuel burned by a heating system from the beginning of `start_date` to the end of `end_date`,
+// in the fuel unit. Measured by the fuel meter, else derived from burner hours or oil tanks.
+pub fn fuel_burned(
+    conn: &mut SqliteConnection,
+    system: &HeatingSystem,
+    fuel_cost_type: &CostType,
+    fuel: &PeriodFuel,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<(FuelSource, Decimal), FuelError> {
+    let (fuel_source, quantity, unit) = match system.fuel_meter_id {
+        Some(meter_id) => {
+            let meter = meters::table
+                .filter(meters::id.eq(meter_id))
+                .select(Meter::as_select())
+                .first::<Meter>(conn)?;
+            let Some(unit) = FuelUnit::parse(&meter.unit) else {
+                return Err(FuelError::Undetermined(format!(
+                    "Fuel meter '{}' of heating system '{}' measures '{}', not l, m³, kg or kWh",
+                    meter.name, system.name, meter.unit
+                )));
+            };
+            match hot_water::meter_consumption(conn, &meter, start_date, end_date)? {
+                Some(quantity) => (FuelSource::FuelMeter, quantity, unit),
+                None => {
+                    return Err(FuelError::Undetermined(format!(
+                        "Not enough readings of the fuel meter of heating system '{}' from {} to {}",
+                        system.name, start_date, end_date
+                    )))
+                }
+            }
+        }
+        None if fuel_cost_type.uses_burner_hours => (
+            FuelSource::BurnerHours,
+            heating::oil_consumption(conn, system.building_id, start_date, end_date)?.litres,
+            FuelUnit::Litre,
+        ),
+        None => {
+            // Tanks left out are reported by the allocation of the fuel cost type
+            let costs = oil_tank::fuel_costs_for_cost_type(
+                conn,
+                system.fuel_cost_type_id,
+                start_date,
+                end_date,
+                &mut Vec::new(),
+            )?;
+            if costs.is_empty() {
+                return Err(FuelError::Undetermined(format!(
+                    "No fuel quantity for heating system '{}': set a fuel meter, use burner hours or assign an oil tank to '{}'",
+                    system.name, fuel_cost_type.name
+                )));
+            }
+            (
+                FuelSource::OilTanks,
+                costs.iter().map(|cost| cost.consumed_litres).sum(),
+                FuelUnit::Litre,
+            )
+        }
+    };
+
+    match fuel.fuel_units(quantity, unit) {
+        Some(fuel_quantity) => Ok((fuel_source, fuel_quantity)),
+        None => Err(FuelError::Undetermined(format!(
+            "Fuel of heating system '{}' is measured in {} and can't be converted into {}",
+            system.name,
+            unit.label(),
+            fuel.fuel_unit.label()
+        ))),
+    }
+}
+
+// Fuel quantity, energy content, useful heat and emissions of a heating system in a period
+pub fn fuel_energy(
+    conn: &mut SqliteConnection,
+    system: &HeatingSystem,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<FuelEnergy, FuelError> {
+    let fuel_cost_type = cost_types::table
+        .filter(cost_types::id.eq(system.fuel_cost_type_id))
+        .select(CostType::as_select())
+        .first::<CostType>(conn)?;
+    let fuel = fuel_for_period(conn, system, start_date, end_date)?;
+    let (fuel_source, fuel_quantity) = fuel_burned(conn, system, &fuel_cost_type, &fuel, start_date, end_date)?;
+    let energy_kwh = fuel.energy_kwh(fuel_quantity);
+
+    Ok(FuelEnergy {
+        heating_system_id: system.id.unwrap_or(0),
+        start_date,
+        end_date,
+        fuel_source,
+        fuel_quantity,
+        energy_kwh,
+        useful_heat_kwh: fuel.efficiency.map(|efficiency| energy_kwh * efficiency),
+        emissions_kg: fuel.co2_emission_factor.map(|factor| energy_kwh * factor),
+        fuel,
+    })
+}
@@ -0,0 +1,170 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use log::warn;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::decimal;
+use crate::models::heating::BurnerCalibration;
+use crate::models::meter::{InterpolationMethod, Meter, BURNER_HOURS_METER_TYPE};
+use crate::schema::{burner_calibrations, meters};
+use crate::services::interpolation::{self, DayWeighting};
+
+// Operating hours of one burner-hour meter converted to litres of oil
+#[derive(Debug, Clone, Serialize)]
+pub struct StageConsumption {
+    pub meter_id: i32,
+    pub stage: i32,
+    pub hours: Decimal,
+    pub litres: Decimal,
+    pub estimated: bool,         // Hours based on interpolated meter values
+    pub uncalibrated_hours: Decimal, // Hours before the first calibration of the stage, not converted
+}
+
+// Oil burned within a date range, derived from the burner-hour meters
+#[derive(Debug, Clone, Serialize)]
+pub struct OilConsumption {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub litres: Decimal,
+    pub stages: Vec<StageConsumption>,
+    pub warnings: Vec<String>, // Hours left out of the litres: missing readings or calibrations
+}
+
+// Oil consumption of a building's burner from the beginning of `start_date` to the end of `end_date`.
+// The hours of each stage are converted with the calibration version valid at the time.
+pub fn oil_consumption(
+    conn: &mut SqliteConnection,
+    building_id: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<OilConsumption, diesel::result::Error> {
+    let burner_meters = meters::table
+        .filter(meters::meter_type.eq(BURNER_HOURS_METER_TYPE))
+        .filter(meters::building_id.eq(building_id))
+        .select(Meter::as_select())
+        .load::<Meter>(conn)?;
+    let calibrations = burner_calibrations::table
+        .filter(burner_calibrations::building_id.eq(building_id))
+        .order(burner_calibrations::valid_from.asc())
+        .load::<BurnerCalibration>(conn)?;
+
+    let degree_days = interpolation::load_degree_days(conn, building_id)?;
+
+    let mut stages = Vec::new();
+    let mut warnings = Vec::new();
+    let mut report = |message: String| {
+        warn!("{}", message);
+        warnings.push(message);
+    };
+    for meter in burner_meters {
+        let (Some(meter_id), Some(stage)) = (meter.id, meter.burner_stage) else {
+            report(format!("Burner-hour meter '{}' has no burner stage assigned", meter.name));
+            continue;
+        };
+        let weighting = DayWeighting::for_method(InterpolationMethod::from(meter.interpolation_method), &degree_days);
+        let readings = interpolation::load_readings(conn, meter_id)?;
+
+        let mut consumption = StageConsumption {
+            meter_id,
+            stage,
+            hours: Decimal::ZERO,
+            litres: Decimal::ZERO,
+            estimated: false,
+            uncalibrated_hours: Decimal::ZERO,
+        };
+
+        for (from, to, calibration) in calibration_windows(&calibrations, stage, start_date, end_date) {
+            let Some(hours) = interpolation::consumption_between(&readings, from, to, &weighting) else {
+                report(format!(
+                    "Not enough readings to determine the operating hours of meter '{}' from {} to {}",
+                    meter.name, from, to
+                ));
+                continue;
+            };
+
+            consumption.hours += hours.value;
+            consumption.estimated |= hours.estimated();
+            match calibration {
+                Some(calibration) => {
+                    consumption.litres += hours.value * decimal::from_f32(calibration.litres_per_hour)
+                }
+                None => consumption.uncalibrated_hours += hours.value,
+            }
+        }
+
+        if consumption.uncalibrated_hours > Decimal::ZERO {
+            report(format!(
+                "No calibration for burner stage {} before {}, {:.1} hours of meter '{}' not converted",
+                stage,
+                end_date,
+                consumption.uncalibrated_hours.round_dp(1),
+                meter.name
+            ));
+        }
+        stages.push(consumption);
+    }
+
+    Ok(OilConsumption {
+        start_date,
+        end_date,
+        litres: stages.iter().map(|stage| stage.litres).sum(),
+        stages,
+        warnings,
+    })
+}
+
+// Day before the next calibration version of the same stage of the building's burner, if any
+pub fn valid_to(calibrations: &[BurnerCalibration], calibration: &BurnerCalibration) -> Option<NaiveDate> {
+    calibrations
+        .iter()
+        .filter(|other| {
+            other.building_id == calibration.building_id
+                && other.stage == calibration.stage
+                && other.valid_from > calibration.valid_from
+        })
+        .map(|other| other.valid_from)
+        .min()
+        .and_then(|next_valid_from| next_valid_from.pred_opt())
+}
+
+// Split a date range at the calibration versions of a stage. Days before the
+// first version are returned without a calibration.
+fn calibration_windows(
+    calibrations: &[BurnerCalibration],
+    stage: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Vec<(NaiveDate, NaiveDate, Option<&BurnerCalibration>)> {
+    let stage_calibrations: Vec<&BurnerCalibration> = calibrations
+        .iter()
+        .filter(|calibration| calibration.stage == stage)
+        .collect();
+
+    let mut windows = Vec::new();
+    let mut from = start_date;
+    while from <= end_date {
+        let current = stage_calibrations
+            .iter()
+            .rev()
+            .find(|calibration| calibration.valid_from <= from)
+            .copied();
+        let next_valid_from = stage_calibrations
+            .iter()
+            .map(|calibration| calibration.valid_from)
+            .find(|valid_from| *valid_from > from);
+
+        let to = match next_valid_from.and_then(|date| date.pred_opt()) {
+            Some(day_before_next) if day_before_next < end_date => day_before_next,
+            _ => end_date,
+        };
+        windows.push((from, to, current));
+
+        match to.succ_opt() {
+            Some(next_from) => from = next_from,
+            None => break,
+        }
+    }
+
+    windows
+}
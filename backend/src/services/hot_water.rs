@@ -0,0 +1,199 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::cost::CostType;
+use crate::models::decimal;
+use crate::models::heating::{FuelUnit, HeatingSystem};
+use crate::models::meter::{InterpolationMethod, Meter};
+use crate::schema::meters;
+use crate::services::fuel::{self, FuelError, FuelSource};
+use crate::services::interpolation::{self, DayWeighting};
+
+// How the heat quantity of the hot water was determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotWaterMethod {
+    HeatMeter, // Measured, HeizkostenV § 9 (2) sentence 1
+    Formula,   // 2.5 kWh/(m³·K) × V × (tw − 10 °C), § 9 (2) sentence 2
+}
+
+impl HotWaterMethod {
+    // Label used on the printed statement
+    pub fn label(&self) -> &'static str {
+        match self {
+            HotWaterMethod::HeatMeter => "Wärmezähler",
+            HotWaterMethod::Formula => "Formel nach § 9 Abs. 2 HeizkostenV",
+        }
+    }
+}
+
+// Split of a combined heating system's fuel into space heating and hot water (HeizkostenV § 9)
+#[derive(Debug, Clone, Serialize)]
+pub struct HotWaterSplit {
+    pub heating_system_id: i32,
+    pub fuel_cost_type_id: i32,
+    pub fuel_cost_type_name: String,
+    pub heating_cost_type_id: i32,
+    pub hot_water_cost_type_id: i32,
+    pub method: HotWaterMethod,
+    pub hot_water_volume: Option<Decimal>,      // V in m³, formula only
+    pub hot_water_temperature: Option<Decimal>, // tw in °C, formula only
+    pub heat_quantity: Decimal,                 // Q in kWh
+    pub fuel_unit: FuelUnit,
+    pub calorific_value: Decimal,               // Hi in kWh per fuel unit
+    pub hot_water_fuel: Decimal,                // B = Q / Hi
+    pub fuel_source: FuelSource,
+    pub fuel_quantity: Decimal, // Fuel burned in the period
+    pub hot_water_percent: Decimal,
+}
+
+impl HotWaterSplit {
+    pub fn hot_water_share(&self) -> Decimal {
+        self.hot_water_percent / Decimal::ONE_HUNDRED
+    }
+
+    // Percentage of the fuel costs a derived cost type receives
+    pub fn percent_for(&self, cost_type_id: i32) -> Decimal {
+        if cost_type_id == self.hot_water_cost_type_id {
+            self.hot_water_percent
+        } else {
+            Decimal::ONE_HUNDRED - self.hot_water_percent
+        }
+    }
+}
+
+// Errors that can occur while splitting the fuel of a heating system
+#[derive(Debug, Error)]
+pub enum HotWaterError {
+    #[error("database error: {0}")]
+    Database(#[from] diesel::result::Error),
+    #[error("{0}")]
+    Undetermined(String), // Input missing or implausible, the fuel costs can't be split
+}
+
+impl From<FuelError> for HotWaterError {
+    fn from(error: FuelError) -> Self {
+        match error {
+            FuelError::Database(e) => HotWaterError::Database(e),
+            FuelError::Undetermined(message) => HotWaterError::Undetermined(message),
+        }
+    }
+}
+
+// Heat quantity of the hot water by the § 9 (2) formula: 2.5 kWh/(m³·K) × V × (tw − 10 °C)
+pub fn formula_heat_quantity(volume: Decimal, temperature: Decimal) -> Decimal {
+    dec!(2.5) * volume * (temperature - dec!(10))
+}
+
+// Split the fuel burned from the beginning of `start_date` to the end of `end_date`.
+// `hot_water_volume` is the consumption of the meters bound to the hot water cost type in m³,
+// used with the formula if the system has no hot water heat meter.
+pub fn hot_water_split(
+    conn: &mut SqliteConnection,
+    system: &HeatingSystem,
+    fuel_cost_type: &CostType,
+    hot_water_volume: Option<Decimal>,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<HotWaterSplit, HotWaterError> {
+    // Q, measured or from the volume
+    let (method, heat_quantity, hot_water_temperature) = match system.hot_water_heat_meter_id {
+        Some(meter_id) => {
+            let meter = meters::table
+                .filter(meters::id.eq(meter_id))
+                .select(Meter::as_select())
+                .first::<Meter>(conn)?;
+            let Some(heat_quantity) = meter_consumption(conn, &meter, start_date, end_date)? else {
+                return Err(HotWaterError::Undetermined(format!(
+                    "Not enough readings of the hot water heat meter of heating system '{}' from {} to {}",
+                    system.name, start_date, end_date
+                )));
+            };
+            (HotWaterMethod::HeatMeter, heat_quantity, None)
+        }
+        None => {
+            let Some(volume) = hot_water_volume else {
+                return Err(HotWaterError::Undetermined(format!(
+                    "Heating system '{}' has neither a hot water heat meter nor hot water meters bound to its hot water cost type",
+                    system.name
+                )));
+            };
+            let temperature = decimal::from_f32(system.hot_water_temperature);
+            (
+                HotWaterMethod::Formula,
+                formula_heat_quantity(volume, temperature),
+                Some(temperature),
+            )
+        }
+    };
+
+    // Total fuel of the period in the unit of the calorific value
+    let fuel = fuel::fuel_for_period(conn, system, start_date, end_date)?;
+    let (fuel_source, fuel_quantity) =
+        fuel::fuel_burned(conn, system, fuel_cost_type, &fuel, start_date, end_date)?;
+    if fuel_quantity <= Decimal::ZERO {
+        return Err(HotWaterError::Undetermined(format!(
+            "Heating system '{}' burned no fuel from {} to {}",
+            system.name, start_date, end_date
+        )));
+    }
+
+    let calorific_value = fuel.calorific_value;
+    if calorific_value <= Decimal::ZERO {
+        return Err(HotWaterError::Undetermined(format!(
+            "Heating system '{}' has no calorific value",
+            system.name
+        )));
+    }
+
+    // B = Q / Hi, in percent of the fuel burned
+    let hot_water_fuel = heat_quantity / calorific_value;
+    let hot_water_percent = hot_water_fuel * Decimal::ONE_HUNDRED / fuel_quantity;
+    if hot_water_percent > Decimal::ONE_HUNDRED {
+        return Err(HotWaterError::Undetermined(format!(
+            "Hot water of heating system '{}' needs {:.1} of {:.1} fuel units burned from {} to {}",
+            system.name, hot_water_fuel, fuel_quantity, start_date, end_date
+        )));
+    }
+
+    Ok(HotWaterSplit {
+        heating_system_id: system.id.unwrap_or(0),
+        fuel_cost_type_id: system.fuel_cost_type_id,
+        fuel_cost_type_name: fuel_cost_type.name.clone(),
+        heating_cost_type_id: system.heating_cost_type_id,
+        hot_water_cost_type_id: system.hot_water_cost_type_id,
+        method,
+        hot_water_volume: hot_water_volume.filter(|_| method == HotWaterMethod::Formula),
+        hot_water_temperature,
+        heat_quantity,
+        fuel_unit: fuel.fuel_unit,
+        calorific_value,
+        hot_water_fuel,
+        fuel_source,
+        fuel_quantity,
+        hot_water_percent,
+    })
+}
+
+// Consumption of a single meter, None without enough readings
+pub fn meter_consumption(
+    conn: &mut SqliteConnection,
+    meter: &Meter,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<Option<Decimal>, diesel::result::Error> {
+    let readings = interpolation::load_readings(conn, meter.id.unwrap_or(0))?;
+    let degree_days = interpolation::load_degree_days(conn, meter.building_id)?;
+
+    Ok(interpolation::consumption_between(
+        &readings,
+        start_date,
+        end_date,
+        &DayWeighting::for_method(InterpolationMethod::from(meter.interpolation_method.clone()), &degree_days),
+    )
+    .map(|consumption| consumption.value))
+}
@@ -0,0 +1,352 @@
+use chrono::{Datelike, NaiveDate};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+
+use crate::models::decimal;
+use crate::models::heating::DegreeDayShare;
+use crate::models::meter::InterpolationMethod;
+use crate::models::meter_reading::MeterReading;
+use crate::schema::{degree_day_shares, meter_readings};
+
+// Monthly shares of the annual heating demand in per mille (Gradtagzahlen, January to December)
+pub const MONTHLY_DEGREE_DAY_SHARES: [Decimal; 12] = [
+    dec!(170), dec!(150), dec!(130), dec!(80), dec!(40), dec!(13.3),
+    dec!(13.3), dec!(13.3), dec!(30), dec!(80), dec!(120), dec!(160),
+];
+
+// Degree-day table of a building: the standard shares unless the building has its own
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DegreeDays {
+    pub monthly_per_mille: [Decimal; 12], // January to December
+    pub custom: bool,                     // Building's own table instead of the standard one
+}
+
+impl Default for DegreeDays {
+    fn default() -> Self {
+        DegreeDays {
+            monthly_per_mille: MONTHLY_DEGREE_DAY_SHARES,
+            custom: false,
+        }
+    }
+}
+
+impl DegreeDays {
+    // Share of a single day within its month's degree-day share
+    pub fn day_weight(&self, date: NaiveDate) -> Decimal {
+        let first_of_month = NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap();
+        let first_of_next_month = first_of_month
+            .checked_add_months(chrono::Months::new(1))
+            .unwrap();
+        let days_in_month = Decimal::from((first_of_next_month - first_of_month).num_days());
+
+        self.monthly_per_mille[date.month0() as usize] / days_in_month
+    }
+}
+
+// How the days between two dates are weighted when spreading consumption or a period over them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DayWeighting {
+    Calendar,               // Every day counts the same
+    DegreeDays(DegreeDays), // Days count with their month's degree-day share
+}
+
+impl DayWeighting {
+    // Weighting of a meter's interpolation method
+    pub fn for_method(method: InterpolationMethod, degree_days: &DegreeDays) -> Self {
+        match method {
+            InterpolationMethod::Linear => DayWeighting::Calendar,
+            InterpolationMethod::Seasonal => DayWeighting::DegreeDays(*degree_days),
+        }
+    }
+
+    pub fn day_weight(&self, date: NaiveDate) -> Decimal {
+        match self {
+            DayWeighting::Calendar => Decimal::ONE,
+            DayWeighting::DegreeDays(degree_days) => degree_days.day_weight(date),
+        }
+    }
+
+    // Sum of the day weights from `from` (inclusive) to `to` (exclusive)
+    pub fn weight_between(&self, from: NaiveDate, to: NaiveDate) -> Decimal {
+        match self {
+            DayWeighting::Calendar => Decimal::from((to - from).num_days()),
+            DayWeighting::DegreeDays(degree_days) => from
+                .iter_days()
+                .take_while(|date| *date < to)
+                .map(|date| degree_days.day_weight(date))
+                .sum(),
+        }
+    }
+}
+
+// Meter values are extrapolated at most this many days beyond the first or last reading
+pub const MAX_EXTRAPOLATION_DAYS: i64 = 31;
+
+// A reading taken at the beginning of its day
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ReadingPoint {
+    pub date: NaiveDate,
+    pub value: Decimal,
+}
+
+// Counter value of a meter at the beginning of a day, either read or estimated
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MeterState {
+    pub date: NaiveDate,
+    pub value: Decimal,
+    pub estimated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interpolated_between: Option<[ReadingPoint; 2]>, // Readings an estimate is based on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extrapolated_from: Option<[ReadingPoint; 2]>, // Readings an estimate outside of them continues
+}
+
+impl MeterState {
+    // Days between an extrapolated value and the nearest reading, 0 if not extrapolated
+    pub fn extrapolated_days(&self) -> i64 {
+        match self.extrapolated_from {
+            Some([first, _]) if self.date < first.date => (first.date - self.date).num_days(),
+            Some([_, last]) => (self.date - last.date).num_days(),
+            None => 0,
+        }
+    }
+}
+
+// Consumption between two meter states
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Consumption {
+    pub from: MeterState,
+    pub to: MeterState,
+    pub value: Decimal,
+}
+
+impl Consumption {
+    pub fn estimated(&self) -> bool {
+        self.from.estimated || self.to.estimated
+    }
+}
+
+// Readings of a meter ordered by date, keeping the first reading of each day
+pub fn load_readings(
+    conn: &mut SqliteConnection,
+    meter_id: i32,
+) -> Result<Vec<MeterReading>, diesel::result::Error> {
+    let mut readings = meter_readings::table
+        .filter(meter_readings::meter_id.eq(meter_id))
+        .order(meter_readings::reading_date.asc())
+        .load::<MeterReading>(conn)?;
+    readings.dedup_by_key(|reading| reading.reading_date.date());
+
+    Ok(readings)
+}
+
+// Degree-day table of a building; the standard table unless all twelve months are overridden
+pub fn load_degree_days(
+    conn: &mut SqliteConnection,
+    building_id: i32,
+) -> Result<DegreeDays, diesel::result::Error> {
+    let shares = degree_day_shares::table
+        .filter(degree_day_shares::building_id.eq(building_id))
+        .order(degree_day_shares::month.asc())
+        .load::<DegreeDayShare>(conn)?;
+
+    if shares.len() != 12 {
+        return Ok(DegreeDays::default());
+    }
+
+    let mut monthly_per_mille = MONTHLY_DEGREE_DAY_SHARES;
+    for share in &shares {
+        if let Some(slot) = monthly_per_mille.get_mut((share.month - 1) as usize) {
+            *slot = decimal::from_f32(share.per_mille);
+        }
+    }
+
+    Ok(DegreeDays {
+        monthly_per_mille,
+        custom: true,
+    })
+}
+
+// Counter value of a meter at the beginning of the given day, see `interpolate`
+pub fn state_at(
+    readings: &[MeterReading],
+    date: NaiveDate,
+    weighting: &DayWeighting,
+) -> Option<MeterState> {
+    interpolate(&reading_points(readings), date, weighting)
+}
+
+// Counter value at the beginning of the given day from (date, value) points ordered by date,
+// one per day. Points are taken at the beginning of their day. Between two points the value is
+// interpolated, up to MAX_EXTRAPOLATION_DAYS before the first or after the last point it is
+// extrapolated from the nearest two. Returns None if there are not enough points for an estimate.
+pub fn interpolate(
+    points: &[(NaiveDate, Decimal)],
+    date: NaiveDate,
+    weighting: &DayWeighting,
+) -> Option<MeterState> {
+    if let Some((_, value)) = points.iter().find(|(point_date, _)| *point_date == date) {
+        return Some(MeterState {
+            date,
+            value: *value,
+            estimated: false,
+            interpolated_between: None,
+            extrapolated_from: None,
+        });
+    }
+    if points.len() < 2 {
+        return None;
+    }
+
+    let (first_date, _) = points[0];
+    let (last_date, _) = points[points.len() - 1];
+    let outside_days = (first_date - date).num_days().max((date - last_date).num_days());
+    if outside_days > MAX_EXTRAPOLATION_DAYS {
+        return None;
+    }
+
+    // Surrounding points, or the nearest pair when the date lies outside all points
+    let next_index = points
+        .iter()
+        .position(|(point_date, _)| *point_date > date)
+        .unwrap_or(points.len())
+        .clamp(1, points.len() - 1);
+    let (previous_date, previous_value) = points[next_index - 1];
+    let (next_date, next_value) = points[next_index];
+
+    let interval_weight = weighting.weight_between(previous_date, next_date);
+    if interval_weight <= Decimal::ZERO {
+        return None;
+    }
+
+    // Negative for dates before the previous reading
+    let elapsed_weight = if date >= previous_date {
+        weighting.weight_between(previous_date, date)
+    } else {
+        -weighting.weight_between(date, previous_date)
+    };
+    let value = previous_value + (next_value - previous_value) * elapsed_weight / interval_weight;
+
+    let readings = [
+        ReadingPoint { date: previous_date, value: previous_value },
+        ReadingPoint { date: next_date, value: next_value },
+    ];
+    let extrapolated = outside_days > 0;
+
+    Some(MeterState {
+        date,
+        value,
+        estimated: true,
+        interpolated_between: (!extrapolated).then_some(readings),
+        extrapolated_from: extrapolated.then_some(readings),
+    })
+}
+
+// Consumption from the beginning of `from` to the end of `to`
+pub fn consumption_between(
+    readings: &[MeterReading],
+    from: NaiveDate,
+    to: NaiveDate,
+    weighting: &DayWeighting,
+) -> Option<Consumption> {
+    let points = reading_points(readings);
+    let start = interpolate(&points, from, weighting)?;
+    let end = interpolate(&points, to.succ_opt()?, weighting)?;
+
+    if end.value < start.value {
+        return None;
+    }
+
+    Some(Consumption {
+        from: start,
+        to: end,
+        value: end.value - start.value,
+    })
+}
+
+fn reading_points(readings: &[MeterReading]) -> Vec<(NaiveDate, Decimal)> {
+    readings
+        .iter()
+        .map(|reading| (reading.reading_date.date(), reading.value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    // Readings of 100 on Jan 1 and 200 on Jan 11, ten units a day
+    fn points() -> Vec<(NaiveDate, Decimal)> {
+        vec![(date(2024, 1, 1), dec!(100)), (date(2024, 1, 11), dec!(200))]
+    }
+
+    #[test]
+    fn reading_on_the_date_is_taken_as_is() {
+        let state = interpolate(&points(), date(2024, 1, 11), &DayWeighting::Calendar).unwrap();
+
+        assert_eq!(state.value, dec!(200));
+        assert!(!state.estimated);
+        assert!(state.interpolated_between.is_none());
+        assert!(state.extrapolated_from.is_none());
+    }
+
+    #[test]
+    fn value_between_readings_is_interpolated() {
+        let state = interpolate(&points(), date(2024, 1, 6), &DayWeighting::Calendar).unwrap();
+
+        assert_eq!(state.value, dec!(150));
+        assert!(state.estimated);
+        let [previous, next] = state.interpolated_between.unwrap();
+        assert_eq!((previous.date, next.date), (date(2024, 1, 1), date(2024, 1, 11)));
+        assert!(state.extrapolated_from.is_none());
+        assert_eq!(state.extrapolated_days(), 0);
+    }
+
+    #[test]
+    fn value_beyond_the_readings_is_extrapolated() {
+        let after = interpolate(&points(), date(2024, 1, 21), &DayWeighting::Calendar).unwrap();
+        assert_eq!(after.value, dec!(300));
+        assert!(after.interpolated_between.is_none());
+        assert!(after.extrapolated_from.is_some());
+        assert_eq!(after.extrapolated_days(), 10);
+
+        let before = interpolate(&points(), date(2023, 12, 27), &DayWeighting::Calendar).unwrap();
+        assert_eq!(before.value, dec!(50));
+        assert_eq!(before.extrapolated_days(), 5);
+    }
+
+    #[test]
+    fn extrapolation_is_limited() {
+        let last_reading = date(2024, 1, 11);
+        let limit = last_reading + chrono::Duration::days(MAX_EXTRAPOLATION_DAYS);
+
+        assert!(interpolate(&points(), limit, &DayWeighting::Calendar).is_some());
+        assert!(interpolate(&points(), limit.succ_opt().unwrap(), &DayWeighting::Calendar).is_none());
+    }
+
+    #[test]
+    fn single_reading_gives_no_estimate() {
+        let points = [(date(2024, 1, 1), dec!(100))];
+
+        assert!(interpolate(&points, date(2024, 1, 1), &DayWeighting::Calendar).is_some());
+        assert!(interpolate(&points, date(2024, 1, 2), &DayWeighting::Calendar).is_none());
+    }
+
+    #[test]
+    fn degree_days_shift_consumption_to_the_heating_season() {
+        let points = [(date(2024, 1, 1), dec!(0)), (date(2025, 1, 1), dec!(1000))];
+        let weighting = DayWeighting::DegreeDays(DegreeDays::default());
+        let mid_year = date(2024, 7, 1);
+
+        let calendar = interpolate(&points, mid_year, &DayWeighting::Calendar).unwrap();
+        let degree_days = interpolate(&points, mid_year, &weighting).unwrap();
+        assert!(degree_days.value > calendar.value);
+    }
+}
@@ -0,0 +1,294 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use log::warn;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::decimal;
+use crate::models::meter::{InterpolationMethod, Meter};
+use crate::models::meter_reading::MeterReading;
+use crate::schema::meters;
+use crate::services::interpolation::{self, DayWeighting, DegreeDays, MeterState};
+
+// Consumption of a single meter within a date range
+#[derive(Debug, Clone, Serialize)]
+pub struct MeterConsumption {
+    pub meter_id: i32,
+    pub name: String,
+    pub value: Decimal,
+    pub estimated: bool,
+    pub start: MeterState, // Counter values the consumption is computed from
+    pub end: MeterState,
+}
+
+// Consumption of a main meter minus the sum of its sub-meters
+#[derive(Debug, Clone, Serialize)]
+pub struct ResidualConsumption {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub main_meter: MeterConsumption,
+    pub sub_meters: Vec<MeterConsumption>,
+    pub sub_meter_total: Decimal,
+    pub residual: Decimal,
+    pub residual_percent: Option<Decimal>, // Residual in percent of the main meter's consumption
+    pub estimated: bool,
+    pub warnings: Vec<String>, // Sub-meters without readings or above the main meter; the residual is wrong
+    pub notes: Vec<String>,    // Residual above the meter's limit, e.g. a leak or an unmetered consumer
+}
+
+impl ResidualConsumption {
+    // Meter values estimated beyond the first or last reading. A single day is left out: a reading
+    // on the last day of a period is taken at the beginning of that day, one day before its end.
+    pub fn extrapolation_warnings(&self) -> Vec<String> {
+        std::iter::once(&self.main_meter)
+            .chain(&self.sub_meters)
+            .flat_map(|meter| [(meter, meter.start), (meter, meter.end)])
+            .filter(|(_, state)| state.extrapolated_days() > 1)
+            .map(|(meter, state)| {
+                format!(
+                    "Value of meter '{}' on {} extrapolated {} days beyond its readings",
+                    meter.name,
+                    state.date,
+                    state.extrapolated_days()
+                )
+            })
+            .collect()
+    }
+}
+
+// A meter with its readings
+pub struct MeterSeries {
+    pub meter_id: i32,
+    pub name: String,
+    pub method: InterpolationMethod,
+    pub degree_days: DegreeDays, // Degree-day table of the meter's building
+    pub readings: Vec<MeterReading>,
+}
+
+impl MeterSeries {
+    pub fn load(
+        conn: &mut SqliteConnection,
+        meter: &Meter,
+        degree_days: &DegreeDays,
+    ) -> Result<Self, diesel::result::Error> {
+        let meter_id = meter.id.unwrap_or(0);
+
+        Ok(MeterSeries {
+            meter_id,
+            name: meter.name.clone(),
+            method: InterpolationMethod::from(meter.interpolation_method.clone()),
+            degree_days: *degree_days,
+            readings: interpolation::load_readings(conn, meter_id)?,
+        })
+    }
+
+    // Consumption from the beginning of `from` to the end of `to`. Values between readings are
+    // estimated with the meter's interpolation method, or by degree days if requested.
+    pub fn consumption(&self, from: NaiveDate, to: NaiveDate, degree_day_weighting: bool) -> Option<MeterConsumption> {
+        let weighting = if degree_day_weighting {
+            DayWeighting::DegreeDays(self.degree_days)
+        } else {
+            DayWeighting::for_method(self.method, &self.degree_days)
+        };
+
+        interpolation::consumption_between(&self.readings, from, to, &weighting).map(|consumption| {
+            MeterConsumption {
+                meter_id: self.meter_id,
+                name: self.name.clone(),
+                value: consumption.value,
+                estimated: consumption.estimated(),
+                start: consumption.from,
+                end: consumption.to,
+            }
+        })
+    }
+}
+
+// A meter together with the sub-meters installed directly behind it.
+// Without sub-meters the residual is simply the meter's own consumption.
+pub struct MeterTree {
+    pub main_meter: MeterSeries,
+    pub sub_meters: Vec<MeterSeries>,
+    pub residual_warning_percent: Option<Decimal>,
+}
+
+impl MeterTree {
+    pub fn load(
+        conn: &mut SqliteConnection,
+        meter: &Meter,
+        degree_days: &DegreeDays,
+    ) -> Result<Self, diesel::result::Error> {
+        let main_meter = MeterSeries::load(conn, meter, degree_days)?;
+
+        let mut sub_meters = Vec::new();
+        for sub_meter in meters::table
+            .filter(meters::parent_meter_id.eq(main_meter.meter_id))
+            .select(Meter::as_select())
+            .load::<Meter>(conn)?
+        {
+            sub_meters.push(MeterSeries::load(conn, &sub_meter, degree_days)?);
+        }
+
+        Ok(MeterTree {
+            main_meter,
+            sub_meters,
+            residual_warning_percent: meter.residual_warning_percent.map(decimal::from_f32),
+        })
+    }
+
+    // Residual from the beginning of `from` to the end of `to`. Returns None if the
+    // main meter's consumption can't be determined; sub-meters without enough readings
+    // are left out with a warning, their consumption stays in the residual.
+    pub fn residual(&self, from: NaiveDate, to: NaiveDate, degree_day_weighting: bool) -> Option<ResidualConsumption> {
+        let main_meter = self.main_meter.consumption(from, to, degree_day_weighting)?;
+        let mut warnings = Vec::new();
+        let mut notes = Vec::new();
+
+        let mut sub_meters = Vec::new();
+        for series in &self.sub_meters {
+            match series.consumption(from, to, degree_day_weighting) {
+                Some(consumption) => sub_meters.push(consumption),
+                None => warnings.push(format!(
+                    "Not enough readings to determine the consumption of sub-meter '{}' from {} to {}, it is counted in the residual of '{}'",
+                    series.name, from, to, main_meter.name
+                )),
+            }
+        }
+
+        let sub_meter_total: Decimal = sub_meters.iter().map(|sub_meter| sub_meter.value).sum();
+        let residual = main_meter.value - sub_meter_total;
+        let residual_percent = if main_meter.value > Decimal::ZERO {
+            Some(residual / main_meter.value * Decimal::ONE_HUNDRED)
+        } else {
+            None
+        };
+
+        if !sub_meters.is_empty() && residual < Decimal::ZERO {
+            warnings.push(format!(
+                "Sub-meters of '{}' add up to {:.2} from {} to {}, more than the main meter's {:.2}",
+                main_meter.name,
+                sub_meter_total.round_dp(2),
+                from,
+                to,
+                main_meter.value.round_dp(2)
+            ));
+        }
+        if let (Some(limit), Some(percent)) = (self.residual_warning_percent, residual_percent) {
+            if !sub_meters.is_empty() && percent > limit {
+                notes.push(format!(
+                    "Unmeasured residual of '{}' is {:.1} % of its consumption from {} to {}, above the limit of {:.1} %",
+                    main_meter.name,
+                    percent.round_dp(1),
+                    from,
+                    to,
+                    limit.round_dp(1)
+                ));
+            }
+        }
+
+        for message in warnings.iter().chain(&notes) {
+            warn!("{}", message);
+        }
+
+        Some(ResidualConsumption {
+            start_date: from,
+            end_date: to,
+            estimated: main_meter.estimated || sub_meters.iter().any(|sub_meter| sub_meter.estimated),
+            main_meter,
+            sub_meters,
+            sub_meter_total,
+            residual,
+            residual_percent,
+            warnings,
+            notes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::connection::SimpleConnection;
+    use rust_decimal_macros::dec;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    // Main meter 'Haus' with a residual limit of 10 % and the sub-meters 'OG' and 'Garten'
+    fn residual_2024(readings: &str) -> ResidualConsumption {
+        let conn = &mut crate::db::test_connection();
+        conn.batch_execute(
+            "
+            INSERT INTO meters (id, name, meter_type, unit, assignment_type, residual_property_unit_id,
+                residual_warning_percent)
+                VALUES (1, 'Haus', 'water', 'm3', 'common', 1, 10);
+            INSERT INTO meters (id, name, meter_type, unit, assignment_type, property_unit_id, parent_meter_id)
+                VALUES (2, 'OG', 'water', 'm3', 'unit', 2, 1), (3, 'Garten', 'water', 'm3', 'common', NULL, 1);
+            ",
+        )
+        .unwrap();
+        conn.batch_execute(readings).unwrap();
+        let main_meter = meters::table
+            .filter(meters::id.eq(1))
+            .select(Meter::as_select())
+            .first::<Meter>(conn)
+            .unwrap();
+
+        let tree = MeterTree::load(conn, &main_meter, &DegreeDays::default()).unwrap();
+        tree.residual(date(2024, 1, 1), date(2024, 12, 31), false).unwrap()
+    }
+
+    #[test]
+    fn residual_is_the_main_meter_minus_its_sub_meters() {
+        let residual = residual_2024(
+            "
+            INSERT INTO meter_readings (meter_id, reading_date, value) VALUES
+                (1, '2024-01-01 00:00:00', '1000'), (1, '2025-01-01 00:00:00', '1100'),
+                (2, '2024-01-01 00:00:00', '0'), (2, '2025-01-01 00:00:00', '60'),
+                (3, '2024-01-01 00:00:00', '0'), (3, '2025-01-01 00:00:00', '10');
+            ",
+        );
+
+        assert_eq!(residual.main_meter.value, dec!(100));
+        assert_eq!(residual.sub_meter_total, dec!(70));
+        assert_eq!(residual.residual, dec!(30));
+        assert_eq!(residual.residual_percent, Some(dec!(30)));
+        assert!(residual.warnings.is_empty());
+        // 30 % unmeasured is above the limit of 10 %
+        assert_eq!(residual.notes.len(), 1);
+    }
+
+    #[test]
+    fn sub_meters_above_the_main_meter_are_a_warning() {
+        let residual = residual_2024(
+            "
+            INSERT INTO meter_readings (meter_id, reading_date, value) VALUES
+                (1, '2024-01-01 00:00:00', '1000'), (1, '2025-01-01 00:00:00', '1100'),
+                (2, '2024-01-01 00:00:00', '0'), (2, '2025-01-01 00:00:00', '90'),
+                (3, '2024-01-01 00:00:00', '0'), (3, '2025-01-01 00:00:00', '20');
+            ",
+        );
+
+        assert_eq!(residual.residual, dec!(-10));
+        assert_eq!(residual.warnings.len(), 1);
+        assert!(residual.warnings[0].contains("more than the main meter"));
+    }
+
+    #[test]
+    fn sub_meter_without_readings_stays_in_the_residual() {
+        let residual = residual_2024(
+            "
+            INSERT INTO meter_readings (meter_id, reading_date, value) VALUES
+                (1, '2024-01-01 00:00:00', '1000'), (1, '2025-01-01 00:00:00', '1100'),
+                (2, '2024-01-01 00:00:00', '0'), (2, '2025-01-01 00:00:00', '60'),
+                (3, '2024-01-01 00:00:00', '0');
+            ",
+        );
+
+        assert_eq!(residual.sub_meters.len(), 1);
+        assert_eq!(residual.residual, dec!(40));
+        assert_eq!(residual.warnings.len(), 1);
+        assert!(residual.warnings[0].contains("sub-meter 'Garten'"));
+    }
+}
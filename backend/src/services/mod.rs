@@ -0,0 +1,12 @@
+pub mod allocation;
+pub mod co2;
+pub mod fuel;
+pub mod heating;
+pub mod hot_water;
+pub mod interpolation;
+pub mod meter_hierarchy;
+pub mod oil_tank;
+pub mod owner;
+pub mod prepayment;
+pub mod rounding;
+pub mod trace;
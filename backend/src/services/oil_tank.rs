@@ -0,0 +1,314 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use log::warn;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::oil_tank::{OilDelivery, OilTank, OilTankReading};
+use crate::schema::{cost_types, oil_deliveries, oil_tank_readings, oil_tanks};
+use crate::services::interpolation::{self, DayWeighting, DegreeDays};
+
+// Litres of one delivery consumed within a period, valued at its purchase price
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsumedLot {
+    pub delivery_id: Option<i32>, // None for stock of unknown origin before the first delivery
+    pub delivery_date: Option<NaiveDate>,
+    pub litres: Decimal,
+    pub price_per_litre: Decimal,
+    pub amount: Decimal,
+}
+
+// Oil consumed from a tank within a period and its FIFO valuation
+#[derive(Debug, Clone, Serialize)]
+pub struct FuelCost {
+    pub tank_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub opening_litres: Decimal,
+    pub delivered_litres: Decimal,
+    pub closing_litres: Decimal,
+    pub consumed_litres: Decimal,
+    pub amount: Decimal,
+    pub estimated: bool, // Tank level at a period boundary interpolated between readings
+    pub lots: Vec<ConsumedLot>,
+    pub warnings: Vec<String>, // Litres valued at a guessed price or not valued at all
+}
+
+// A delivery (or the initial stock) waiting in the FIFO queue
+struct Lot {
+    delivery_id: Option<i32>,
+    delivery_date: Option<NaiveDate>,
+    litres: Decimal,
+    price_per_litre: Decimal,
+}
+
+// Fuel cost of a tank from the beginning of `start_date` to the end of `end_date`:
+// opening stock plus deliveries minus closing stock, valued first in first out.
+// Returns None if the tank has fewer than two level readings.
+pub fn fuel_cost(
+    conn: &mut SqliteConnection,
+    tank: &OilTank,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<Option<FuelCost>, diesel::result::Error> {
+    let tank_id = tank.id.unwrap_or(0);
+    let deliveries = oil_deliveries::table
+        .filter(oil_deliveries::tank_id.eq(tank_id))
+        .order((oil_deliveries::delivery_date.asc(), oil_deliveries::id.asc()))
+        .load::<OilDelivery>(conn)?;
+    let mut readings = oil_tank_readings::table
+        .filter(oil_tank_readings::tank_id.eq(tank_id))
+        .order((oil_tank_readings::reading_date.asc(), oil_tank_readings::id.asc()))
+        .load::<OilTankReading>(conn)?;
+    readings.dedup_by_key(|reading| reading.reading_date);
+    let mut warnings = Vec::new();
+
+    if readings.len() < 2 {
+        warn!("Oil tank '{}' needs at least two level readings", tank.name);
+        return Ok(None);
+    }
+
+    let delivered_before = |date: NaiveDate| -> Decimal {
+        deliveries
+            .iter()
+            .filter(|delivery| delivery.delivery_date < date)
+            .map(|delivery| delivery.litres)
+            .sum()
+    };
+
+    // Cumulative consumption at each reading: everything delivered so far minus the level.
+    // Unlike the level it never drops on deliveries and can be interpolated like a meter.
+    let mut points: Vec<(NaiveDate, Decimal)> = readings
+        .iter()
+        .map(|reading| {
+            (
+                reading.reading_date,
+                delivered_before(reading.reading_date) - reading.litres,
+            )
+        })
+        .collect();
+
+    // Stock found in the tank before any recorded delivery is valued at the first known price
+    let mut lots = Vec::new();
+    let initial_stock = -points
+        .iter()
+        .map(|(_, consumed)| *consumed)
+        .fold(Decimal::ZERO, Decimal::min);
+    if initial_stock > Decimal::ZERO {
+        let message = format!(
+            "Oil tank '{}' holds {:.0} l not covered by recorded deliveries, valued at the first delivery price",
+            tank.name,
+            initial_stock.round_dp(0)
+        );
+        warn!("{}", message);
+        warnings.push(message);
+        lots.push(Lot {
+            delivery_id: None,
+            delivery_date: None,
+            litres: initial_stock,
+            price_per_litre: deliveries.first().map(|d| d.price_per_litre()).unwrap_or_default(),
+        });
+        for (_, consumed) in points.iter_mut() {
+            *consumed += initial_stock;
+        }
+    }
+    lots.extend(deliveries.iter().map(|delivery| Lot {
+        delivery_id: delivery.id,
+        delivery_date: Some(delivery.delivery_date),
+        litres: delivery.litres,
+        price_per_litre: delivery.price_per_litre(),
+    }));
+
+    let Some(period_end) = end_date.succ_opt() else {
+        return Ok(None);
+    };
+
+    // Oil is burned with the heating demand, so levels between readings follow the degree days
+    // of the building the tank's cost type belongs to
+    let tank_building_id = match tank.cost_type_id {
+        Some(cost_type_id) => cost_types::table
+            .filter(cost_types::id.eq(cost_type_id))
+            .select(cost_types::building_id)
+            .first::<i32>(conn)
+            .optional()?,
+        None => None,
+    };
+    let degree_days = match tank_building_id {
+        Some(building_id) => interpolation::load_degree_days(conn, building_id)?,
+        None => DegreeDays::default(),
+    };
+    let weighting = DayWeighting::DegreeDays(degree_days);
+
+    let (Some(consumed_at_start), Some(consumed_at_end)) = (
+        interpolation::interpolate(&points, start_date, &weighting),
+        interpolation::interpolate(&points, period_end, &weighting),
+    ) else {
+        return Ok(None);
+    };
+    let consumed_litres = (consumed_at_end.value - consumed_at_start.value).max(Decimal::ZERO);
+
+    let consumed_lots = take_fifo(&lots, consumed_at_start.value.max(Decimal::ZERO), consumed_litres);
+    let valued_litres: Decimal = consumed_lots.iter().map(|lot| lot.litres).sum();
+    if valued_litres + Decimal::new(5, 1) < consumed_litres {
+        let message = format!(
+            "Oil tank '{}' consumed {:.0} l but deliveries cover only {:.0} l, the rest is not valued",
+            tank.name,
+            consumed_litres.round_dp(0),
+            valued_litres.round_dp(0)
+        );
+        warn!("{}", message);
+        warnings.push(message);
+    }
+
+    let opening_litres = delivered_before(start_date) + initial_stock - consumed_at_start.value;
+    let closing_litres = delivered_before(period_end) + initial_stock - consumed_at_end.value;
+
+    Ok(Some(FuelCost {
+        tank_id,
+        start_date,
+        end_date,
+        opening_litres,
+        delivered_litres: delivered_before(period_end) - delivered_before(start_date),
+        closing_litres,
+        consumed_litres,
+        amount: consumed_lots.iter().map(|lot| lot.amount).sum(),
+        estimated: consumed_at_start.estimated || consumed_at_end.estimated,
+        lots: consumed_lots,
+        warnings,
+    }))
+}
+
+// Fuel cost of each tank assigned to a cost type. Tanks without enough readings are left out
+// and reported in `warnings` together with litres the deliveries don't cover.
+pub fn fuel_costs_for_cost_type(
+    conn: &mut SqliteConnection,
+    cost_type_id: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<FuelCost>, diesel::result::Error> {
+    let tanks = oil_tanks::table
+        .filter(oil_tanks::cost_type_id.eq(cost_type_id))
+        .load::<OilTank>(conn)?;
+
+    let mut costs = Vec::new();
+    for tank in tanks {
+        match fuel_cost(conn, &tank, start_date, end_date)? {
+            Some(cost) => {
+                warnings.extend(cost.warnings.iter().cloned());
+                costs.push(cost);
+            }
+            None => {
+                let message = format!(
+                    "Not enough level readings of oil tank '{}' from {} to {}, its fuel costs are left out",
+                    tank.name, start_date, end_date
+                );
+                warn!("{}", message);
+                warnings.push(message);
+            }
+        }
+    }
+
+    Ok(costs)
+}
+
+// Skip the litres consumed before the period, then take the period's litres from the oldest lots
+fn take_fifo(lots: &[Lot], skip_litres: Decimal, take_litres: Decimal) -> Vec<ConsumedLot> {
+    let mut skip = skip_litres;
+    let mut remaining = take_litres;
+    let mut consumed = Vec::new();
+
+    for lot in lots {
+        if remaining <= Decimal::ZERO {
+            break;
+        }
+        let available = lot.litres - skip.min(lot.litres);
+        skip = (skip - lot.litres).max(Decimal::ZERO);
+        if available <= Decimal::ZERO {
+            continue;
+        }
+
+        let litres = available.min(remaining);
+        remaining -= litres;
+        consumed.push(ConsumedLot {
+            delivery_id: lot.delivery_id,
+            delivery_date: lot.delivery_date,
+            litres,
+            price_per_litre: lot.price_per_litre,
+            amount: litres * lot.price_per_litre,
+        });
+    }
+
+    consumed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::connection::SimpleConnection;
+    use rust_decimal_macros::dec;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn lot(delivery_id: i32, litres: Decimal, price_per_litre: Decimal) -> Lot {
+        Lot {
+            delivery_id: Some(delivery_id),
+            delivery_date: None,
+            litres,
+            price_per_litre,
+        }
+    }
+
+    #[test]
+    fn oldest_oil_is_taken_first() {
+        let lots = [lot(1, dec!(1000), dec!(1.00)), lot(2, dec!(1000), dec!(1.20))];
+        let consumed = take_fifo(&lots, dec!(600), dec!(800));
+
+        assert_eq!(consumed.len(), 2);
+        assert_eq!((consumed[0].delivery_id, consumed[0].litres, consumed[0].amount), (Some(1), dec!(400), dec!(400)));
+        assert_eq!((consumed[1].delivery_id, consumed[1].litres, consumed[1].amount), (Some(2), dec!(400), dec!(480)));
+    }
+
+    #[test]
+    fn period_consumption_is_valued_at_the_delivery_prices() {
+        let conn = &mut crate::db::test_connection();
+        conn.batch_execute(
+            "
+            INSERT INTO oil_tanks (id, name, capacity_litres) VALUES (1, 'Keller', '5000');
+            INSERT INTO oil_deliveries (tank_id, delivery_date, litres, total_price)
+                VALUES (1, '2023-12-01', '1000', '1000'), (1, '2024-06-01', '2000', '2400');
+            INSERT INTO oil_tank_readings (tank_id, reading_date, litres)
+                VALUES (1, '2024-01-01', '1000'), (1, '2025-01-01', '500');
+            ",
+        )
+        .unwrap();
+        let tank = oil_tanks::table.first::<OilTank>(conn).unwrap();
+
+        let cost = fuel_cost(conn, &tank, date(2024, 1, 1), date(2024, 12, 31)).unwrap().unwrap();
+        assert_eq!(cost.opening_litres, dec!(1000));
+        assert_eq!(cost.delivered_litres, dec!(2000));
+        assert_eq!(cost.closing_litres, dec!(500));
+        assert_eq!(cost.consumed_litres, dec!(2500));
+        // 1000 l of the old stock at 1.00 €, 1500 l of the June delivery at 1.20 €
+        assert_eq!(cost.amount, dec!(2800));
+        assert!(!cost.estimated);
+        assert!(cost.warnings.is_empty());
+    }
+
+    #[test]
+    fn tank_with_one_reading_has_no_fuel_cost() {
+        let conn = &mut crate::db::test_connection();
+        conn.batch_execute(
+            "
+            INSERT INTO oil_tanks (id, name, capacity_litres) VALUES (1, 'Keller', '5000');
+            INSERT INTO oil_tank_readings (tank_id, reading_date, litres) VALUES (1, '2024-01-01', '1000');
+            ",
+        )
+        .unwrap();
+        let tank = oil_tanks::table.first::<OilTank>(conn).unwrap();
+
+        assert!(fuel_cost(conn, &tank, date(2024, 1, 1), date(2024, 12, 31)).unwrap().is_none());
+    }
+}
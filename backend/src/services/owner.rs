@@ -0,0 +1,133 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::property_unit::PropertyUnit;
+use crate::services::allocation::{AllocationKey, BillingAllocation, CostPart, LandlordCost, ParticipantKind};
+use crate::services::co2::Co2Split;
+use crate::services::rounding::round_cents;
+
+// One unit's part of a cost line carried by the owner
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerShareLine {
+    pub cost_type_id: i32,
+    pub cost_type_name: String,
+    pub cost_part: Option<CostPart>,
+    pub common_consumption: bool,
+    pub allocation_key: AllocationKey,
+    pub property_unit_id: i32,
+    pub quantity: Decimal,
+    pub amount: Decimal,
+    pub estimated: bool,
+}
+
+// Costs the owner carries for one kind of participant
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerAccount {
+    pub lines: Vec<OwnerShareLine>,
+    pub total: Decimal,
+}
+
+// How the days of a billing period are used in one unit
+#[derive(Debug, Clone, Serialize)]
+pub struct UnitOccupancy {
+    pub property_unit_id: i32,
+    pub name: String,
+    pub period_days: i64,
+    pub tenancy_days: i64, // Days with at least one tenant
+    pub owner_days: i64,   // Days of the owner's own use
+    pub vacancy_days: i64, // Days without tenancy in units not used by the owner
+}
+
+// Costs of a billing period that are not passed on to tenants
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerOverview {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub units: Vec<UnitOccupancy>,
+    pub own_share: OwnerAccount, // Eigenanteil of owner-occupied units
+    pub vacancy: OwnerAccount,   // Leerstand, carried by the landlord
+    pub co2_splits: Vec<Co2Split>,
+    pub co2_landlord_share: Decimal, // Landlord's part of the CO2 costs (CO2KostAufG)
+    pub non_allocable: Vec<LandlordCost>, // Invoices outside BetrKV § 2, e.g. repairs and management
+    pub non_allocable_total: Decimal,
+    pub total: Decimal,
+}
+
+pub fn owner_overview(allocation: &BillingAllocation, units: &[PropertyUnit]) -> OwnerOverview {
+    let own_share = owner_account(allocation, ParticipantKind::Owner);
+    let vacancy = owner_account(allocation, ParticipantKind::Vacancy);
+    let co2_landlord_share = round_cents(allocation.co2_splits.iter().map(|split| split.landlord_amount).sum());
+    let non_allocable_total: Decimal = allocation.landlord_costs.iter().map(|cost| cost.amount).sum();
+
+    OwnerOverview {
+        start_date: allocation.start_date,
+        end_date: allocation.end_date,
+        units: unit_occupancy(allocation, units),
+        total: own_share.total + vacancy.total + co2_landlord_share + non_allocable_total,
+        own_share,
+        vacancy,
+        co2_splits: allocation.co2_splits.clone(),
+        co2_landlord_share,
+        non_allocable: allocation.landlord_costs.clone(),
+        non_allocable_total,
+    }
+}
+
+// Owner and vacancy days per unit; the remaining days of the period are let
+fn unit_occupancy(allocation: &BillingAllocation, units: &[PropertyUnit]) -> Vec<UnitOccupancy> {
+    units
+        .iter()
+        .filter_map(|unit| {
+            let unit_id = unit.id?;
+            let days_of = |kind: ParticipantKind| -> i64 {
+                allocation
+                    .participants
+                    .iter()
+                    .filter(|p| p.kind == kind && p.property_unit_id == unit_id)
+                    .map(|p| p.occupied_days)
+                    .sum()
+            };
+            let owner_days = days_of(ParticipantKind::Owner);
+            let vacancy_days = days_of(ParticipantKind::Vacancy);
+
+            Some(UnitOccupancy {
+                property_unit_id: unit_id,
+                name: unit.name.clone(),
+                period_days: allocation.period_days,
+                tenancy_days: allocation.period_days - owner_days - vacancy_days,
+                owner_days,
+                vacancy_days,
+            })
+        })
+        .collect()
+}
+
+// Shares of all participants of the given kind, one line per cost line and unit
+fn owner_account(allocation: &BillingAllocation, kind: ParticipantKind) -> OwnerAccount {
+    let lines: Vec<OwnerShareLine> = allocation
+        .lines
+        .iter()
+        .flat_map(|line| {
+            line.shares
+                .iter()
+                .filter(move |share| share.kind == kind && !share.amount.is_zero())
+                .map(move |share| OwnerShareLine {
+                    cost_type_id: line.cost_type_id,
+                    cost_type_name: line.cost_type_name.clone(),
+                    cost_part: line.cost_part,
+                    common_consumption: line.common_consumption,
+                    allocation_key: line.allocation_key,
+                    property_unit_id: share.property_unit_id,
+                    quantity: share.quantity,
+                    amount: share.amount,
+                    estimated: share.estimated,
+                })
+        })
+        .collect();
+
+    OwnerAccount {
+        total: lines.iter().map(|line| line.amount).sum(),
+        lines,
+    }
+}
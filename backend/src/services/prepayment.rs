@@ -0,0 +1,160 @@
+use chrono::{Datelike, NaiveDate};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::prepayment::{Prepayment, PrepaymentSchedule};
+use crate::schema::{prepayment_schedules, prepayments};
+use crate::services::rounding::round_cents;
+
+// Where the prepayment amount deducted on a statement comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PrepaymentSource {
+    Received,     // Sum of the recorded payments of the billing period
+    NoneReceived, // No payments recorded, nothing is deducted even if a schedule exists
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrepaymentSummary {
+    pub scheduled_amount: Decimal,
+    pub received_amount: Decimal,
+    pub deducted_amount: Decimal,
+    pub source: PrepaymentSource,
+}
+
+// Summarize the prepayments of a tenant for a billing period.
+// Only recorded payments are deducted; the schedule is reported for comparison.
+pub fn prepayment_summary(
+    conn: &mut SqliteConnection,
+    tenant_id: i32,
+    billing_period_id: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<PrepaymentSummary, diesel::result::Error> {
+    let schedules = prepayment_schedules::table
+        .filter(prepayment_schedules::tenant_id.eq(tenant_id))
+        .load::<PrepaymentSchedule>(conn)?;
+    let scheduled_amount = scheduled_amount(&schedules, start_date, end_date);
+
+    let received = prepayments::table
+        .filter(prepayments::tenant_id.eq(tenant_id))
+        .filter(prepayments::billing_period_id.eq(billing_period_id))
+        .load::<Prepayment>(conn)?;
+    let received_amount: Decimal = received.iter().map(|p| p.amount).sum();
+
+    let source = if received.is_empty() {
+        PrepaymentSource::NoneReceived
+    } else {
+        PrepaymentSource::Received
+    };
+
+    Ok(PrepaymentSummary {
+        scheduled_amount,
+        received_amount,
+        deducted_amount: received_amount,
+        source,
+    })
+}
+
+// Statement line of the balance after the prepayments: Nachzahlung, Guthaben or settled
+pub fn balance_line(total_amount: Decimal, summary: &PrepaymentSummary) -> String {
+    let balance = round_cents(total_amount - summary.deducted_amount);
+    match balance.cmp(&Decimal::ZERO) {
+        std::cmp::Ordering::Greater => format!("Nachzahlung: {:.2} €", balance),
+        std::cmp::Ordering::Less => format!("Guthaben: {:.2} €", -balance),
+        std::cmp::Ordering::Equal => "Ausgeglichen: 0.00 €".to_string(),
+    }
+}
+
+// Sum of the monthly amounts due within the period, rounded to cents.
+// Months only partly within the period are prorated by days; the schedules valid on the
+// first day of the month within the period apply.
+fn scheduled_amount(schedules: &[PrepaymentSchedule], start_date: NaiveDate, end_date: NaiveDate) -> Decimal {
+    let mut total = Decimal::ZERO;
+    let mut month_start = NaiveDate::from_ymd_opt(start_date.year(), start_date.month(), 1).unwrap();
+
+    while month_start <= end_date {
+        let next_month = month_start.checked_add_months(chrono::Months::new(1)).unwrap();
+        let due_date = month_start.max(start_date);
+        let last_day = next_month.pred_opt().unwrap().min(end_date);
+        let monthly_amount: Decimal = schedules
+            .iter()
+            .filter(|schedule| {
+                schedule.valid_from <= due_date && schedule.valid_to.is_none_or(|valid_to| valid_to >= due_date)
+            })
+            .map(|schedule| schedule.monthly_amount)
+            .sum();
+        let days = Decimal::from((last_day - due_date).num_days() + 1);
+        let month_days = Decimal::from((next_month - month_start).num_days());
+        total += monthly_amount * days / month_days;
+        month_start = next_month;
+    }
+
+    round_cents(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::connection::SimpleConnection;
+    use rust_decimal_macros::dec;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn summary(deducted_amount: Decimal) -> PrepaymentSummary {
+        PrepaymentSummary {
+            scheduled_amount: deducted_amount,
+            received_amount: deducted_amount,
+            deducted_amount,
+            source: PrepaymentSource::Received,
+        }
+    }
+
+    #[test]
+    fn received_payments_are_deducted_and_the_schedule_only_reported() {
+        let conn = &mut crate::db::test_connection();
+        conn.batch_execute(
+            "
+            INSERT INTO prepayment_schedules (tenant_id, monthly_amount, valid_from) VALUES (1, '100', '2024-01-01');
+            INSERT INTO prepayments (tenant_id, billing_period_id, amount, received_date)
+                VALUES (1, 1, '500', '2024-03-01'), (1, 1, '250.50', '2024-09-01'), (1, 2, '100', '2025-01-01');
+            ",
+        )
+        .unwrap();
+
+        let paid = prepayment_summary(conn, 1, 1, date(2024, 1, 1), date(2024, 12, 31)).unwrap();
+        assert_eq!(paid.source, PrepaymentSource::Received);
+        assert_eq!(paid.scheduled_amount, dec!(1200));
+        assert_eq!(paid.received_amount, dec!(750.50));
+        assert_eq!(paid.deducted_amount, dec!(750.50));
+
+        let unpaid = prepayment_summary(conn, 2, 1, date(2024, 1, 1), date(2024, 12, 31)).unwrap();
+        assert_eq!(unpaid.source, PrepaymentSource::NoneReceived);
+        assert_eq!(unpaid.deducted_amount, Decimal::ZERO);
+    }
+
+    #[test]
+    fn partial_months_of_the_schedule_are_prorated_by_days() {
+        let schedule = PrepaymentSchedule {
+            id: Some(1),
+            tenant_id: 1,
+            monthly_amount: dec!(100),
+            valid_from: date(2024, 1, 1),
+            valid_to: None,
+            created_at: date(2024, 1, 1).and_hms_opt(0, 0, 0).unwrap(),
+            updated_at: date(2024, 1, 1).and_hms_opt(0, 0, 0).unwrap(),
+        };
+
+        // 16 of 31 days in January, all of February and March
+        assert_eq!(scheduled_amount(&[schedule], date(2024, 1, 16), date(2024, 3, 31)), dec!(251.61));
+    }
+
+    #[test]
+    fn balance_is_a_payment_a_credit_or_settled() {
+        assert_eq!(balance_line(dec!(1000), &summary(dec!(750.50))), "Nachzahlung: 249.50 €");
+        assert_eq!(balance_line(dec!(1000), &summary(dec!(1200))), "Guthaben: 200.00 €");
+        assert_eq!(balance_line(dec!(1000.004), &summary(dec!(1000))), "Ausgeglichen: 0.00 €");
+    }
+}
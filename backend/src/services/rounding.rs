@@ -0,0 +1,111 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+const CENT: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+// Commercial rounding to whole cents, halves away from zero
+pub fn round_cents(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+// Round exact amounts to cents so that they add up to `total` rounded to cents
+// (largest remainder method). Every amount is cut down to whole cents first; the cents
+// still missing go one by one to the amounts with the largest cut-off remainders.
+// On equal remainders the earlier amount is served first.
+pub fn distribute_cents(total: Decimal, amounts: &[Decimal]) -> Vec<Decimal> {
+    // Work on positive values; credits are rounded the same way and negated back
+    let negative = total < Decimal::ZERO;
+    let sign = if negative { Decimal::NEGATIVE_ONE } else { Decimal::ONE };
+    let target = round_cents(total * sign);
+
+    let mut rounded: Vec<Decimal> = amounts
+        .iter()
+        .map(|amount| (amount * sign).round_dp_with_strategy(2, RoundingStrategy::ToZero))
+        .collect();
+    let mut remainders: Vec<(usize, Decimal)> = amounts
+        .iter()
+        .zip(&rounded)
+        .map(|(amount, cut)| amount * sign - cut)
+        .enumerate()
+        .collect();
+
+    let missing = target - rounded.iter().copied().sum::<Decimal>();
+    if missing > Decimal::ZERO {
+        remainders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let mut left = missing;
+        for (index, _) in remainders.iter().cycle() {
+            if left <= Decimal::ZERO {
+                break;
+            }
+            rounded[*index] += CENT;
+            left -= CENT;
+        }
+    } else if missing < Decimal::ZERO {
+        // Amounts adding up to more than the total give back cents, smallest remainders first
+        remainders.sort_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)));
+        let mut left = -missing;
+        for (index, _) in remainders.iter().cycle() {
+            if left <= Decimal::ZERO {
+                break;
+            }
+            rounded[*index] -= CENT;
+            left -= CENT;
+        }
+    }
+
+    rounded.into_iter().map(|amount| amount * sign).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn round_cents_rounds_halves_away_from_zero() {
+        assert_eq!(round_cents(dec!(0.125)), dec!(0.13));
+        assert_eq!(round_cents(dec!(-0.125)), dec!(-0.13));
+        assert_eq!(round_cents(dec!(0.124)), dec!(0.12));
+    }
+
+    #[test]
+    fn distribute_cents_adds_up_to_the_total() {
+        let third = dec!(100) / dec!(3);
+        assert_eq!(
+            distribute_cents(dec!(100), &[third, third, third]),
+            vec![dec!(33.34), dec!(33.33), dec!(33.33)]
+        );
+    }
+
+    #[test]
+    fn distribute_cents_serves_the_largest_remainder_first() {
+        assert_eq!(
+            distribute_cents(dec!(30), &[dec!(10.004), dec!(10.006), dec!(9.99)]),
+            vec![dec!(10.00), dec!(10.01), dec!(9.99)]
+        );
+    }
+
+    #[test]
+    fn distribute_cents_rounds_credits_like_charges() {
+        let third = dec!(-100) / dec!(3);
+        assert_eq!(
+            distribute_cents(dec!(-100), &[third, third, third]),
+            vec![dec!(-33.34), dec!(-33.33), dec!(-33.33)]
+        );
+    }
+
+    #[test]
+    fn distribute_cents_gives_back_cents_over_the_total() {
+        assert_eq!(
+            distribute_cents(dec!(10.01), &[dec!(5.01), dec!(5.01)]),
+            vec![dec!(5.01), dec!(5.00)]
+        );
+    }
+
+    #[test]
+    fn distribute_cents_keeps_exact_amounts() {
+        assert_eq!(
+            distribute_cents(dec!(60), &[dec!(10), dec!(20), dec!(30)]),
+            vec![dec!(10), dec!(20), dec!(30)]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::billing::BillingPeriod;
+use crate::models::building::VacancyPersons;
+use crate::models::tenant::Tenant;
+use crate::services::allocation::{
+    AllocationKey, AppliedTransfer, Apportionment, BillingAllocation, CostPart, LandlordCost, MeterUsage, Participant,
+    TariffSegment, TransferPart,
+};
+use crate::services::co2::Co2Split;
+use crate::services::heating::OilConsumption;
+use crate::services::hot_water::HotWaterSplit;
+use crate::services::interpolation::DegreeDays;
+use crate::services::prepayment::PrepaymentSummary;
+
+// Calculation trace (Rechenweg) of one tenant's statement: every cost line the tenant takes
+// part in with the readings, consumption, key quantities and rounding it is computed from
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementTrace {
+    pub billing_period_id: i32,
+    pub building_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub period_days: i64,
+    pub tenant_id: i32,
+    pub tenant_name: String,
+    pub property_unit_id: i32,
+    pub occupied_days: i64,
+    pub participants: Vec<Participant>, // Everyone sharing the costs, with their key quantities
+    pub vacancy_persons: VacancyPersons, // Persons a vacant unit counts with for the PersonCount key
+    pub vacancy_persons_note: &'static str,
+    pub degree_days: DegreeDays,        // Table of the degree-day weighted items
+    pub items: Vec<ItemTrace>,
+    pub landlord_costs: Vec<LandlordCost>, // Non-allocable invoices of the building, not passed on
+    pub total_amount: Decimal,
+    pub prepayments: PrepaymentSummary,
+    pub balance_amount: Decimal, // Positive: Nachzahlung, negative: Guthaben
+    pub warnings: Vec<String>,   // Problems found while allocating the building's costs
+    pub notes: Vec<String>,      // Estimates and fallbacks the numbers are based on
+}
+
+// Derivation of a single statement item, in the order of the statement
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemTrace {
+    pub position: i32,
+    pub cost_type_id: i32,
+    pub cost_type_name: String,
+    pub cost_part: Option<CostPart>,
+    pub cost_part_percent: Option<Decimal>,
+    pub tariff_segment: Option<TariffSegment>,
+    pub apportionment: Option<Apportionment>,
+    pub common_consumption: bool,
+    pub priced_consumption: Option<Decimal>, // Building consumption multiplied by the tariff
+    pub oil_consumption: Option<OilConsumption>,
+    pub hot_water_split: Option<HotWaterSplit>, // Share of a heating system's fuel costs
+    pub degree_day_weighting: bool,             // Tenancies and meter estimates weighted by degree days
+    pub co2_split: Option<Co2Split>,            // Tier of the fuel's CO2 costs (CO2KostAufG)
+    pub co2_cost: Option<Decimal>,              // Tenant's part of the CO2 costs in the line
+    pub co2_landlord_amount: Option<Decimal>,   // Landlord's part of those taken off the tenant's share
+    pub transfer_in: Option<AppliedTransfer>,   // Costs moved in from another cost type
+    pub transfers_out: Vec<TransferPart>,       // Parts moved out to other cost types
+    pub common_meters: Vec<MeterUsage>,
+    pub key_meters: Vec<MeterUsage>,
+    pub exact_total_amount: Decimal,
+    pub total_amount: Decimal, // Line total after rounding the cost type to cents
+    pub allocation_key: AllocationKey,
+    pub fallback_from: Option<AllocationKey>,
+    pub total_quantity: Decimal,
+    pub tenant_quantity: Decimal,
+    pub share_percent: Decimal, // Tenant quantity in percent of the total quantity
+    pub exact_amount: Decimal,  // Tenant's share of the exact line total
+    pub share_amount: Decimal,
+    pub rounding_difference: Decimal, // Share amount minus exact amount
+    pub estimated: bool,
+}
+
+// Trace the statement of a tenant from the allocation of its billing period.
+// Items are numbered like the statement items generated from the same allocation.
+pub fn statement_trace(
+    allocation: &BillingAllocation,
+    billing_period: &BillingPeriod,
+    tenant: &Tenant,
+    prepayments: PrepaymentSummary,
+) -> StatementTrace {
+    let tenant_id = tenant.id.unwrap_or(0);
+    let occupied_days = allocation
+        .participants
+        .iter()
+        .filter(|participant| participant.tenant_id == Some(tenant_id))
+        .map(|participant| participant.occupied_days)
+        .sum();
+
+    let items: Vec<ItemTrace> = allocation
+        .lines
+        .iter()
+        .filter_map(|line| line.share_for(tenant_id).map(|share| (line, share)))
+        .enumerate()
+        .map(|(position, (line, share))| {
+            let share_percent = if line.total_quantity.is_zero() {
+                Decimal::ZERO
+            } else {
+                share.quantity * Decimal::ONE_HUNDRED / line.total_quantity
+            };
+
+            let co2 = line.co2_for(share);
+
+            ItemTrace {
+                position: position as i32,
+                cost_type_id: line.cost_type_id,
+                cost_type_name: line.cost_type_name.clone(),
+                cost_part: line.cost_part,
+                cost_part_percent: line.cost_part_percent,
+                tariff_segment: line.tariff_segment.clone(),
+                apportionment: line.apportionment.clone(),
+                common_consumption: line.common_consumption,
+                priced_consumption: line.priced_consumption,
+                oil_consumption: line.oil_consumption.clone(),
+                hot_water_split: line.hot_water_split.clone(),
+                degree_day_weighting: line.degree_day_weighting,
+                co2_split: line.co2_split.clone(),
+                co2_cost: co2.map(|(co2_cost, _)| co2_cost),
+                co2_landlord_amount: co2.map(|(_, landlord_amount)| landlord_amount),
+                transfer_in: line.transfer_in.clone(),
+                transfers_out: line.transfers_out.clone(),
+                common_meters: line.common_meters.clone(),
+                key_meters: line.key_meters.clone(),
+                exact_total_amount: line.exact_total_amount,
+                total_amount: line.total_amount,
+                allocation_key: line.allocation_key,
+                fallback_from: line.fallback_from,
+                total_quantity: line.total_quantity,
+                tenant_quantity: share.quantity,
+                share_percent,
+                exact_amount: share.exact_amount,
+                share_amount: share.amount,
+                rounding_difference: share.amount - share.exact_amount,
+                estimated: share.estimated,
+            }
+        })
+        .collect();
+
+    let total_amount: Decimal = items.iter().map(|item| item.share_amount).sum();
+    let balance_amount = total_amount - prepayments.deducted_amount;
+
+    StatementTrace {
+        billing_period_id: billing_period.id.unwrap_or(0),
+        building_id: billing_period.building_id,
+        start_date: allocation.start_date,
+        end_date: allocation.end_date,
+        period_days: allocation.period_days,
+        tenant_id,
+        tenant_name: tenant.name.clone(),
+        property_unit_id: tenant.property_unit_id,
+        occupied_days,
+        participants: allocation.participants.clone(),
+        vacancy_persons: allocation.vacancy_persons,
+        vacancy_persons_note: allocation.vacancy_persons.label(),
+        degree_days: allocation.degree_days,
+        items,
+        landlord_costs: allocation.landlord_costs.clone(),
+        total_amount,
+        prepayments,
+        balance_amount,
+        warnings: allocation.warnings.clone(),
+        notes: allocation.notes.clone(),
+    }
+}
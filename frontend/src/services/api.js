@@ -10,11 +10,33 @@ export const apiClient = axios.create({
     timeout: 10000
 });
 
+// Buildings API Service
+export const buildingService = {
+    getAll() {
+        return apiClient.get('/buildings');
+    },
+    get(id) {
+        return apiClient.get(`/buildings/${id}`);
+    },
+    create(data) {
+        return apiClient.post('/buildings', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/buildings/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/buildings/${id}`);
+    }
+};
+
 // Property Units API Service
 export const propertyUnitService = {
     getAll() {
         return apiClient.get('/property-units');
     },
+    getByBuilding(buildingId) {
+        return apiClient.get('/property-units', { params: { building_id: buildingId } });
+    },
     get(id) {
         return apiClient.get(`/property-units/${id}`);
     },
@@ -40,6 +62,9 @@ export const tenantService = {
     getByPropertyUnit(propertyUnitId) {
         return apiClient.get(`/tenants/by-property-unit/${propertyUnitId}`);
     },
+    getByBuilding(buildingId) {
+        return apiClient.get('/tenants', { params: { building_id: buildingId } });
+    },
     create(data) {
         return apiClient.post('/tenants', data);
     },
@@ -65,6 +90,11 @@ export const meterService = {
     getCommon() {
         return apiClient.get('/meters/common');
     },
+    getResidual(id, startDate, endDate) {
+        return apiClient.get(`/meters/${id}/residual`, {
+            params: { start_date: startDate, end_date: endDate }
+        });
+    },
     create(data) {
         return apiClient.post('/meters', data);
     },
@@ -73,6 +103,17 @@ export const meterService = {
     },
     delete(id) {
         return apiClient.delete(`/meters/${id}`);
+    },
+    getCostTypes(id) {
+        return apiClient.get(`/meters/${id}/cost-types`);
+    },
+    bindCostType(id, costTypeId, conversionFactor = null) {
+        return apiClient.put(`/meters/${id}/cost-types/${costTypeId}`, {
+            conversion_factor: conversionFactor
+        });
+    },
+    unbindCostType(id, costTypeId) {
+        return apiClient.delete(`/meters/${id}/cost-types/${costTypeId}`);
     }
 };
 
@@ -90,8 +131,9 @@ export const meterReadingService = {
     getByDateRange(meterId, startDate, endDate) {
         return apiClient.get(`/meter-readings/by-date-range/${meterId}/${startDate}/${endDate}`);
     },
-    getConsumption(meterId) {
-        return apiClient.get(`/meter-readings/consumption/${meterId}`);
+    getConsumption(meterId, startDate = null, endDate = null) {
+        const params = startDate && endDate ? { start_date: startDate, end_date: endDate } : {};
+        return apiClient.get(`/meter-readings/consumption/${meterId}`, { params });
     },
     create(data) {
         return apiClient.post('/meter-readings', data);
@@ -124,6 +166,9 @@ export const costTypeService = {
     getAllocationMethods() {
         return apiClient.get('/cost-types/allocation-methods');
     },
+    getBetrkvCategories() {
+        return apiClient.get('/cost-types/betrkv-categories');
+    },
     assignAllocationMethod(costTypeId, methodId) {
         return apiClient.post(`/cost-types/${costTypeId}/allocation-methods/${methodId}`);
     },
@@ -163,3 +208,190 @@ export const fixedCostService = {
         return apiClient.delete(`/fixed-costs/${id}`);
     }
 };
+
+export const costTransferService = {
+    getAll() {
+        return apiClient.get('/cost-transfers');
+    },
+    getByBuilding(buildingId) {
+        return apiClient.get('/cost-transfers', { params: { building_id: buildingId } });
+    },
+    get(id) {
+        return apiClient.get(`/cost-transfers/${id}`);
+    },
+    create(data) {
+        return apiClient.post('/cost-transfers', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/cost-transfers/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/cost-transfers/${id}`);
+    }
+};
+
+// Prepayment Schedules API Service
+export const prepaymentScheduleService = {
+    getByTenant(tenantId) {
+        return apiClient.get(`/prepayment-schedules/tenant/${tenantId}`);
+    },
+    create(data) {
+        return apiClient.post('/prepayment-schedules', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/prepayment-schedules/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/prepayment-schedules/${id}`);
+    }
+};
+
+// Received Prepayments API Service
+export const prepaymentService = {
+    getByTenant(tenantId) {
+        return apiClient.get(`/prepayments/tenant/${tenantId}`);
+    },
+    getByBillingPeriod(billingPeriodId) {
+        return apiClient.get(`/prepayments/billing-period/${billingPeriodId}`);
+    },
+    create(data) {
+        return apiClient.post('/prepayments', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/prepayments/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/prepayments/${id}`);
+    }
+};
+
+// Burner Calibrations API Service
+export const burnerCalibrationService = {
+    getAll() {
+        return apiClient.get('/burner-calibrations');
+    },
+    create(data) {
+        return apiClient.post('/burner-calibrations', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/burner-calibrations/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/burner-calibrations/${id}`);
+    }
+};
+
+// Heating Systems API Service
+export const heatingSystemService = {
+    getAll() {
+        return apiClient.get('/heating-systems');
+    },
+    getById(id) {
+        return apiClient.get(`/heating-systems/${id}`);
+    },
+    getEnergy(id, startDate, endDate) {
+        return apiClient.get(`/heating-systems/${id}/energy`, {
+            params: { start_date: startDate, end_date: endDate }
+        });
+    },
+    create(data) {
+        return apiClient.post('/heating-systems', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/heating-systems/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/heating-systems/${id}`);
+    }
+};
+
+// Heating System Fuels API Service
+export const heatingSystemFuelService = {
+    getFuelTypes() {
+        return apiClient.get('/heating-system-fuels/fuel-types');
+    },
+    getByHeatingSystem(heatingSystemId) {
+        return apiClient.get('/heating-system-fuels', { params: { heating_system_id: heatingSystemId } });
+    },
+    create(data) {
+        return apiClient.post('/heating-system-fuels', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/heating-system-fuels/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/heating-system-fuels/${id}`);
+    }
+};
+
+// Heating API Service
+export const heatingService = {
+    getOilConsumption(startDate, endDate) {
+        return apiClient.get('/heating/oil-consumption', {
+            params: { start_date: startDate, end_date: endDate }
+        });
+    },
+    getDegreeDays() {
+        return apiClient.get('/heating/degree-days');
+    },
+    setDegreeDays(monthlyPerMille) {
+        return apiClient.put('/heating/degree-days', { monthly_per_mille: monthlyPerMille });
+    },
+    resetDegreeDays() {
+        return apiClient.delete('/heating/degree-days');
+    }
+};
+
+// Oil Tank API Service
+export const oilTankService = {
+    getAll() {
+        return apiClient.get('/oil-tanks');
+    },
+    getById(id) {
+        return apiClient.get(`/oil-tanks/${id}`);
+    },
+    create(data) {
+        return apiClient.post('/oil-tanks', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/oil-tanks/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/oil-tanks/${id}`);
+    },
+    getFuelCost(id, billingPeriodId) {
+        return apiClient.get(`/oil-tanks/${id}/fuel-cost/${billingPeriodId}`);
+    }
+};
+
+// Oil Delivery API Service
+export const oilDeliveryService = {
+    getByTank(tankId) {
+        return apiClient.get(`/oil-deliveries/tank/${tankId}`);
+    },
+    create(data) {
+        return apiClient.post('/oil-deliveries', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/oil-deliveries/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/oil-deliveries/${id}`);
+    }
+};
+
+// Oil Tank Reading API Service
+export const oilTankReadingService = {
+    getByTank(tankId) {
+        return apiClient.get(`/oil-tank-readings/tank/${tankId}`);
+    },
+    create(data) {
+        return apiClient.post('/oil-tank-readings', data);
+    },
+    update(id, data) {
+        return apiClient.put(`/oil-tank-readings/${id}`, data);
+    },
+    delete(id) {
+        return apiClient.delete(`/oil-tank-readings/${id}`);
+    }
+};
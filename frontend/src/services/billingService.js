@@ -26,6 +26,27 @@ export default {
     deleteBillingPeriod(id) {
         return api.delete(`/billing-periods/${id}`);
     },
+
+    // Get the costs carried by the owner for a period
+    getOwnerOverview(id) {
+        return api.get(`/billing-periods/${id}/owner-overview`);
+    },
+
+    // Start generating the statements of all tenants of a period as a background job
+    generatePeriodStatements(billingPeriodId) {
+        return api.post(`/billing-periods/${billingPeriodId}/generate-statements`);
+    },
+
+    // Get the progress and outcome of a batch generation job
+    getBillingJob(id) {
+        return api.get(`/billing-jobs/${id}`);
+    },
+
+    // Get the batch generation jobs of a period, newest first
+    getBillingPeriodJobs(billingPeriodId) {
+        return api.get(`/billing-periods/${billingPeriodId}/billing-jobs`);
+    },
+
     // Generate a statement for a tenant and period
     generateStatement(billingPeriodId, tenantId) {
         return api.post('/billing-statements/generate', {
@@ -34,11 +55,29 @@ export default {
         });
     },
 
+    // Calculate the statements of a period without storing them; all tenants if none is given
+    previewStatements(billingPeriodId, tenantId = null) {
+        return api.post('/billing-statements/preview', {
+            billing_period_id: billingPeriodId,
+            tenant_id: tenantId
+        });
+    },
+
     // Get a statement by ID
     getStatement(id) {
         return api.get(`/billing-statements/${id}`);
     },
 
+    // Get the itemized lines of a statement
+    getStatementItems(id) {
+        return api.get(`/billing-statements/${id}/items`);
+    },
+
+    // Get the calculation trace stored with a statement
+    getStatementTrace(id) {
+        return api.get(`/billing-statements/${id}/trace`);
+    },
+
     // Get statements for a tenant
     getTenantStatements(tenantId) {
         return api.get(`/billing-statements/tenant/${tenantId}`);